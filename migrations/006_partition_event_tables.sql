@@ -0,0 +1,159 @@
+-- 将事件表 (swap_events / mint_events / burn_events) 转换为按月分区的表
+-- 分区命名规则: <table>_pYYYYMM，另有 <table>_default 兜底分区
+-- 分区表的主键/唯一约束必须包含分区键，因此唯一约束改为 (chain_id, transaction_hash, log_index, timestamp)
+
+CREATE OR REPLACE FUNCTION ensure_monthly_partition(parent TEXT, ts TIMESTAMPTZ)
+RETURNS TEXT AS $$
+DECLARE
+    month_start TIMESTAMPTZ := date_trunc('month', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    month_end TIMESTAMPTZ := month_start + INTERVAL '1 month';
+    partition_name TEXT := format('%s_p%s', parent, to_char(month_start AT TIME ZONE 'UTC', 'YYYYMM'));
+    default_name TEXT := format('%s_default', parent);
+    has_rows BOOLEAN := FALSE;
+BEGIN
+    IF to_regclass(partition_name) IS NOT NULL THEN
+        RETURN partition_name;
+    END IF;
+
+    IF to_regclass(default_name) IS NOT NULL THEN
+        EXECUTE format(
+            'SELECT EXISTS (SELECT 1 FROM %I WHERE timestamp >= %L AND timestamp < %L)',
+            default_name, month_start, month_end
+        ) INTO has_rows;
+    END IF;
+
+    IF has_rows THEN
+        -- 默认分区中已有该月数据：先建独立表并迁移数据，再挂载为分区
+        EXECUTE format('CREATE TABLE %I (LIKE %I INCLUDING DEFAULTS INCLUDING CONSTRAINTS)', partition_name, parent);
+        EXECUTE format(
+            'WITH moved AS (DELETE FROM %I WHERE timestamp >= %L AND timestamp < %L RETURNING *) INSERT INTO %I SELECT * FROM moved',
+            default_name, month_start, month_end, partition_name
+        );
+        EXECUTE format(
+            'ALTER TABLE %I ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+            parent, partition_name, month_start, month_end
+        );
+    ELSE
+        EXECUTE format(
+            'CREATE TABLE IF NOT EXISTS %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+            partition_name, parent, month_start, month_end
+        );
+    END IF;
+
+    RETURN partition_name;
+END;
+$$ LANGUAGE plpgsql;
+
+BEGIN;
+
+-- ============================================================================
+-- swap_events
+-- ============================================================================
+ALTER TABLE IF EXISTS swap_events RENAME TO swap_events_legacy;
+
+CREATE TABLE swap_events (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    sender VARCHAR(42) NOT NULL,
+    amount0_in DECIMAL(78, 0) NOT NULL,
+    amount1_in DECIMAL(78, 0) NOT NULL,
+    amount0_out DECIMAL(78, 0) NOT NULL,
+    amount1_out DECIMAL(78, 0) NOT NULL,
+    to_address VARCHAR(42) NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index INTEGER NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (id, timestamp),
+    UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+) PARTITION BY RANGE (timestamp);
+
+CREATE TABLE swap_events_default PARTITION OF swap_events DEFAULT;
+
+SELECT ensure_monthly_partition('swap_events', month)
+FROM (SELECT DISTINCT date_trunc('month', timestamp) AS month FROM swap_events_legacy) months;
+
+INSERT INTO swap_events
+    (id, chain_id, pair_address, sender, amount0_in, amount1_in, amount0_out, amount1_out,
+     to_address, block_number, transaction_hash, log_index, timestamp)
+SELECT id, chain_id, pair_address, sender, amount0_in, amount1_in, amount0_out, amount1_out,
+       to_address, block_number, transaction_hash, log_index, timestamp
+FROM swap_events_legacy;
+
+DROP TABLE swap_events_legacy;
+
+-- ============================================================================
+-- mint_events
+-- ============================================================================
+ALTER TABLE IF EXISTS mint_events RENAME TO mint_events_legacy;
+
+CREATE TABLE mint_events (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    sender VARCHAR(42) NOT NULL,
+    amount0 DECIMAL(78, 0) NOT NULL,
+    amount1 DECIMAL(78, 0) NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index INTEGER NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, timestamp),
+    UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+) PARTITION BY RANGE (timestamp);
+
+CREATE TABLE mint_events_default PARTITION OF mint_events DEFAULT;
+
+SELECT ensure_monthly_partition('mint_events', month)
+FROM (SELECT DISTINCT date_trunc('month', timestamp) AS month FROM mint_events_legacy) months;
+
+INSERT INTO mint_events
+    (id, chain_id, pair_address, sender, amount0, amount1, block_number, transaction_hash, log_index, timestamp)
+SELECT id, chain_id, pair_address, sender, amount0, amount1, block_number, transaction_hash, log_index, timestamp
+FROM mint_events_legacy;
+
+DROP TABLE mint_events_legacy;
+
+-- ============================================================================
+-- burn_events
+-- ============================================================================
+ALTER TABLE IF EXISTS burn_events RENAME TO burn_events_legacy;
+
+CREATE TABLE burn_events (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    sender VARCHAR(42) NOT NULL,
+    amount0 DECIMAL(78, 0) NOT NULL,
+    amount1 DECIMAL(78, 0) NOT NULL,
+    to_address VARCHAR(42) NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index INTEGER NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (id, timestamp),
+    UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+) PARTITION BY RANGE (timestamp);
+
+CREATE TABLE burn_events_default PARTITION OF burn_events DEFAULT;
+
+SELECT ensure_monthly_partition('burn_events', month)
+FROM (SELECT DISTINCT date_trunc('month', timestamp) AS month FROM burn_events_legacy) months;
+
+INSERT INTO burn_events
+    (id, chain_id, pair_address, sender, amount0, amount1, to_address, block_number, transaction_hash, log_index, timestamp)
+SELECT id, chain_id, pair_address, sender, amount0, amount1, to_address, block_number, transaction_hash, log_index, timestamp
+FROM burn_events_legacy;
+
+DROP TABLE burn_events_legacy;
+
+-- 分区表上的索引会自动应用到所有分区
+CREATE INDEX IF NOT EXISTS idx_swap_events_pair ON swap_events(chain_id, pair_address);
+CREATE INDEX IF NOT EXISTS idx_swap_events_pair_timestamp ON swap_events(chain_id, pair_address, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_swap_events_sender ON swap_events(chain_id, sender);
+CREATE INDEX IF NOT EXISTS idx_swap_events_to ON swap_events(chain_id, to_address);
+CREATE INDEX IF NOT EXISTS idx_mint_events_chain_pair ON mint_events(chain_id, pair_address);
+CREATE INDEX IF NOT EXISTS idx_burn_events_chain_pair ON burn_events(chain_id, pair_address);
+
+COMMIT;
@@ -0,0 +1,47 @@
+-- 预聚合K线：按周期分区的 pair_candles 表，由事件监听器在写入 swap 时增量维护
+-- 升级后已有的 swap 历史由维护服务 (MaintenanceService) 自动回填
+
+CREATE OR REPLACE FUNCTION candle_bucket(period TEXT, ts TIMESTAMPTZ)
+RETURNS TIMESTAMPTZ AS $$
+    SELECT CASE period
+        WHEN '1m' THEN date_bin('1 minute', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '5m' THEN date_bin('5 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '15m' THEN date_bin('15 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '30m' THEN date_bin('30 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '1h' THEN date_bin('1 hour', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '4h' THEN date_bin('4 hours', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '1d' THEN date_bin('1 day', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '1w' THEN date_bin('7 days', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+        WHEN '1M' THEN date_trunc('month', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+        WHEN '1y' THEN date_trunc('year', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+    END
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE TABLE IF NOT EXISTS pair_candles (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    period VARCHAR(3) NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open DECIMAL NOT NULL,
+    high DECIMAL NOT NULL,
+    low DECIMAL NOT NULL,
+    close DECIMAL NOT NULL,
+    volume0 DECIMAL NOT NULL DEFAULT 0,
+    volume1 DECIMAL NOT NULL DEFAULT 0,
+    trade_count BIGINT NOT NULL DEFAULT 0,
+    first_seq BIGINT NOT NULL,
+    last_seq BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address, period, bucket_start)
+) PARTITION BY LIST (period);
+
+CREATE TABLE IF NOT EXISTS pair_candles_1m PARTITION OF pair_candles FOR VALUES IN ('1m');
+CREATE TABLE IF NOT EXISTS pair_candles_5m PARTITION OF pair_candles FOR VALUES IN ('5m');
+CREATE TABLE IF NOT EXISTS pair_candles_15m PARTITION OF pair_candles FOR VALUES IN ('15m');
+CREATE TABLE IF NOT EXISTS pair_candles_30m PARTITION OF pair_candles FOR VALUES IN ('30m');
+CREATE TABLE IF NOT EXISTS pair_candles_1h PARTITION OF pair_candles FOR VALUES IN ('1h');
+CREATE TABLE IF NOT EXISTS pair_candles_4h PARTITION OF pair_candles FOR VALUES IN ('4h');
+CREATE TABLE IF NOT EXISTS pair_candles_1d PARTITION OF pair_candles FOR VALUES IN ('1d');
+CREATE TABLE IF NOT EXISTS pair_candles_1w PARTITION OF pair_candles FOR VALUES IN ('1w');
+CREATE TABLE IF NOT EXISTS pair_candles_1mo PARTITION OF pair_candles FOR VALUES IN ('1M');
+CREATE TABLE IF NOT EXISTS pair_candles_1y PARTITION OF pair_candles FOR VALUES IN ('1y');
@@ -0,0 +1,42 @@
+-- swap_events 增加按代币精度归一化的数量、双向成交价和买卖方向
+-- amount0 / amount1: (amount_in + amount_out) / 10^decimals
+-- price0: token0 以 token1 计价；price1: token1 以 token0 计价
+-- side: buy = 用 token0 买入 token1，sell = 用 token1 买入 token0
+
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS amount0 DECIMAL;
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS amount1 DECIMAL;
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS price0 DECIMAL;
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS price1 DECIMAL;
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS side VARCHAR(4);
+
+BEGIN;
+
+WITH scales AS (
+    SELECT chain_id, address,
+           power(10::numeric, COALESCE(token0_decimals, 18)) AS scale0,
+           power(10::numeric, COALESCE(token1_decimals, 18)) AS scale1
+    FROM trading_pairs
+)
+UPDATE swap_events se
+-- 限制在 28 位小数以内，保证能解码为 rust_decimal::Decimal
+SET amount0 = trim_scale(round((se.amount0_in + se.amount0_out) / s.scale0, 28)),
+    amount1 = trim_scale(round((se.amount1_in + se.amount1_out) / s.scale1, 28)),
+    price0 = trim_scale(round(CASE
+        WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN (se.amount1_out * s.scale0) / (se.amount0_in * s.scale1)
+        WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN (se.amount1_in * s.scale0) / (se.amount0_out * s.scale1)
+    END, 28)),
+    price1 = trim_scale(round(CASE
+        WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN (se.amount0_in * s.scale1) / (se.amount1_out * s.scale0)
+        WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN (se.amount0_out * s.scale1) / (se.amount1_in * s.scale0)
+    END, 28)),
+    side = CASE
+        WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 'buy'
+        WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 'sell'
+    END
+FROM scales s
+WHERE s.chain_id = se.chain_id AND s.address = se.pair_address AND se.amount0 IS NULL;
+
+-- K线改为使用归一化价格，清空后由维护服务从原始事件重建
+TRUNCATE pair_candles;
+
+COMMIT;
@@ -0,0 +1,25 @@
+-- 交易对计价方向：trading_pairs.quote_side 记录计价代币 (0 = token0, 1 = token1)
+-- quote_tokens 由事件服务启动时根据 <PREFIX>_QUOTE_TOKENS 配置同步，并据此刷新所有交易对的 quote_side
+
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS quote_side INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS quote_tokens (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    priority INTEGER NOT NULL,
+    PRIMARY KEY (chain_id, token_address)
+);
+
+CREATE OR REPLACE FUNCTION pair_quote_side(p_chain_id INTEGER, p_token0 TEXT, p_token1 TEXT)
+RETURNS INTEGER AS $$
+    SELECT CASE
+        WHEN q1.priority IS NOT NULL AND (q0.priority IS NULL OR q1.priority < q0.priority) THEN 1
+        ELSE 0
+    END
+    FROM (SELECT
+        (SELECT priority FROM quote_tokens WHERE chain_id = p_chain_id AND token_address = lower(p_token0)) AS priority
+    ) q0,
+    (SELECT
+        (SELECT priority FROM quote_tokens WHERE chain_id = p_chain_id AND token_address = lower(p_token1)) AS priority
+    ) q1
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,61 @@
+-- USD 估值：交易对储备量、代币美元价格，以及事件上的美元成交量/价值
+-- token_usd_prices 由估值服务定期从稳定币 (<PREFIX>_STABLE_TOKENS) 和 token_prices 锚点出发，沿交易对图推导
+-- volume_usd / value_usd 在写入时计算，估值服务会补算最近 VALUATION_BACKFILL_DAYS 天内价格尚未就绪的记录
+
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS volume_usd DECIMAL;
+ALTER TABLE mint_events ADD COLUMN IF NOT EXISTS value_usd DECIMAL;
+ALTER TABLE burn_events ADD COLUMN IF NOT EXISTS value_usd DECIMAL;
+
+CREATE TABLE IF NOT EXISTS pair_reserves (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    reserve0 DECIMAL(78, 0) NOT NULL,
+    reserve1 DECIMAL(78, 0) NOT NULL,
+    reserve_usd DECIMAL,
+    block_number BIGINT NOT NULL,
+    log_index INTEGER NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address)
+);
+
+CREATE TABLE IF NOT EXISTS token_usd_prices (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    price_usd DECIMAL NOT NULL,
+    source VARCHAR(10) NOT NULL,
+    via_pair VARCHAR(42),
+    hops INTEGER NOT NULL,
+    liquidity_usd DECIMAL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, token_address)
+);
+
+CREATE OR REPLACE FUNCTION pair_liquidity_usd(p_chain_id INTEGER, p_pair TEXT, p_raw0 NUMERIC, p_raw1 NUMERIC)
+RETURNS NUMERIC AS $$
+    SELECT trim_scale(round(CASE
+        WHEN u0.price_usd IS NOT NULL AND u1.price_usd IS NOT NULL
+            THEN p_raw0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) * u0.price_usd
+               + p_raw1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) * u1.price_usd
+        WHEN u0.price_usd IS NOT NULL
+            THEN 2 * p_raw0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) * u0.price_usd
+        WHEN u1.price_usd IS NOT NULL
+            THEN 2 * p_raw1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) * u1.price_usd
+    END, 28))
+    FROM trading_pairs tp
+    LEFT JOIN token_usd_prices u0 ON u0.chain_id = tp.chain_id AND u0.token_address = lower(tp.token0)
+    LEFT JOIN token_usd_prices u1 ON u1.chain_id = tp.chain_id AND u1.token_address = lower(tp.token1)
+    WHERE tp.chain_id = p_chain_id AND tp.address = p_pair
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION swap_volume_usd(p_chain_id INTEGER, p_pair TEXT, p_amount0 NUMERIC, p_amount1 NUMERIC)
+RETURNS NUMERIC AS $$
+    SELECT trim_scale(round(CASE
+        WHEN u0.price_usd IS NOT NULL AND (u1.price_usd IS NULL OR u0.hops <= u1.hops)
+            THEN p_amount0 * u0.price_usd
+        ELSE p_amount1 * u1.price_usd
+    END, 28))
+    FROM trading_pairs tp
+    LEFT JOIN token_usd_prices u0 ON u0.chain_id = tp.chain_id AND u0.token_address = lower(tp.token0)
+    LEFT JOIN token_usd_prices u1 ON u1.chain_id = tp.chain_id AND u1.token_address = lower(tp.token1)
+    WHERE tp.chain_id = p_chain_id AND tp.address = p_pair
+$$ LANGUAGE sql STABLE;
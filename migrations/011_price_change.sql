@@ -0,0 +1,32 @@
+-- 基于 1m K线的价格涨跌幅计算：参考价取窗口起点之前最后一根已收盘K线的收盘价，
+-- 窗口内没有成交时沿用更早的价格，窗口起点时尚未成交的交易对取首根K线的开盘价
+
+CREATE OR REPLACE FUNCTION pair_price_at(p_chain_id INTEGER, p_pair TEXT, p_ts TIMESTAMPTZ)
+RETURNS NUMERIC AS $$
+    SELECT COALESCE(
+        (SELECT close FROM pair_candles
+         WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+           AND bucket_start <= p_ts - INTERVAL '1 minute'
+         ORDER BY bucket_start DESC LIMIT 1),
+        (SELECT open FROM pair_candles
+         WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+           AND bucket_start > p_ts - INTERVAL '1 minute'
+         ORDER BY bucket_start ASC LIMIT 1)
+    )
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION pair_price_change(p_chain_id INTEGER, p_pair TEXT, p_window INTERVAL, p_quote_side INTEGER)
+RETURNS NUMERIC AS $$
+    SELECT CASE WHEN cur > 0 AND ref > 0 THEN
+        trim_scale(round(CASE WHEN p_quote_side = 0
+            THEN (cur - ref) / ref
+            ELSE (ref - cur) / cur
+        END * 100, 8))
+    END
+    FROM (SELECT
+        (SELECT close FROM pair_candles
+         WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+         ORDER BY bucket_start DESC LIMIT 1) AS cur,
+        pair_price_at(p_chain_id, p_pair, NOW() - p_window) AS ref
+    ) p
+$$ LANGUAGE sql STABLE;
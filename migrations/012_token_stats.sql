@@ -0,0 +1,76 @@
+-- 代币维度聚合：按代币（而非交易对）汇总其参与的所有交易对的成交量、流动性、成交笔数和 TVL 加权美元价格
+-- 旧的 token_stats_mv / pair_stats_mv 定义引用了不存在的列且从未创建，这里重建 token_stats_mv 并移除 pair_stats_mv
+-- 视图由估值服务每轮估值后 REFRESH CONCURRENTLY
+
+DROP MATERIALIZED VIEW IF EXISTS pair_stats_mv;
+DROP MATERIALIZED VIEW IF EXISTS token_stats_mv;
+
+CREATE MATERIALIZED VIEW token_stats_mv AS
+WITH sides AS (
+    SELECT tp.chain_id, tp.address AS pair_address, tp.created_at,
+           lower(tp.token0) AS token_address, lower(tp.token1) AS other_token,
+           tp.token0_symbol AS symbol, tp.token0_name AS name, tp.token0_decimals AS decimals,
+           tp.token1_decimals AS other_decimals,
+           pr.reserve0 AS reserve, pr.reserve1 AS other_reserve, pr.reserve_usd,
+           1 AS price_side
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+    UNION ALL
+    SELECT tp.chain_id, tp.address, tp.created_at,
+           lower(tp.token1), lower(tp.token0),
+           tp.token1_symbol, tp.token1_name, tp.token1_decimals,
+           tp.token0_decimals,
+           pr.reserve1, pr.reserve0, pr.reserve_usd,
+           0
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+),
+pools AS (
+    SELECT s.*,
+           CASE WHEN s.reserve > 0 AND s.other_reserve > 0 THEN
+               u.price_usd * (s.other_reserve / power(10::numeric, COALESCE(s.other_decimals, 18)))
+                           / (s.reserve / power(10::numeric, COALESCE(s.decimals, 18)))
+           END AS pool_price_usd,
+           v.volume_1h, v.volume_24h, v.trade_count_24h
+    FROM sides s
+    LEFT JOIN token_usd_prices u ON u.chain_id = s.chain_id AND u.token_address = s.other_token
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(SUM(se.volume_usd) FILTER (WHERE se.timestamp >= NOW() - INTERVAL '1 hour'), 0) AS volume_1h,
+               COALESCE(SUM(se.volume_usd), 0) AS volume_24h,
+               COUNT(*) AS trade_count_24h
+        FROM swap_events se
+        WHERE se.chain_id = s.chain_id AND se.pair_address = s.pair_address
+          AND se.timestamp >= NOW() - INTERVAL '24 hours'
+    ) v
+),
+tokens AS (
+    SELECT chain_id, token_address,
+           (array_agg(symbol ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE symbol IS NOT NULL))[1] AS symbol,
+           (array_agg(name ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           MAX(decimals) AS decimals,
+           COUNT(*)::INTEGER AS pair_count,
+           MIN(created_at) AS listed_at,
+           COALESCE(SUM(reserve_usd), 0) AS liquidity_usd,
+           SUM(volume_1h) AS volume_1h_usd,
+           SUM(volume_24h) AS volume_24h_usd,
+           SUM(trade_count_24h) AS trade_count_24h,
+           SUM(pool_price_usd * reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0)
+               / NULLIF(SUM(reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0), 0)
+               AS weighted_price_usd,
+           (array_agg(pair_address ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_pair,
+           (array_agg(price_side ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_side
+    FROM pools
+    GROUP BY chain_id, token_address
+)
+SELECT t.chain_id, t.token_address, t.symbol, t.name, t.decimals,
+       t.pair_count, t.listed_at, t.main_pair,
+       trim_scale(round(COALESCE(t.weighted_price_usd, u.price_usd), 28)) AS price_usd,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '1 hour', t.main_side) AS price_change_1h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '24 hours', t.main_side) AS price_change_24h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '7 days', t.main_side) AS price_change_7d,
+       t.liquidity_usd, t.volume_1h_usd, t.volume_24h_usd, t.trade_count_24h,
+       NOW() AS refreshed_at
+FROM tokens t
+LEFT JOIN token_usd_prices u ON u.chain_id = t.chain_id AND u.token_address = t.token_address;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_token_stats_mv_token ON token_stats_mv(chain_id, token_address);
@@ -0,0 +1,23 @@
+-- 代币供应量：供应量服务定期读取链上 totalSupply()，扣除排除地址的 balanceOf() 得到流通量
+-- 排除地址 = 链级 <PREFIX>_SUPPLY_EXCLUDED_ADDRESSES（默认销毁地址）+ token_supply_exclusions 中的单币配置
+-- 市值 = 美元价格 × 流通量，FDV = 美元价格 × COALESCE(token_metadata.max_supply, 链上 totalSupply)
+
+CREATE TABLE IF NOT EXISTS token_supply (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    total_supply DECIMAL NOT NULL,
+    circulating_supply DECIMAL NOT NULL,
+    excluded_supply DECIMAL NOT NULL DEFAULT 0,
+    block_number BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, token_address)
+);
+
+CREATE TABLE IF NOT EXISTS token_supply_exclusions (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    holder_address VARCHAR(42) NOT NULL,
+    label VARCHAR(50),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, token_address, holder_address)
+);
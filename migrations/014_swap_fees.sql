@@ -0,0 +1,27 @@
+-- swap 手续费：按工厂费率（<PREFIX>_SWAP_FEE_BPS，默认 30 基点）对输入数量收取
+-- fee_amount 以输入代币计（归一化），fee_usd = volume_usd × 费率，估值服务补算 volume_usd 时一并补算
+-- 事件服务启动时同步 factory_fees，费率变化时重算该链所有 swap 的手续费
+
+CREATE TABLE IF NOT EXISTS factory_fees (
+    chain_id INTEGER PRIMARY KEY,
+    factory_address VARCHAR(42) NOT NULL,
+    fee_bps INTEGER NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION pair_fee_rate(p_chain_id INTEGER)
+RETURNS NUMERIC AS $$
+    SELECT COALESCE((SELECT fee_bps FROM factory_fees WHERE chain_id = p_chain_id), 30)::NUMERIC / 10000
+$$ LANGUAGE sql STABLE;
+
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS fee_amount DECIMAL;
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS fee_usd DECIMAL;
+
+BEGIN;
+
+UPDATE swap_events
+SET fee_amount = trim_scale(round(CASE WHEN side = 'buy' THEN amount0 ELSE amount1 END * pair_fee_rate(chain_id), 28)),
+    fee_usd = trim_scale(round(volume_usd * pair_fee_rate(chain_id), 28))
+WHERE side IS NOT NULL AND fee_amount IS NULL;
+
+COMMIT;
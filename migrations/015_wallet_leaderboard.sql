@@ -0,0 +1,55 @@
+-- 排行榜增量聚合：钱包 × 交易对 × UTC 日
+-- 事件服务写入 swap / mint / burn 时累加，估值服务补算 volume_usd、value_usd 时把补上的部分再累加
+-- 钱包为交易发起人；amount0_net / amount1_net 为钱包视角的代币净流入（已按精度归一化）
+
+CREATE TABLE IF NOT EXISTS wallet_pair_daily_stats (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    wallet_address VARCHAR(42) NOT NULL,
+    day DATE NOT NULL,
+    trade_count BIGINT NOT NULL DEFAULT 0,
+    volume_usd DECIMAL NOT NULL DEFAULT 0,
+    fees_paid_usd DECIMAL NOT NULL DEFAULT 0,
+    amount0_net DECIMAL NOT NULL DEFAULT 0,
+    amount1_net DECIMAL NOT NULL DEFAULT 0,
+    mint_count BIGINT NOT NULL DEFAULT 0,
+    burn_count BIGINT NOT NULL DEFAULT 0,
+    liquidity_added_usd DECIMAL NOT NULL DEFAULT 0,
+    liquidity_removed_usd DECIMAL NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address, wallet_address, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_pair_daily_stats_chain_day ON wallet_pair_daily_stats(chain_id, day);
+
+-- 由历史事件重建（在事件服务停止时执行）
+BEGIN;
+
+TRUNCATE wallet_pair_daily_stats;
+
+INSERT INTO wallet_pair_daily_stats
+    (chain_id, pair_address, wallet_address, day, trade_count, volume_usd, fees_paid_usd,
+     amount0_net, amount1_net, mint_count, burn_count, liquidity_added_usd, liquidity_removed_usd)
+SELECT chain_id, pair_address, wallet_address, day,
+       SUM(trade_count), SUM(volume_usd), SUM(fees_paid_usd), SUM(amount0_net), SUM(amount1_net),
+       SUM(mint_count), SUM(burn_count), SUM(liquidity_added_usd), SUM(liquidity_removed_usd)
+FROM (
+    SELECT chain_id, pair_address, sender AS wallet_address, (timestamp AT TIME ZONE 'UTC')::date AS day,
+           1 AS trade_count, COALESCE(volume_usd, 0) AS volume_usd, COALESCE(fee_usd, 0) AS fees_paid_usd,
+           CASE WHEN side = 'buy' THEN -amount0 ELSE amount0 END AS amount0_net,
+           CASE WHEN side = 'buy' THEN amount1 ELSE -amount1 END AS amount1_net,
+           0 AS mint_count, 0 AS burn_count, 0 AS liquidity_added_usd, 0 AS liquidity_removed_usd
+    FROM swap_events
+    WHERE side IS NOT NULL AND amount0 IS NOT NULL AND amount1 IS NOT NULL
+    UNION ALL
+    SELECT chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date,
+           0, 0, 0, 0, 0, 1, 0, COALESCE(value_usd, 0), 0
+    FROM mint_events
+    UNION ALL
+    SELECT chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date,
+           0, 0, 0, 0, 0, 0, 1, 0, COALESCE(value_usd, 0)
+    FROM burn_events
+) events
+GROUP BY chain_id, pair_address, wallet_address, day;
+
+COMMIT;
@@ -0,0 +1,15 @@
+-- 钱包归属：事件表的 sender 为交易发起人 (tx.from)，event_sender 保存事件中的 sender（通常为路由合约），
+-- burn / swap 的 to_address 为代币接收方。历史数据的 event_sender 为 NULL
+-- known_routers 由事件服务启动时按 <PREFIX>_ROUTER_ADDRESSES 同步
+
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS event_sender VARCHAR(42);
+ALTER TABLE mint_events ADD COLUMN IF NOT EXISTS event_sender VARCHAR(42);
+ALTER TABLE burn_events ADD COLUMN IF NOT EXISTS event_sender VARCHAR(42);
+
+CREATE TABLE IF NOT EXISTS known_routers (
+    chain_id INTEGER NOT NULL,
+    address VARCHAR(42) NOT NULL,
+    label VARCHAR(50),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, address)
+);
@@ -0,0 +1,31 @@
+-- 多跳成交：同一交易内首尾相接的 swap（如 A -> WETH -> B）合并为一笔逻辑成交
+-- 由事件服务的成交重建任务按区块区间写入，进度记录在 last_processed_blocks 的 'trades' 类型下，
+-- 升级后会从最早的 swap 开始自动回填历史数据
+
+CREATE TABLE IF NOT EXISTS trades (
+    chain_id INTEGER NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    first_log_index INTEGER NOT NULL,
+    log_indexes INTEGER[] NOT NULL,
+    block_number BIGINT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    tx_origin VARCHAR(42) NOT NULL,
+    event_sender VARCHAR(42),
+    recipient VARCHAR(42) NOT NULL,
+    token_in VARCHAR(42) NOT NULL,
+    token_in_symbol VARCHAR(20),
+    amount_in DECIMAL NOT NULL,
+    token_out VARCHAR(42) NOT NULL,
+    token_out_symbol VARCHAR(20),
+    amount_out DECIMAL NOT NULL,
+    path TEXT[] NOT NULL,
+    pairs TEXT[] NOT NULL,
+    hop_count INTEGER NOT NULL,
+    PRIMARY KEY (chain_id, transaction_hash, first_log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_time ON trades(chain_id, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_block ON trades(chain_id, block_number);
+CREATE INDEX IF NOT EXISTS idx_trades_origin ON trades(tx_origin, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_recipient ON trades(recipient, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_swap_events_block ON swap_events(chain_id, block_number);
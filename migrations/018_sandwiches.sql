@@ -0,0 +1,41 @@
+-- 夹子攻击检测：同一区块同一交易对内，攻击者抢跑 (frontrun) 与尾随 (backrun) 夹住受害者 swap
+-- 由事件服务的检测任务按区块区间写入，进度记录在 last_processed_blocks 的 'mev' 类型下，
+-- 升级后会从最早的 swap 开始自动回填历史数据
+
+CREATE TABLE IF NOT EXISTS sandwiches (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    block_number BIGINT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    attacker VARCHAR(42) NOT NULL,
+    frontrun_tx VARCHAR(66) NOT NULL,
+    frontrun_log_index INTEGER NOT NULL,
+    backrun_tx VARCHAR(66) NOT NULL,
+    backrun_log_index INTEGER NOT NULL,
+    victim_count INTEGER NOT NULL,
+    victim_volume_usd DECIMAL,
+    profit_token VARCHAR(42) NOT NULL,
+    profit_token_symbol VARCHAR(20),
+    profit_amount DECIMAL NOT NULL,
+    profit_usd DECIMAL,
+    PRIMARY KEY (chain_id, frontrun_tx, frontrun_log_index)
+);
+
+CREATE TABLE IF NOT EXISTS sandwich_swaps (
+    chain_id INTEGER NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_number BIGINT NOT NULL,
+    role VARCHAR(10) NOT NULL,
+    wallet VARCHAR(42) NOT NULL,
+    volume_usd DECIMAL,
+    frontrun_tx VARCHAR(66) NOT NULL,
+    frontrun_log_index INTEGER NOT NULL,
+    PRIMARY KEY (chain_id, transaction_hash, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sandwiches_time ON sandwiches(chain_id, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_sandwiches_block ON sandwiches(chain_id, block_number);
+CREATE INDEX IF NOT EXISTS idx_sandwiches_attacker ON sandwiches(attacker, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_sandwich_swaps_block ON sandwich_swaps(chain_id, block_number);
+CREATE INDEX IF NOT EXISTS idx_sandwich_swaps_wallet ON sandwich_swaps(wallet, role);
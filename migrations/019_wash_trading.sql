@@ -0,0 +1,98 @@
+-- 刷量评分：交易对近 24 小时的来回交易、循环流转和钱包集中度指标，由估值服务每轮重算
+-- token_stats_mv 增加自然成交量 (organic_volume_24h_usd) 和按成交量加权的刷量评分 (wash_score)，需要重建视图
+
+CREATE TABLE IF NOT EXISTS pair_wash_stats (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    volume_usd DECIMAL NOT NULL,
+    organic_volume_usd DECIMAL NOT NULL,
+    trade_count BIGINT NOT NULL,
+    trader_count BIGINT NOT NULL,
+    round_trip_volume_usd DECIMAL NOT NULL,
+    circular_volume_usd DECIMAL NOT NULL,
+    top_wallet_share DECIMAL NOT NULL,
+    top5_wallet_share DECIMAL NOT NULL,
+    wash_score DECIMAL NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address)
+);
+
+DROP MATERIALIZED VIEW IF EXISTS token_stats_mv;
+
+CREATE MATERIALIZED VIEW token_stats_mv AS
+WITH sides AS (
+    SELECT tp.chain_id, tp.address AS pair_address, tp.created_at,
+           lower(tp.token0) AS token_address, lower(tp.token1) AS other_token,
+           tp.token0_symbol AS symbol, tp.token0_name AS name, tp.token0_decimals AS decimals,
+           tp.token1_decimals AS other_decimals,
+           pr.reserve0 AS reserve, pr.reserve1 AS other_reserve, pr.reserve_usd,
+           1 AS price_side
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+    UNION ALL
+    SELECT tp.chain_id, tp.address, tp.created_at,
+           lower(tp.token1), lower(tp.token0),
+           tp.token1_symbol, tp.token1_name, tp.token1_decimals,
+           tp.token0_decimals,
+           pr.reserve1, pr.reserve0, pr.reserve_usd,
+           0
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+),
+pools AS (
+    SELECT s.*,
+           CASE WHEN s.reserve > 0 AND s.other_reserve > 0 THEN
+               u.price_usd * (s.other_reserve / power(10::numeric, COALESCE(s.other_decimals, 18)))
+                           / (s.reserve / power(10::numeric, COALESCE(s.decimals, 18)))
+           END AS pool_price_usd,
+           v.volume_1h, v.volume_24h, v.trade_count_24h,
+           COALESCE(w.organic_volume_usd, 0) AS organic_volume_24h
+    FROM sides s
+    LEFT JOIN token_usd_prices u ON u.chain_id = s.chain_id AND u.token_address = s.other_token
+    LEFT JOIN pair_wash_stats w ON w.chain_id = s.chain_id AND w.pair_address = s.pair_address
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(SUM(se.volume_usd) FILTER (WHERE se.timestamp >= NOW() - INTERVAL '1 hour'), 0) AS volume_1h,
+               COALESCE(SUM(se.volume_usd), 0) AS volume_24h,
+               COUNT(*) AS trade_count_24h
+        FROM swap_events se
+        WHERE se.chain_id = s.chain_id AND se.pair_address = s.pair_address
+          AND se.timestamp >= NOW() - INTERVAL '24 hours'
+    ) v
+),
+tokens AS (
+    SELECT chain_id, token_address,
+           (array_agg(symbol ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE symbol IS NOT NULL))[1] AS symbol,
+           (array_agg(name ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           MAX(decimals) AS decimals,
+           COUNT(*)::INTEGER AS pair_count,
+           MIN(created_at) AS listed_at,
+           COALESCE(SUM(reserve_usd), 0) AS liquidity_usd,
+           SUM(volume_1h) AS volume_1h_usd,
+           SUM(volume_24h) AS volume_24h_usd,
+           SUM(organic_volume_24h) AS organic_volume_24h_usd,
+           SUM(trade_count_24h) AS trade_count_24h,
+           SUM(pool_price_usd * reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0)
+               / NULLIF(SUM(reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0), 0)
+               AS weighted_price_usd,
+           (array_agg(pair_address ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_pair,
+           (array_agg(price_side ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_side
+    FROM pools
+    GROUP BY chain_id, token_address
+)
+SELECT t.chain_id, t.token_address, t.symbol, t.name, t.decimals,
+       t.pair_count, t.listed_at, t.main_pair,
+       trim_scale(round(COALESCE(t.weighted_price_usd, u.price_usd), 28)) AS price_usd,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '1 hour', t.main_side) AS price_change_1h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '24 hours', t.main_side) AS price_change_24h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '7 days', t.main_side) AS price_change_7d,
+       t.liquidity_usd, t.volume_1h_usd, t.volume_24h_usd, t.trade_count_24h,
+       t.organic_volume_24h_usd,
+       -- 按成交量加权的刷量评分，即非自然成交量占比
+       CASE WHEN t.volume_24h_usd > 0 THEN
+           trim_scale(round((1 - LEAST(t.organic_volume_24h_usd / t.volume_24h_usd, 1)) * 100, 4))
+       ELSE 0 END AS wash_score,
+       NOW() AS refreshed_at
+FROM tokens t
+LEFT JOIN token_usd_prices u ON u.chain_id = t.chain_id AND u.token_address = t.token_address;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_token_stats_mv_token ON token_stats_mv(chain_id, token_address);
@@ -0,0 +1,21 @@
+-- 跑路告警：大额撤池 (large_burn)、计价代币储备骤降 (reserve_drop)、LP 集中在首个流动性提供者 (lp_concentration)
+-- 同一 (chain_id, dedup_key) 只写入一次，新告警同时推送到 WebSocket alerts 频道
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    alert_type VARCHAR(30) NOT NULL,
+    severity VARCHAR(10) NOT NULL,
+    wallet VARCHAR(42),
+    transaction_hash VARCHAR(66),
+    block_number BIGINT,
+    message TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    dedup_key VARCHAR(200) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chain_id, dedup_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_time ON alerts(chain_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_alerts_pair ON alerts(chain_id, pair_address, created_at DESC);
@@ -0,0 +1,25 @@
+-- 新代币风险评分：交易对创建时检查非计价方代币的字节码（无代码 / 可升级代理 / 增发、黑名单、暂停等特权函数），
+-- 之后由风险服务在 RISK_WINDOW_HOURS 内定期重算 LP 锁仓 (lp_unlocked) 和转账税 (fee_on_transfer) 标记
+
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS risk_token VARCHAR(42);
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS risk_flags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS risk_score INTEGER;
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS lp_locked_pct DECIMAL;
+ALTER TABLE trading_pairs ADD COLUMN IF NOT EXISTS risk_checked_at TIMESTAMPTZ;
+
+CREATE OR REPLACE FUNCTION token_risk_score(p_flags TEXT[])
+RETURNS INTEGER AS $$
+    SELECT LEAST(100, COALESCE(SUM(CASE f
+        WHEN 'no_code' THEN 100
+        WHEN 'proxy' THEN 25
+        WHEN 'mintable' THEN 25
+        WHEN 'blacklist' THEN 20
+        WHEN 'pausable' THEN 15
+        WHEN 'fee_on_transfer' THEN 20
+        WHEN 'lp_unlocked' THEN 20
+        ELSE 0
+    END), 0))::INTEGER
+    FROM unnest(p_flags) AS f
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE INDEX IF NOT EXISTS idx_trading_pairs_risk_token ON trading_pairs(chain_id, risk_token);
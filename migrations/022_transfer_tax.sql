@@ -0,0 +1,18 @@
+-- 转账税与 rebase 检测：swap 监听器对风险代币（非计价方）采样交易回执，比对 Swap 数量与同一交易中的 Transfer 日志，
+-- 检测到转账税的 swap 记录 transfer_tax_rate；独立 Sync 或同一代币既转入又转出的 swap 记为储备量漂移 (rebase_events)
+
+ALTER TABLE swap_events ADD COLUMN IF NOT EXISTS transfer_tax_rate DECIMAL;
+
+CREATE TABLE IF NOT EXISTS token_transfer_taxes (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    buy_tax_rate DECIMAL,
+    sell_tax_rate DECIMAL,
+    max_tax_rate DECIMAL NOT NULL DEFAULT 0,
+    sampled_swaps BIGINT NOT NULL DEFAULT 0,
+    taxed_swaps BIGINT NOT NULL DEFAULT 0,
+    rebase_events BIGINT NOT NULL DEFAULT 0,
+    last_sampled_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, token_address)
+);
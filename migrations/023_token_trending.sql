@@ -0,0 +1,24 @@
+-- 热门代币评分：估值服务每轮刷新代币统计视图后，按最近 1 小时相对此前 24 小时的自然成交量加速度、
+-- 独立交易者增长，以及 1 小时涨幅和流动性加权计算，权重见 TRENDING_* 环境变量
+
+CREATE TABLE IF NOT EXISTS token_trending (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    score DECIMAL NOT NULL,
+    volume_1h_usd DECIMAL NOT NULL,
+    volume_baseline_usd DECIMAL NOT NULL,
+    volume_acceleration DECIMAL NOT NULL,
+    traders_1h BIGINT NOT NULL,
+    traders_baseline DECIMAL NOT NULL,
+    trader_growth DECIMAL NOT NULL,
+    price_change_1h DECIMAL NOT NULL,
+    liquidity_usd DECIMAL NOT NULL,
+    volume_component DECIMAL NOT NULL,
+    trader_component DECIMAL NOT NULL,
+    price_component DECIMAL NOT NULL,
+    liquidity_component DECIMAL NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, token_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_trending_score ON token_trending(chain_id, score DESC);
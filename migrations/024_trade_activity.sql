@@ -0,0 +1,158 @@
+-- 成交广度：钱包 × 交易对 × 5 分钟的增量聚合 (wallet_pair_activity_5m)，swap 写入时累加，估值服务补算美元成交量时同步累加，
+-- 维护服务删除 24 小时之前的桶；token_stats_mv 增加 5m / 1h / 6h / 24h 独立交易者、买卖方数量、买卖笔数和买卖成交额，需要重建视图
+
+CREATE TABLE IF NOT EXISTS wallet_pair_activity_5m (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    wallet_address VARCHAR(42) NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    buy_count BIGINT NOT NULL DEFAULT 0,
+    sell_count BIGINT NOT NULL DEFAULT 0,
+    buy_volume_usd DECIMAL NOT NULL DEFAULT 0,
+    sell_volume_usd DECIMAL NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address, bucket, wallet_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_pair_activity_5m_bucket ON wallet_pair_activity_5m(bucket);
+
+-- 用最近 24 小时的 swap 初始化聚合
+INSERT INTO wallet_pair_activity_5m
+    (chain_id, pair_address, wallet_address, bucket, buy_count, sell_count, buy_volume_usd, sell_volume_usd)
+SELECT chain_id, pair_address, sender, candle_bucket('5m', timestamp),
+       COUNT(*) FILTER (WHERE side = 'buy'),
+       COUNT(*) FILTER (WHERE side = 'sell'),
+       COALESCE(SUM(volume_usd) FILTER (WHERE side = 'buy'), 0),
+       COALESCE(SUM(volume_usd) FILTER (WHERE side = 'sell'), 0)
+FROM swap_events
+WHERE timestamp >= NOW() - INTERVAL '24 hours' AND side IS NOT NULL
+GROUP BY chain_id, pair_address, sender, candle_bucket('5m', timestamp)
+ON CONFLICT (chain_id, pair_address, bucket, wallet_address) DO NOTHING;
+
+DROP MATERIALIZED VIEW IF EXISTS token_stats_mv;
+
+CREATE MATERIALIZED VIEW token_stats_mv AS
+WITH sides AS (
+    SELECT tp.chain_id, tp.address AS pair_address, tp.created_at,
+           lower(tp.token0) AS token_address, lower(tp.token1) AS other_token,
+           tp.token0_symbol AS symbol, tp.token0_name AS name, tp.token0_decimals AS decimals,
+           tp.token1_decimals AS other_decimals,
+           pr.reserve0 AS reserve, pr.reserve1 AS other_reserve, pr.reserve_usd,
+           1 AS price_side
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+    UNION ALL
+    SELECT tp.chain_id, tp.address, tp.created_at,
+           lower(tp.token1), lower(tp.token0),
+           tp.token1_symbol, tp.token1_name, tp.token1_decimals,
+           tp.token0_decimals,
+           pr.reserve1, pr.reserve0, pr.reserve_usd,
+           0
+    FROM trading_pairs tp
+    LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+),
+pools AS (
+    SELECT s.*,
+           CASE WHEN s.reserve > 0 AND s.other_reserve > 0 THEN
+               u.price_usd * (s.other_reserve / power(10::numeric, COALESCE(s.other_decimals, 18)))
+                           / (s.reserve / power(10::numeric, COALESCE(s.decimals, 18)))
+           END AS pool_price_usd,
+           v.volume_1h, v.volume_24h, v.trade_count_24h,
+           COALESCE(w.organic_volume_usd, 0) AS organic_volume_24h
+    FROM sides s
+    LEFT JOIN token_usd_prices u ON u.chain_id = s.chain_id AND u.token_address = s.other_token
+    LEFT JOIN pair_wash_stats w ON w.chain_id = s.chain_id AND w.pair_address = s.pair_address
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(SUM(se.volume_usd) FILTER (WHERE se.timestamp >= NOW() - INTERVAL '1 hour'), 0) AS volume_1h,
+               COALESCE(SUM(se.volume_usd), 0) AS volume_24h,
+               COUNT(*) AS trade_count_24h
+        FROM swap_events se
+        WHERE se.chain_id = s.chain_id AND se.pair_address = s.pair_address
+          AND se.timestamp >= NOW() - INTERVAL '24 hours'
+    ) v
+),
+tokens AS (
+    SELECT chain_id, token_address,
+           (array_agg(symbol ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE symbol IS NOT NULL))[1] AS symbol,
+           (array_agg(name ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           MAX(decimals) AS decimals,
+           COUNT(*)::INTEGER AS pair_count,
+           MIN(created_at) AS listed_at,
+           COALESCE(SUM(reserve_usd), 0) AS liquidity_usd,
+           SUM(volume_1h) AS volume_1h_usd,
+           SUM(volume_24h) AS volume_24h_usd,
+           SUM(organic_volume_24h) AS organic_volume_24h_usd,
+           SUM(trade_count_24h) AS trade_count_24h,
+           SUM(pool_price_usd * reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0)
+               / NULLIF(SUM(reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0), 0)
+               AS weighted_price_usd,
+           (array_agg(pair_address ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_pair,
+           (array_agg(price_side ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_side
+    FROM pools
+    GROUP BY chain_id, token_address
+),
+activity AS (
+    SELECT s.chain_id, s.token_address, a.wallet_address, a.bucket,
+           CASE WHEN s.price_side = 0 THEN a.buy_count ELSE a.sell_count END AS buys,
+           CASE WHEN s.price_side = 0 THEN a.sell_count ELSE a.buy_count END AS sells,
+           CASE WHEN s.price_side = 0 THEN a.buy_volume_usd ELSE a.sell_volume_usd END AS buy_volume_usd,
+           CASE WHEN s.price_side = 0 THEN a.sell_volume_usd ELSE a.buy_volume_usd END AS sell_volume_usd
+    FROM sides s
+    JOIN wallet_pair_activity_5m a ON a.chain_id = s.chain_id AND a.pair_address = s.pair_address
+    WHERE a.bucket > NOW() - INTERVAL '1445 minutes'
+),
+token_activity AS (
+    SELECT chain_id, token_address,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes') AS traders_5m,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes' AND buys > 0) AS buyers_5m,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes' AND sells > 0) AS sellers_5m,
+           COALESCE(SUM(buys) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes'), 0)::BIGINT AS buys_5m,
+           COALESCE(SUM(sells) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes'), 0)::BIGINT AS sells_5m,
+           COALESCE(SUM(buy_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes'), 0) AS buy_volume_5m,
+           COALESCE(SUM(sell_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '10 minutes'), 0) AS sell_volume_5m,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes') AS traders_1h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes' AND buys > 0) AS buyers_1h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes' AND sells > 0) AS sellers_1h,
+           COALESCE(SUM(buys) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes'), 0)::BIGINT AS buys_1h,
+           COALESCE(SUM(sells) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes'), 0)::BIGINT AS sells_1h,
+           COALESCE(SUM(buy_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes'), 0) AS buy_volume_1h,
+           COALESCE(SUM(sell_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '65 minutes'), 0) AS sell_volume_1h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes') AS traders_6h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes' AND buys > 0) AS buyers_6h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes' AND sells > 0) AS sellers_6h,
+           COALESCE(SUM(buys) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes'), 0)::BIGINT AS buys_6h,
+           COALESCE(SUM(sells) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes'), 0)::BIGINT AS sells_6h,
+           COALESCE(SUM(buy_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes'), 0) AS buy_volume_6h,
+           COALESCE(SUM(sell_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '365 minutes'), 0) AS sell_volume_6h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes') AS traders_24h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes' AND buys > 0) AS buyers_24h,
+           COUNT(DISTINCT wallet_address) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes' AND sells > 0) AS sellers_24h,
+           COALESCE(SUM(buys) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes'), 0)::BIGINT AS buys_24h,
+           COALESCE(SUM(sells) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes'), 0)::BIGINT AS sells_24h,
+           COALESCE(SUM(buy_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes'), 0) AS buy_volume_24h,
+           COALESCE(SUM(sell_volume_usd) FILTER (WHERE bucket > NOW() - INTERVAL '1445 minutes'), 0) AS sell_volume_24h
+    FROM activity
+    GROUP BY chain_id, token_address
+)
+SELECT t.chain_id, t.token_address, t.symbol, t.name, t.decimals,
+       t.pair_count, t.listed_at, t.main_pair,
+       trim_scale(round(COALESCE(t.weighted_price_usd, u.price_usd), 28)) AS price_usd,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '1 hour', t.main_side) AS price_change_1h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '24 hours', t.main_side) AS price_change_24h,
+       pair_price_change(t.chain_id, t.main_pair, INTERVAL '7 days', t.main_side) AS price_change_7d,
+       t.liquidity_usd, t.volume_1h_usd, t.volume_24h_usd, t.trade_count_24h,
+       t.organic_volume_24h_usd,
+       -- 按成交量加权的刷量评分，即非自然成交量占比
+       CASE WHEN t.volume_24h_usd > 0 THEN
+           trim_scale(round((1 - LEAST(t.organic_volume_24h_usd / t.volume_24h_usd, 1)) * 100, 4))
+       ELSE 0 END AS wash_score,
+       ta.traders_5m, ta.buyers_5m, ta.sellers_5m, ta.buys_5m, ta.sells_5m, ta.buy_volume_5m, ta.sell_volume_5m,
+       ta.traders_1h, ta.buyers_1h, ta.sellers_1h, ta.buys_1h, ta.sells_1h, ta.buy_volume_1h, ta.sell_volume_1h,
+       ta.traders_6h, ta.buyers_6h, ta.sellers_6h, ta.buys_6h, ta.sells_6h, ta.buy_volume_6h, ta.sell_volume_6h,
+       ta.traders_24h, ta.buyers_24h, ta.sellers_24h, ta.buys_24h, ta.sells_24h, ta.buy_volume_24h, ta.sell_volume_24h,
+       NOW() AS refreshed_at
+FROM tokens t
+LEFT JOIN token_usd_prices u ON u.chain_id = t.chain_id AND u.token_address = t.token_address
+LEFT JOIN token_activity ta ON ta.chain_id = t.chain_id AND ta.token_address = t.token_address;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_token_stats_mv_token ON token_stats_mv(chain_id, token_address);
@@ -0,0 +1,17 @@
+-- TWAP 采样：TWAP 服务定期在最新区块读取 TVL 靠前交易对的 price0/1CumulativeLast 和 getReserves，
+-- 按 blockTimestampLast 补上未累计的部分后写入，/api/pairs/:chain_id/:address/twap 由两次采样的累计价格差计算均价
+
+CREATE TABLE IF NOT EXISTS pair_twap_samples (
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_timestamp TIMESTAMPTZ NOT NULL,
+    price0_cumulative NUMERIC(78, 0) NOT NULL,
+    price1_cumulative NUMERIC(78, 0) NOT NULL,
+    reserve0 NUMERIC(78, 0) NOT NULL,
+    reserve1 NUMERIC(78, 0) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, pair_address, block_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_pair_twap_samples_time ON pair_twap_samples(chain_id, pair_address, block_timestamp DESC);
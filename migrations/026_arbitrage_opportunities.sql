@@ -0,0 +1,22 @@
+-- 循环套利机会：交换事件监听器每处理完一批区块后，扫描经过本批次有事件的交易对的环路，
+-- 按记录的储备量模拟兑换求最优投入，扣除手续费和价格冲击后利润达到下限的机会写入并推送到 arbitrage 频道
+
+CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    block_number BIGINT NOT NULL,
+    start_token VARCHAR(42) NOT NULL,
+    pairs TEXT[] NOT NULL,
+    amount_in DECIMAL NOT NULL,
+    amount_out DECIMAL NOT NULL,
+    profit DECIMAL NOT NULL,
+    profit_usd DECIMAL NOT NULL,
+    amount_in_usd DECIMAL NOT NULL,
+    spread_pct DECIMAL NOT NULL,
+    return_pct DECIMAL NOT NULL,
+    route JSONB NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chain_id, block_number, pairs)
+);
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_opportunities_time ON arbitrage_opportunities(chain_id, detected_at DESC);
@@ -0,0 +1,25 @@
+-- 巨鲸交易：swap 监听器写入 swap / mint / burn 时，美元价值达到 WHALE_MIN_USD 或占事件前储备比例达到
+-- WHALE_RESERVE_PCT（可按链、交易对覆盖）的事件写入，由 /api/whales 查询并推送到 whales 频道
+
+CREATE TABLE IF NOT EXISTS whale_trades (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chain_id INTEGER NOT NULL,
+    pair_address VARCHAR(42) NOT NULL,
+    event_type VARCHAR(10) NOT NULL,
+    side VARCHAR(4),
+    wallet VARCHAR(42) NOT NULL,
+    amount0 DECIMAL NOT NULL,
+    amount1 DECIMAL NOT NULL,
+    value_usd DECIMAL,
+    reserve_pct DECIMAL,
+    trigger VARCHAR(10) NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index INTEGER NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chain_id, transaction_hash, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_whale_trades_time ON whale_trades(chain_id, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_whale_trades_pair ON whale_trades(chain_id, pair_address, timestamp DESC);
@@ -0,0 +1,10 @@
+-- 供应量读取失败记录：totalSupply 调用回退或数量超出 Decimal 范围的代币不会写入 token_supply，
+-- 记录最近失败时间和连续失败次数，供应量服务按次数退避重试（最多 8 个刷新周期），读取成功后删除
+
+CREATE TABLE IF NOT EXISTS token_supply_failures (
+    chain_id INTEGER NOT NULL,
+    token_address VARCHAR(42) NOT NULL,
+    failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (chain_id, token_address)
+);
@@ -0,0 +1,42 @@
+use super::super::ApiState;
+use crate::database::operations::{AlertFilter, AlertOperations};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct AlertQuery {
+    pub chain_id: Option<i32>,
+    pub pair: Option<String>,
+    pub r#type: Option<String>, // large_burn / reserve_drop / lp_concentration
+    pub severity: Option<String>, // warning / critical
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Alert handlers
+pub async fn get_alerts(
+    Query(params): Query<AlertQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<Alert>>, StatusCode> {
+    let filter = AlertFilter {
+        chain_id: params.chain_id,
+        pair_address: params.pair.map(|p| p.to_lowercase()),
+        alert_type: params.r#type,
+        severity: params.severity,
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match AlertOperations::get_alerts(state.database.pool(), &filter, limit, offset).await {
+        Ok(alerts) => Ok(Json(alerts)),
+        Err(e) => {
+            tracing::error!("Failed to get alerts: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use super::super::ApiState;
+use crate::database::operations::{ArbitrageFilter, ArbitrageOperations};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ArbitrageQuery {
+    pub chain_id: Option<i32>,
+    pub pair: Option<String>, // 环路经过的交易对
+    pub min_profit_usd: Option<Decimal>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Arbitrage handlers
+pub async fn get_arbitrage_opportunities(
+    Query(params): Query<ArbitrageQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<ArbitrageOpportunity>>, StatusCode> {
+    let filter = ArbitrageFilter {
+        chain_id: params.chain_id,
+        pair_address: params.pair.map(|p| p.to_lowercase()),
+        min_profit_usd: params.min_profit_usd,
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match ArbitrageOperations::get_opportunities(state.database.pool(), &filter, limit, offset).await {
+        Ok(opportunities) => Ok(Json(opportunities)),
+        Err(e) => {
+            tracing::error!("Failed to get arbitrage opportunities: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
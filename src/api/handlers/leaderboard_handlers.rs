@@ -0,0 +1,75 @@
+use super::super::ApiState;
+use crate::database::operations::{
+    parse_leaderboard_window, LeaderboardOperations, LeaderboardScope, LeaderboardSort,
+};
+use crate::types::*;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub sort_by: Option<String>, // volume, trades, pnl, liquidity
+    pub window: Option<String>,  // 1d, 7d, 30d, all
+    pub limit: Option<i64>,
+}
+
+// 排行榜handlers
+pub async fn get_pair_leaderboard(
+    Path((chain_id, address)): Path<(i32, String)>,
+    Query(params): Query<LeaderboardQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> {
+    leaderboard(&state, chain_id, LeaderboardScope::Pair(address.to_lowercase()), params).await
+}
+
+pub async fn get_token_leaderboard(
+    Path((chain_id, address)): Path<(i32, String)>,
+    Query(params): Query<LeaderboardQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> {
+    leaderboard(&state, chain_id, LeaderboardScope::Token(address.to_lowercase()), params).await
+}
+
+pub async fn get_chain_leaderboard(
+    Path(chain_id): Path<i32>,
+    Query(params): Query<LeaderboardQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> {
+    leaderboard(&state, chain_id, LeaderboardScope::Chain, params).await
+}
+
+async fn leaderboard(
+    state: &ApiState,
+    chain_id: i32,
+    scope: LeaderboardScope,
+    params: LeaderboardQuery,
+) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> {
+    let sort = match params.sort_by.as_deref() {
+        Some(s) => LeaderboardSort::parse(s).ok_or(StatusCode::BAD_REQUEST)?,
+        None => LeaderboardSort::Volume,
+    };
+    let days = parse_leaderboard_window(params.window.as_deref().unwrap_or("7d"))
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+
+    match LeaderboardOperations::get_leaderboard(
+        state.database.pool(),
+        chain_id,
+        &scope,
+        sort,
+        days,
+        limit,
+    )
+    .await
+    {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => {
+            tracing::error!("Failed to get leaderboard: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
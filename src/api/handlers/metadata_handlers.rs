@@ -1,5 +1,5 @@
 use super::super::ApiState;
-use crate::database::operations::MetadataOperations;
+use crate::database::operations::{MetadataOperations, SupplyOperations};
 use crate::types::*;
 use axum::{
     extract::{Path, Query, State},
@@ -120,3 +120,56 @@ pub async fn list_token_metadata(
         }
     }
 }
+
+/// 代币不计入流通量的持有地址（团队、金库等），链级默认的销毁地址不在此列出
+pub async fn get_supply_exclusions(
+    Path((chain_id, address)): Path<(i32, String)>,
+    State(state): State<ApiState>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    match SupplyOperations::get_exclusions(state.database.pool(), chain_id, &address).await {
+        Ok(exclusions) => Ok(ApiResponse::success(exclusions)),
+        Err(e) => {
+            tracing::error!("Failed to get supply exclusions: {}", e);
+            Err(ApiResponse::<()>::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get supply exclusions: {}", e),
+            ))
+        }
+    }
+}
+
+/// 整体替换代币的排除地址，供应量服务下一轮会重新计算流通量
+pub async fn update_supply_exclusions(
+    Path((chain_id, address)): Path<(i32, String)>,
+    State(state): State<ApiState>,
+    Json(payload): Json<UpdateSupplyExclusions>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    if let Some(invalid) = payload
+        .exclusions
+        .iter()
+        .find(|e| e.holder_address.parse::<ethers::types::Address>().is_err())
+    {
+        return Err(ApiResponse::<()>::error(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid holder address: {}", invalid.holder_address),
+        ));
+    }
+
+    match SupplyOperations::replace_exclusions(
+        state.database.pool(),
+        chain_id,
+        &address,
+        &payload.exclusions,
+    )
+    .await
+    {
+        Ok(()) => Ok(ApiResponse::success(payload.exclusions)),
+        Err(e) => {
+            tracing::error!("Failed to update supply exclusions: {}", e);
+            Err(ApiResponse::<()>::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update supply exclusions: {}", e),
+            ))
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use super::super::ApiState;
+use crate::database::operations::{MevOperations, SandwichFilter};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct SandwichQuery {
+    pub chain_id: Option<i32>,
+    pub pair: Option<String>,
+    pub attacker: Option<String>,
+    pub victim: Option<String>, // 受害钱包（交易发起人）
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// MEV handlers
+pub async fn get_sandwiches(
+    Query(params): Query<SandwichQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<Sandwich>>, StatusCode> {
+    let filter = SandwichFilter {
+        chain_id: params.chain_id,
+        pair_address: params.pair.map(|p| p.to_lowercase()),
+        attacker: params.attacker.map(|a| a.to_lowercase()),
+        victim: params.victim.map(|v| v.to_lowercase()),
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match MevOperations::get_sandwiches(state.database.pool(), &filter, limit, offset).await {
+        Ok(sandwiches) => Ok(Json(sandwiches)),
+        Err(e) => {
+            tracing::error!("Failed to get sandwiches: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -3,12 +3,26 @@ pub mod token_handlers;
 pub mod wallet_handlers;
 pub mod status_handlers;
 pub mod metadata_handlers;
+pub mod leaderboard_handlers;
+pub mod trade_handlers;
+pub mod mev_handlers;
+pub mod alert_handlers;
+pub mod quote_handlers;
+pub mod arbitrage_handlers;
+pub mod whale_handlers;
 
 pub use trading_handlers::*;
 pub use token_handlers::*;
 pub use wallet_handlers::*;
 pub use status_handlers::*;
 pub use metadata_handlers::*;
+pub use leaderboard_handlers::*;
+pub use trade_handlers::*;
+pub use mev_handlers::*;
+pub use alert_handlers::*;
+pub use quote_handlers::*;
+pub use arbitrage_handlers::*;
+pub use whale_handlers::*;
 
 
 use axum::{
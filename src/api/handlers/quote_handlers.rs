@@ -0,0 +1,57 @@
+use super::super::ApiState;
+use crate::services::swap_quote_service::{
+    QuoteKind, SwapQuoteService, DEFAULT_QUOTE_MAX_HOPS, MAX_QUOTE_HOPS,
+};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct SwapQuoteQuery {
+    pub chain_id: i32,
+    pub token_in: String,
+    pub token_out: String,
+    /// 归一化数量，exact_in 时为 token_in 数量，exact_out 时为 token_out 数量
+    pub amount: Decimal,
+    pub kind: Option<String>, // exact_in（默认）/ exact_out
+    pub max_hops: Option<usize>, // 1-4，默认 3
+}
+
+// Quote handlers
+pub async fn get_swap_quote(
+    Query(params): Query<SwapQuoteQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<SwapQuote>, StatusCode> {
+    let kind = QuoteKind::parse(params.kind.as_deref()).ok_or(StatusCode::BAD_REQUEST)?;
+    if params.amount <= Decimal::ZERO {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let max_hops = params
+        .max_hops
+        .unwrap_or(DEFAULT_QUOTE_MAX_HOPS)
+        .clamp(1, MAX_QUOTE_HOPS);
+
+    match SwapQuoteService::quote(
+        state.database.pool(),
+        params.chain_id,
+        &params.token_in,
+        &params.token_out,
+        params.amount,
+        kind,
+        max_hops,
+    )
+    .await
+    {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to quote swap: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
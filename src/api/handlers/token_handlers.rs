@@ -5,6 +5,7 @@ use axum::{
     http::StatusCode,
     response::Json,
 };
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use crate::database::operations::{TokenOperations};
 
@@ -13,8 +14,10 @@ pub struct TokenListQuery {
     pub chain_id: Option<i32>,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
-    pub sort_by: Option<String>, // price, volume, market_cap
+    pub sort_by: Option<String>, // price, volume_24h, volume_1h, organic_volume_24h, wash_score, trades, liquidity, market_cap, fdv, pairs, price_change_1h, price_change_24h, price_change_7d
     pub order: Option<String>,   // asc, desc
+    pub max_wash_score: Option<Decimal>, // 0-100，排除刷量评分更高的代币
+    pub max_risk_score: Option<i32>, // 0-100，仅 /api/tokens/new，排除风险分更高的代币
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,7 +43,8 @@ pub async fn get_token_list(
         limit, 
         offset, 
         &sort_by, 
-        &order
+        &order,
+        params.max_wash_score,
     ).await {
         Ok(tokens) => Ok(Json(tokens)),
         Err(e) => {
@@ -91,13 +95,14 @@ pub async fn search_tokens(
 pub async fn get_trending_tokens(
     Query(params): Query<TokenListQuery>,
     State(state): State<ApiState>,
-) -> Result<Json<Vec<TokenListItem>>, StatusCode> {
+) -> Result<Json<Vec<TrendingToken>>, StatusCode> {
     let limit = params.limit.unwrap_or(50);
 
     match TokenOperations::get_trending_tokens(
         state.database.pool(), 
         params.chain_id, 
-        limit
+        limit,
+        params.max_wash_score,
     ).await {
         Ok(tokens) => Ok(Json(tokens)),
         Err(e) => {
@@ -116,7 +121,8 @@ pub async fn get_new_tokens(
     match TokenOperations::get_new_tokens(
         state.database.pool(), 
         params.chain_id, 
-        limit
+        limit,
+        params.max_risk_score,
     ).await {
         Ok(tokens) => Ok(Json(tokens)),
         Err(e) => {
@@ -0,0 +1,42 @@
+use super::super::ApiState;
+use crate::database::operations::{TradeFilter, TradeOperations};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct TradeFeedQuery {
+    pub chain_id: Option<i32>,
+    pub wallet: Option<String>, // 交易发起人或最终接收方
+    pub token: Option<String>,  // 输入或输出代币
+    pub min_hops: Option<i32>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// 多跳成交流handlers
+pub async fn get_trades(
+    Query(params): Query<TradeFeedQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<Trade>>, StatusCode> {
+    let filter = TradeFilter {
+        chain_id: params.chain_id,
+        wallet: params.wallet.map(|w| w.to_lowercase()),
+        token: params.token.map(|t| t.to_lowercase()),
+        min_hops: params.min_hops,
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match TradeOperations::get_trades(state.database.pool(), &filter, limit, offset).await {
+        Ok(trades) => Ok(Json(trades)),
+        Err(e) => {
+            tracing::error!("Failed to get trades: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,20 +1,53 @@
 use super::super::ApiState;
-use crate::database::operations::TradingOperations;
+use crate::database::operations::{
+    is_valid_candle_period, CandleOperations, CandleQuery, QuoteOperations, TradingOperations,
+    TwapOperations, DEFAULT_FEE_APR_WINDOWS, DEFAULT_TWAP_WINDOWS, FEE_WINDOW_MAX_SECS,
+    FEE_WINDOW_UNITS, TWAP_WINDOW_MAX_SECS, TWAP_WINDOW_UNITS,
+};
+use crate::database::utils::parse_window;
+use crate::types::QuoteSelection;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct KlineQuery {
     pub interval: Option<String>,
     pub limit: Option<i32>,
+    /// 起始时间（unix 秒），包含
+    pub start_time: Option<i64>,
+    /// 结束时间（unix 秒），包含
+    pub end_time: Option<i64>,
+    pub quote: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TimeSeriesQuery {
     pub hours: Option<i32>,
+    pub quote: Option<String>,
+}
+
+/// 计价方向：token0 | token1 | auto（默认）
+#[derive(Debug, Deserialize)]
+pub struct QuoteQuery {
+    pub quote: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairDetailQuery {
+    pub quote: Option<String>,
+    /// 手续费 APR 窗口，逗号分隔，如 `24h,7d,30d`
+    pub fee_windows: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwapQuery {
+    pub quote: Option<String>,
+    /// TWAP 窗口，逗号分隔，如 `5m,1h,24h`
+    pub windows: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +61,7 @@ pub struct PairsQuery {
 pub struct TradeQuery {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
+    pub quote: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -63,9 +97,37 @@ pub async fn get_pairs(
 
 pub async fn get_pair_detail(
     Path((chain_id, address)): Path<(i32, String)>,
+    Query(params): Query<PairDetailQuery>,
     State(state): State<ApiState>,
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
-    match TradingOperations::get_pair_detail(state.database.pool(), &address, chain_id).await {
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
+
+    let labels: Vec<String> = match params.fee_windows.as_deref() {
+        Some(windows) => windows.split(',').map(|w| w.trim().to_string()).collect(),
+        None => DEFAULT_FEE_APR_WINDOWS.iter().map(|w| w.to_string()).collect(),
+    };
+    let mut fee_windows = Vec::with_capacity(labels.len());
+    for label in labels {
+        match parse_window(&label, FEE_WINDOW_UNITS, FEE_WINDOW_MAX_SECS) {
+            Some(secs) => fee_windows.push((label, secs)),
+            None => {
+                return Err(ApiResponse::<()>::error(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid fee window: {}, expected e.g. 24h or 7d (max 365d)", label),
+                ))
+            }
+        }
+    }
+
+    match TradingOperations::get_pair_detail(
+        state.database.pool(),
+        &address,
+        chain_id,
+        quote_side,
+        &fee_windows,
+    )
+    .await
+    {
         Ok(Some(detail)) => Ok(ApiResponse::success(detail)),
         Ok(None) => Err(ApiResponse::<()>::error(
             StatusCode::NOT_FOUND,
@@ -85,23 +147,42 @@ pub async fn get_kline(
     State(state): State<ApiState>,
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
     let interval = params.interval.unwrap_or_else(|| "1h".to_string());
-    let limit = params.limit.unwrap_or(100);
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
 
-    if !is_valid_interval(&interval) {
+    if !is_valid_candle_period(&interval) {
         return Err(ApiResponse::<()>::error(
             StatusCode::BAD_REQUEST,
             "Invalid interval parameter".to_string(),
         ));
     }
 
-    match TradingOperations::get_kline_data(
-        state.database.pool(),
-        &address,
-        chain_id,
-        &interval,
-        limit,
-    )
-    .await
+    let start = match params.start_time.map(unix_to_datetime) {
+        Some(None) => return Err(invalid_time_range()),
+        Some(Some(ts)) => Some(ts),
+        None => None,
+    };
+    let end = match params.end_time.map(unix_to_datetime) {
+        Some(None) => return Err(invalid_time_range()),
+        Some(Some(ts)) => Some(ts),
+        None => None,
+    };
+    if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+            return Err(invalid_time_range());
+        }
+    }
+
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
+
+    let query = CandleQuery {
+        period: &interval,
+        start,
+        end,
+        limit: limit as i64,
+        quote_side,
+    };
+
+    match CandleOperations::get_candles(state.database.pool(), chain_id, &address, &query).await
     {
         Ok(klines) => Ok(ApiResponse::success(klines)),
         Err(e) => {
@@ -126,8 +207,16 @@ pub async fn get_timeseries(
         ));
     }
 
-    match TradingOperations::get_timeseries_data(state.database.pool(), &address, chain_id, hours)
-        .await
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
+
+    match TradingOperations::get_timeseries_data(
+        state.database.pool(),
+        &address,
+        chain_id,
+        hours,
+        quote_side,
+    )
+    .await
     {
         Ok(timeseries) => Ok(ApiResponse::success(timeseries)),
         Err(e) => {
@@ -145,6 +234,7 @@ pub async fn get_pair_trades(
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
     let limit = params.limit.unwrap_or(50);
     let offset = params.offset.unwrap_or(0);
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
 
     match TradingOperations::get_pair_trades(
         state.database.pool(),
@@ -152,6 +242,7 @@ pub async fn get_pair_trades(
         chain_id,
         limit,
         offset,
+        quote_side,
     )
     .await
     {
@@ -192,9 +283,14 @@ pub async fn get_pair_liquidity(
 
 pub async fn get_pair_stats(
     Path((chain_id, address)): Path<(i32, String)>,
+    Query(params): Query<QuoteQuery>,
     State(state): State<ApiState>,
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
-    match TradingOperations::get_pair_stats(state.database.pool(), &address, chain_id).await {
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
+
+    match TradingOperations::get_pair_stats(state.database.pool(), &address, chain_id, quote_side)
+        .await
+    {
         Ok(Some(stats)) => Ok(ApiResponse::success(stats)),
         Ok(None) => Err(ApiResponse::<()>::error(
             StatusCode::NOT_FOUND,
@@ -208,10 +304,78 @@ pub async fn get_pair_stats(
     }
 }
 
-// 辅助函数：验证时间区间参数
-fn is_valid_interval(interval: &str) -> bool {
-    matches!(
-        interval,
-        "1m" | "5m" | "15m" | "30m" | "1h" | "4h" | "1d" | "1w" | "1M" | "1y"
+/// 交易对-TWAP
+pub async fn get_pair_twap(
+    Path((chain_id, address)): Path<(i32, String)>,
+    Query(params): Query<TwapQuery>,
+    State(state): State<ApiState>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let quote_side = resolve_quote_side(&state, chain_id, &address, params.quote.as_deref()).await?;
+
+    let labels: Vec<String> = match params.windows.as_deref() {
+        Some(windows) => windows.split(',').map(|w| w.trim().to_string()).collect(),
+        None => DEFAULT_TWAP_WINDOWS.iter().map(|w| w.to_string()).collect(),
+    };
+    let mut windows = Vec::with_capacity(labels.len());
+    for label in labels {
+        match parse_window(&label, TWAP_WINDOW_UNITS, TWAP_WINDOW_MAX_SECS) {
+            Some(secs) => windows.push((label, secs)),
+            None => {
+                return Err(ApiResponse::<()>::error(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid TWAP window: {}, expected e.g. 5m, 1h or 1d (max 7d)", label),
+                ))
+            }
+        }
+    }
+
+    match TwapOperations::get_pair_twap(state.database.pool(), chain_id, &address, quote_side, &windows)
+        .await
+    {
+        Ok(Some(twap)) => Ok(ApiResponse::success(twap)),
+        Ok(None) => Err(ApiResponse::<()>::error(
+            StatusCode::NOT_FOUND,
+            "Pair not found".to_string(),
+        )),
+        Err(e) => {
+            let error_msg = format!("Failed to get pair TWAP: {}", e);
+            tracing::error!("{}", error_msg);
+            Err(ApiResponse::<()>::error(StatusCode::INTERNAL_SERVER_ERROR, error_msg))
+        }
+    }
+}
+
+// 辅助函数：解析 `?quote=` 参数得到计价方 (0 = token0, 1 = token1)
+async fn resolve_quote_side(
+    state: &ApiState,
+    chain_id: i32,
+    address: &str,
+    quote: Option<&str>,
+) -> Result<i32, (StatusCode, String)> {
+    let selection = QuoteSelection::parse(quote).ok_or_else(|| {
+        ApiResponse::<()>::error(
+            StatusCode::BAD_REQUEST,
+            "Invalid quote parameter, expected token0, token1 or auto".to_string(),
+        )
+    })?;
+
+    QuoteOperations::resolve_quote_side(state.database.pool(), chain_id, address, selection)
+        .await
+        .map_err(|e| {
+            let error_msg = format!("Failed to resolve quote token: {}", e);
+            tracing::error!("{}", error_msg);
+            ApiResponse::<()>::error(StatusCode::INTERNAL_SERVER_ERROR, error_msg)
+        })
+}
+
+// 辅助函数：unix 秒转换为 UTC 时间
+fn unix_to_datetime(secs: i64) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp(secs, 0)
+}
+
+fn invalid_time_range() -> (StatusCode, String) {
+    ApiResponse::<()>::error(
+        StatusCode::BAD_REQUEST,
+        "Invalid start_time/end_time parameter".to_string(),
     )
-}
\ No newline at end of file
+}
@@ -1,11 +1,13 @@
 use super::super::ApiState;
-use crate::database::operations::WalletOperations;
+use crate::database::operations::{WalletAttribution, WalletOperations};
+use crate::services::{CostBasisMethod, LpService, PnlService};
 use crate::types::*;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
+use chrono::{Duration, Utc};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -15,12 +17,29 @@ pub struct WalletQuery {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
     pub transaction_type: Option<String>, // swap, mint, burn
+    pub attribution: Option<String>,      // origin（默认）, recipient
 }
 
 #[derive(Debug, Deserialize)]
 pub struct WalletStatsQuery {
     pub chain_id: Option<i32>,
     pub days: Option<i32>,
+    pub attribution: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletPnLQuery {
+    pub chain_id: Option<i32>,
+    pub method: Option<String>, // fifo, average
+    pub days: Option<i32>,      // 不传则统计全部历史
+    pub attribution: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletLiquidityQuery {
+    pub chain_id: Option<i32>,
+    pub include_closed: Option<bool>,
+    pub attribution: Option<String>,
 }
 
 // Wallet相关handlers
@@ -31,6 +50,7 @@ pub async fn get_wallet_transactions(
 ) -> Result<Json<Vec<WalletTransaction>>, StatusCode> {
     let limit = params.limit.unwrap_or(50);
     let offset = params.offset.unwrap_or(0);
+    let attribution = parse_attribution(params.attribution.as_deref())?;
 
     match WalletOperations::get_wallet_transactions(
         state.database.pool(),
@@ -39,6 +59,7 @@ pub async fn get_wallet_transactions(
         limit,
         offset,
         params.transaction_type.as_deref(),
+        attribution,
     )
     .await
     {
@@ -56,11 +77,39 @@ pub async fn get_wallet_stats(
     State(state): State<ApiState>,
 ) -> Result<Json<WalletStats>, StatusCode> {
     let days = params.days.unwrap_or(30);
+    let attribution = parse_attribution(params.attribution.as_deref())?;
 
-    match WalletOperations::get_wallet_stats(state.database.pool(), &address.to_lowercase(), params.chain_id, days)
-        .await
+    let address = address.to_lowercase();
+
+    match WalletOperations::get_wallet_stats(
+        state.database.pool(),
+        &address,
+        params.chain_id,
+        days,
+        attribution,
+    )
+    .await
     {
-        Ok(Some(stats)) => Ok(Json(stats)),
+        Ok(Some(mut stats)) => {
+            let since = Utc::now() - Duration::days(days as i64);
+            match PnlService::wallet_pnl(
+                state.database.pool(),
+                &address,
+                params.chain_id,
+                CostBasisMethod::Fifo,
+                Some(since),
+                attribution,
+            )
+            .await
+            {
+                Ok(pnl) => {
+                    stats.profit_loss = pnl.total_pnl;
+                    stats.win_rate = pnl.win_rate;
+                }
+                Err(e) => tracing::error!("Failed to compute wallet pnl: {}", e),
+            }
+            Ok(Json(stats))
+        }
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Failed to get wallet stats: {}", e);
@@ -68,3 +117,95 @@ pub async fn get_wallet_stats(
         }
     }
 }
+
+pub async fn get_wallet_pnl(
+    Path(address): Path<String>,
+    Query(params): Query<WalletPnLQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<WalletPnL>, StatusCode> {
+    let method = match params.method.as_deref() {
+        Some(m) => CostBasisMethod::parse(m).ok_or(StatusCode::BAD_REQUEST)?,
+        None => CostBasisMethod::Fifo,
+    };
+    let since = params.days.map(|d| Utc::now() - Duration::days(d as i64));
+    let attribution = parse_attribution(params.attribution.as_deref())?;
+
+    match PnlService::wallet_pnl(
+        state.database.pool(),
+        &address.to_lowercase(),
+        params.chain_id,
+        method,
+        since,
+        attribution,
+    )
+    .await
+    {
+        Ok(pnl) => Ok(Json(pnl)),
+        Err(e) => {
+            tracing::error!("Failed to get wallet pnl: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_wallet_portfolio(
+    Path(address): Path<String>,
+    Query(params): Query<WalletPnLQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<WalletPortfolioItem>>, StatusCode> {
+    let method = match params.method.as_deref() {
+        Some(m) => CostBasisMethod::parse(m).ok_or(StatusCode::BAD_REQUEST)?,
+        None => CostBasisMethod::Fifo,
+    };
+    let attribution = parse_attribution(params.attribution.as_deref())?;
+
+    match PnlService::wallet_portfolio(
+        state.database.pool(),
+        &address.to_lowercase(),
+        params.chain_id,
+        method,
+        attribution,
+    )
+    .await
+    {
+        Ok(portfolio) => Ok(Json(portfolio)),
+        Err(e) => {
+            tracing::error!("Failed to get wallet portfolio: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_wallet_liquidity_positions(
+    Path(address): Path<String>,
+    Query(params): Query<WalletLiquidityQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<WalletLiquidityPosition>>, StatusCode> {
+    // Mint 事件没有接收方，LP 头寸只能按交易发起人归属
+    if parse_attribution(params.attribution.as_deref())? != WalletAttribution::Origin {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match LpService::wallet_positions(
+        state.database.pool(),
+        &address.to_lowercase(),
+        params.chain_id,
+        params.include_closed.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(positions) => Ok(Json(positions)),
+        Err(e) => {
+            tracing::error!("Failed to get wallet liquidity positions: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// 辅助函数：解析 `?attribution=` 参数，默认按交易发起人归属
+fn parse_attribution(value: Option<&str>) -> Result<WalletAttribution, StatusCode> {
+    match value {
+        Some(v) => WalletAttribution::parse(v).ok_or(StatusCode::BAD_REQUEST),
+        None => Ok(WalletAttribution::Origin),
+    }
+}
@@ -0,0 +1,45 @@
+use super::super::ApiState;
+use crate::database::operations::{WhaleFilter, WhaleOperations};
+use crate::types::*;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct WhaleQuery {
+    pub chain_id: Option<i32>,
+    pub pair: Option<String>,
+    pub wallet: Option<String>,
+    pub r#type: Option<String>, // swap / mint / burn
+    pub min_usd: Option<Decimal>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Whale handlers
+pub async fn get_whale_trades(
+    Query(params): Query<WhaleQuery>,
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<WhaleTrade>>, StatusCode> {
+    let filter = WhaleFilter {
+        chain_id: params.chain_id,
+        pair_address: params.pair.map(|p| p.to_lowercase()),
+        wallet: params.wallet.map(|w| w.to_lowercase()),
+        event_type: params.r#type,
+        min_usd: params.min_usd,
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    match WhaleOperations::get_whale_trades(state.database.pool(), &filter, limit, offset).await {
+        Ok(trades) => Ok(Json(trades)),
+        Err(e) => {
+            tracing::error!("Failed to get whale trades: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
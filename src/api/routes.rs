@@ -33,6 +33,14 @@ pub fn create_router(state: ApiState) -> Router {
             "/api/pairs/:chain_id/:address/stats",
             get(handlers::get_pair_stats),
         )
+        .route(
+            "/api/pairs/:chain_id/:address/leaderboard",
+            get(handlers::get_pair_leaderboard),
+        )
+        .route(
+            "/api/pairs/:chain_id/:address/twap",
+            get(handlers::get_pair_twap),
+        )
 
 
         // Token routes
@@ -44,6 +52,41 @@ pub fn create_router(state: ApiState) -> Router {
             "/api/tokens/:chain_id/:address",
             get(handlers::get_token_detail),
         )
+        .route(
+            "/api/tokens/:chain_id/:address/leaderboard",
+            get(handlers::get_token_leaderboard),
+        )
+
+
+        // Leaderboard routes
+        .route(
+            "/api/chains/:chain_id/leaderboard",
+            get(handlers::get_chain_leaderboard),
+        )
+
+
+        // Trade feed routes
+        .route("/api/trades", get(handlers::get_trades))
+
+
+        // MEV routes
+        .route("/api/mev/sandwiches", get(handlers::get_sandwiches))
+
+
+        // Alert routes
+        .route("/api/alerts", get(handlers::get_alerts))
+
+
+        // Quote routes
+        .route("/api/quote", get(handlers::get_swap_quote))
+
+
+        // Arbitrage routes
+        .route("/api/arbitrage", get(handlers::get_arbitrage_opportunities))
+
+
+        // Whale routes
+        .route("/api/whales", get(handlers::get_whale_trades))
 
 
         // Wallet routes
@@ -55,6 +98,18 @@ pub fn create_router(state: ApiState) -> Router {
             "/api/wallets/:address/stats",
             get(handlers::get_wallet_stats),
         )
+        .route(
+            "/api/wallets/:address/pnl",
+            get(handlers::get_wallet_pnl),
+        )
+        .route(
+            "/api/wallets/:address/portfolio",
+            get(handlers::get_wallet_portfolio),
+        )
+        .route(
+            "/api/wallets/:address/liquidity",
+            get(handlers::get_wallet_liquidity_positions),
+        )
         
 
         // Token metadata management routes
@@ -66,6 +121,10 @@ pub fn create_router(state: ApiState) -> Router {
             "/api/metadata/tokens/:chain_id/:address",
             get(handlers::get_token_metadata).delete(handlers::delete_token_metadata),
         )
+        .route(
+            "/api/metadata/tokens/:chain_id/:address/supply-exclusions",
+            get(handlers::get_supply_exclusions).put(handlers::update_supply_exclusions),
+        )
         
         
         // Status routes
@@ -154,6 +154,9 @@ fn get_channel_for_event(event_type: &str) -> String {
         "new_burn" | "burn_event" => "liquidity".to_string(),
         "price_update" => "prices".to_string(),
         "volume_update" => "volume".to_string(),
+        "alert" => "alerts".to_string(),
+        "arbitrage" => "arbitrage".to_string(),
+        "whale" => "whales".to_string(),
         _ => "general".to_string(),
     }
 }
@@ -239,3 +242,30 @@ pub fn send_liquidity_event(
 
     let _ = sender.send(event.to_string());
 }
+
+pub fn send_alert_event(sender: &broadcast::Sender<String>, alert: &Alert) {
+    let event = serde_json::json!({
+        "type": "alert",
+        "data": alert
+    });
+
+    let _ = sender.send(event.to_string());
+}
+
+pub fn send_arbitrage_event(sender: &broadcast::Sender<String>, opportunity: &ArbitrageOpportunity) {
+    let event = serde_json::json!({
+        "type": "arbitrage",
+        "data": opportunity
+    });
+
+    let _ = sender.send(event.to_string());
+}
+
+pub fn send_whale_event(sender: &broadcast::Sender<String>, trade: &WhaleTrade) {
+    let event = serde_json::json!({
+        "type": "whale",
+        "data": trade
+    });
+
+    let _ = sender.send(event.to_string());
+}
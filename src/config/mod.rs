@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -7,6 +8,17 @@ pub struct Config {
     pub chains: HashMap<u64, ChainConfig>, // 保持u64作为chain_id的key
     pub server: ServerConfig,
     pub defaults: DefaultConfig,
+    pub maintenance: MaintenanceConfig,
+    pub valuation: ValuationConfig,
+    pub supply: SupplyConfig,
+    pub trades: TradeConfig,
+    pub mev: MevConfig,
+    pub alerts: AlertConfig,
+    pub risk: RiskConfig,
+    pub tax: TaxConfig,
+    pub trending: TrendingConfig,
+    pub twap: TwapConfig,
+    pub arbitrage: ArbitrageConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -25,6 +37,20 @@ pub struct ChainConfig {
     pub poll_interval: u64,
     pub enabled: bool,
     pub block_batch_size: u64,
+    /// 计价代币地址（小写），按优先级排列：稳定币 > 包装原生币 > 其他
+    pub quote_tokens: Vec<String>,
+    /// 美元稳定币地址（小写），估值时按 1 USD 作为价格锚点
+    pub stable_tokens: Vec<String>,
+    /// 计算流通量时排除的持有地址（小写），对该链所有代币生效，通常为销毁地址
+    pub supply_excluded_addresses: Vec<String>,
+    /// 工厂的 swap 手续费率（基点），按输入数量收取，Uniswap V2 为 30
+    pub swap_fee_bps: u32,
+    /// 已知的路由 / 聚合器合约：(小写地址, 标签)，用于区分合约与终端用户
+    pub router_addresses: Vec<(String, Option<String>)>,
+    /// LP 锁仓合约地址（小写），与销毁地址一起计入已锁定的 LP
+    pub lp_locker_addresses: Vec<String>,
+    /// 巨鲸交易阈值，可按交易对覆盖
+    pub whales: WhaleConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -36,6 +62,153 @@ pub struct ServerConfig {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DefaultConfig {
     pub block_batch_size: u64,
+    /// 各链未单独配置时的巨鲸阈值
+    pub whale: WhaleThresholds,
+}
+
+/// 巨鲸交易阈值：美元价值或占池子储备比例任一达到即标记，0 表示不启用该项
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WhaleThresholds {
+    pub min_usd: Decimal,
+    /// 单笔 swap 输入 / mint 存入 / burn 取出数量占事件前对应一侧储备量的比例（%）
+    pub reserve_pct: Decimal,
+}
+
+/// 单条链的巨鲸阈值，`pairs` 按交易对地址（小写）覆盖链级阈值
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WhaleConfig {
+    pub default: WhaleThresholds,
+    pub pairs: HashMap<String, WhaleThresholds>,
+}
+
+impl WhaleConfig {
+    pub fn thresholds(&self, pair_address: &str) -> &WhaleThresholds {
+        self.pairs.get(pair_address).unwrap_or(&self.default)
+    }
+}
+
+/// 数据库维护任务配置（分区、数据保留、VACUUM）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaintenanceConfig {
+    pub interval_secs: u64,
+    /// 原始事件保留天数，0 表示永久保留
+    pub event_retention_days: u32,
+    /// 提前创建的月分区数量
+    pub partition_premake_months: u32,
+    /// VACUUM 时处理的最近月分区数量
+    pub vacuum_hot_partitions: usize,
+    /// 套利机会保留天数
+    pub arbitrage_retention_days: u32,
+}
+
+/// USD 估值任务配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ValuationConfig {
+    pub interval_secs: u64,
+    /// 参与价格推导的交易对最低流动性（USD），过滤薄池操纵
+    pub min_liquidity_usd: u64,
+    /// 从锚点出发的最大推导跳数
+    pub max_hops: u32,
+    /// 补算 USD 成交量/价值时回看的天数
+    pub backfill_days: u32,
+}
+
+/// 代币供应量刷新任务配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SupplyConfig {
+    pub interval_secs: u64,
+    /// 供应量记录超过该时长（秒）视为过期，需要重新读取
+    pub refresh_after_secs: u64,
+    /// 每条链每轮最多读取的代币数量
+    pub batch_size: i64,
+}
+
+/// 多跳成交重建任务配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TradeConfig {
+    pub interval_secs: u64,
+    /// 每条链每轮最多处理的区块数
+    pub batch_blocks: u64,
+}
+
+/// 夹子攻击检测任务配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MevConfig {
+    pub interval_secs: u64,
+    /// 每条链每轮最多处理的区块数
+    pub batch_blocks: u64,
+}
+
+/// 撤池 / 跑路告警配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlertConfig {
+    pub interval_secs: u64,
+    /// 单笔 burn 取出池子流动性的比例（%）达到该值时告警
+    pub large_burn_pct: u32,
+    /// 窗口内计价代币储备下降比例（%）达到该值时告警
+    pub reserve_drop_pct: u32,
+    pub reserve_window_mins: u32,
+    /// 首个流动性提供者（通常为部署者）持有的 LP 份额（%）达到该值时告警
+    pub lp_concentration_pct: u32,
+    /// 池子美元流动性低于该值时不告警
+    pub min_liquidity_usd: u64,
+}
+
+/// 新代币风险评分配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RiskConfig {
+    pub interval_secs: u64,
+    /// 创建时间在该窗口内的交易对会定期重新检查 LP 锁仓和转账税
+    pub window_hours: u32,
+    /// 已锁定（销毁或锁仓合约持有）的 LP 比例（%）低于该值时视为未锁定
+    pub lp_locked_pct: u32,
+    /// 每条链每轮最多检查的交易对数量
+    pub batch_size: i64,
+}
+
+/// 转账税检测配置：对需要采样的 swap 读取交易回执比对 Transfer 日志
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaxConfig {
+    /// 每个代币至少采样的 swap 数量，之后未检测到转账税的代币按 `resample_secs` 间隔抽查
+    pub sample_swaps: i64,
+    pub resample_secs: u64,
+}
+
+/// 热门代币评分配置，各分项归一化到 0-1 后按权重加权，由估值服务每轮刷新视图后重算
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrendingConfig {
+    /// 最近 1 小时成交量相对前 24 小时平均每小时成交量的加速度
+    pub volume_weight: Decimal,
+    /// 最近 1 小时独立交易者相对前 24 小时平均每小时的增长
+    pub trader_weight: Decimal,
+    /// 1 小时涨幅
+    pub price_weight: Decimal,
+    pub liquidity_weight: Decimal,
+    /// 流动性低于该值（USD）的代币不参与排名
+    pub min_liquidity_usd: u64,
+}
+
+/// TWAP 采样配置：定期在最新区块读取交易对的累计价格和储备量
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TwapConfig {
+    pub interval_secs: u64,
+    /// 只采样美元 TVL 不低于该值的交易对
+    pub min_liquidity_usd: u64,
+    /// 每条链每轮最多采样的交易对数量，按 TVL 从高到低
+    pub max_pairs: i64,
+    /// 采样保留时长，决定可查询的最长 TWAP 窗口
+    pub retention_hours: u32,
+}
+
+/// 循环套利检测配置：交换事件监听器每处理完一批区块后，扫描经过本批次有事件的交易对的环路
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArbitrageConfig {
+    /// 扣除手续费和价格冲击后的预期利润（USD）低于该值的机会不记录，未扣除 gas
+    pub min_profit_usd: Decimal,
+    /// 只有美元 TVL 不低于该值的交易对参与环路，过滤薄池
+    pub min_liquidity_usd: u64,
+    /// 环路最多经过的交易对数量（2-4）
+    pub max_hops: usize,
 }
 
 impl Config {
@@ -49,6 +222,10 @@ impl Config {
 
         let defaults = DefaultConfig {
             block_batch_size: env_var_or_default("DEFAULT_BLOCK_BATCH_SIZE", 1000)?,
+            whale: WhaleThresholds {
+                min_usd: env_var_or_default("WHALE_MIN_USD", Decimal::from(100_000))?,
+                reserve_pct: env_var_or_default("WHALE_RESERVE_PCT", Decimal::from(5))?,
+            },
         };
 
         let chains = Self::load_configured_chains(&defaults)?;
@@ -68,6 +245,68 @@ impl Config {
                 port: env_var_or_default("SERVER_PORT", 3000)?,
             },
             defaults,
+            maintenance: MaintenanceConfig {
+                interval_secs: env_var_or_default("MAINTENANCE_INTERVAL", 3600)?,
+                event_retention_days: env_var_or_default("EVENT_RETENTION_DAYS", 0)?,
+                partition_premake_months: env_var_or_default("PARTITION_PREMAKE_MONTHS", 2)?,
+                vacuum_hot_partitions: env_var_or_default("VACUUM_HOT_PARTITIONS", 2)?,
+                arbitrage_retention_days: env_var_or_default("ARBITRAGE_RETENTION_DAYS", 7)?,
+            },
+            valuation: ValuationConfig {
+                interval_secs: env_var_or_default("VALUATION_INTERVAL", 60)?,
+                min_liquidity_usd: env_var_or_default("VALUATION_MIN_LIQUIDITY_USD", 1000)?,
+                max_hops: env_var_or_default("VALUATION_MAX_HOPS", 3)?,
+                backfill_days: env_var_or_default("VALUATION_BACKFILL_DAYS", 7)?,
+            },
+            supply: SupplyConfig {
+                interval_secs: env_var_or_default("SUPPLY_INTERVAL", 300)?,
+                refresh_after_secs: env_var_or_default("SUPPLY_REFRESH_AFTER", 3600)?,
+                batch_size: env_var_or_default("SUPPLY_BATCH_SIZE", 200)?,
+            },
+            trades: TradeConfig {
+                interval_secs: env_var_or_default("TRADE_INTERVAL", 15)?,
+                batch_blocks: env_var_or_default("TRADE_BATCH_BLOCKS", 5000)?,
+            },
+            mev: MevConfig {
+                interval_secs: env_var_or_default("MEV_INTERVAL", 30)?,
+                batch_blocks: env_var_or_default("MEV_BATCH_BLOCKS", 5000)?,
+            },
+            alerts: AlertConfig {
+                interval_secs: env_var_or_default("ALERT_INTERVAL", 60)?,
+                large_burn_pct: env_var_or_default("ALERT_LARGE_BURN_PCT", 50)?,
+                reserve_drop_pct: env_var_or_default("ALERT_RESERVE_DROP_PCT", 50)?,
+                reserve_window_mins: env_var_or_default("ALERT_RESERVE_WINDOW_MINS", 60)?,
+                lp_concentration_pct: env_var_or_default("ALERT_LP_CONCENTRATION_PCT", 80)?,
+                min_liquidity_usd: env_var_or_default("ALERT_MIN_LIQUIDITY_USD", 1000)?,
+            },
+            risk: RiskConfig {
+                interval_secs: env_var_or_default("RISK_INTERVAL", 300)?,
+                window_hours: env_var_or_default("RISK_WINDOW_HOURS", 72)?,
+                lp_locked_pct: env_var_or_default("RISK_LP_LOCKED_PCT", 80)?,
+                batch_size: env_var_or_default("RISK_BATCH_SIZE", 100)?,
+            },
+            tax: TaxConfig {
+                sample_swaps: env_var_or_default("TAX_SAMPLE_SWAPS", 5)?,
+                resample_secs: env_var_or_default("TAX_RESAMPLE_SECS", 3600)?,
+            },
+            trending: TrendingConfig {
+                volume_weight: env_var_or_default("TRENDING_VOLUME_WEIGHT", Decimal::new(35, 2))?,
+                trader_weight: env_var_or_default("TRENDING_TRADER_WEIGHT", Decimal::new(25, 2))?,
+                price_weight: env_var_or_default("TRENDING_PRICE_WEIGHT", Decimal::new(20, 2))?,
+                liquidity_weight: env_var_or_default("TRENDING_LIQUIDITY_WEIGHT", Decimal::new(20, 2))?,
+                min_liquidity_usd: env_var_or_default("TRENDING_MIN_LIQUIDITY_USD", 5000)?,
+            },
+            twap: TwapConfig {
+                interval_secs: env_var_or_default("TWAP_INTERVAL", 60)?,
+                min_liquidity_usd: env_var_or_default("TWAP_MIN_LIQUIDITY_USD", 10000)?,
+                max_pairs: env_var_or_default("TWAP_MAX_PAIRS", 200)?,
+                retention_hours: env_var_or_default("TWAP_RETENTION_HOURS", 48)?,
+            },
+            arbitrage: ArbitrageConfig {
+                min_profit_usd: env_var_or_default("ARBITRAGE_MIN_PROFIT_USD", Decimal::from(10))?,
+                min_liquidity_usd: env_var_or_default("ARBITRAGE_MIN_LIQUIDITY_USD", 10000)?,
+                max_hops: env_var_or_default::<usize>("ARBITRAGE_MAX_HOPS", 3)?.clamp(2, 4),
+            },
         })
     }
 
@@ -83,6 +322,21 @@ impl Config {
 
         for (chain_id, name, prefix) in supported_chains {
             if Self::is_chain_configured(prefix) {
+                let whale = WhaleThresholds {
+                    min_usd: env_var_or_default(
+                        &format!("{}_WHALE_MIN_USD", prefix),
+                        defaults.whale.min_usd,
+                    )?,
+                    reserve_pct: env_var_or_default(
+                        &format!("{}_WHALE_RESERVE_PCT", prefix),
+                        defaults.whale.reserve_pct,
+                    )?,
+                };
+                let whale_pairs = parse_whale_pairs(
+                    &env_var_or_default(&format!("{}_WHALE_PAIRS", prefix), String::new())?,
+                    &whale,
+                )?;
+
                 chains.insert(
                     chain_id,
                     ChainConfig {
@@ -97,6 +351,31 @@ impl Config {
                             &format!("{}_BLOCK_BATCH_SIZE", prefix),
                             defaults.block_batch_size,
                         )?,
+                        quote_tokens: parse_address_list(&env_var_or_default(
+                            &format!("{}_QUOTE_TOKENS", prefix),
+                            default_quote_tokens(chain_id).to_string(),
+                        )?),
+                        stable_tokens: parse_address_list(&env_var_or_default(
+                            &format!("{}_STABLE_TOKENS", prefix),
+                            default_stable_tokens(chain_id).to_string(),
+                        )?),
+                        supply_excluded_addresses: parse_address_list(&env_var_or_default(
+                            &format!("{}_SUPPLY_EXCLUDED_ADDRESSES", prefix),
+                            DEFAULT_SUPPLY_EXCLUDED_ADDRESSES.to_string(),
+                        )?),
+                        swap_fee_bps: env_var_or_default(&format!("{}_SWAP_FEE_BPS", prefix), 30)?,
+                        router_addresses: parse_labeled_address_list(&env_var_or_default(
+                            &format!("{}_ROUTER_ADDRESSES", prefix),
+                            default_router_addresses(chain_id).to_string(),
+                        )?),
+                        lp_locker_addresses: parse_address_list(&env_var_or_default(
+                            &format!("{}_LP_LOCKERS", prefix),
+                            String::new(),
+                        )?),
+                        whales: WhaleConfig {
+                            default: whale,
+                            pairs: whale_pairs,
+                        },
                     },
                 );
             }
@@ -124,7 +403,57 @@ impl Config {
         println!("DB_MAX_CONNECTIONS=10");
         println!("SERVER_HOST=0.0.0.0");
         println!("SERVER_PORT=3000");
-        println!("DEFAULT_BLOCK_BATCH_SIZE=1000\n");
+        println!("DEFAULT_BLOCK_BATCH_SIZE=1000");
+        println!("WHALE_MIN_USD=100000 (巨鲸交易美元阈值，0 表示不启用)");
+        println!("WHALE_RESERVE_PCT=5 (巨鲸交易占池子储备比例阈值 %，0 表示不启用)");
+        println!("<PREFIX>_QUOTE_TOKENS=0x...,0x... (计价代币，按优先级排列：稳定币 > 包装原生币)");
+        println!("<PREFIX>_STABLE_TOKENS=0x...,0x... (美元稳定币，USD 估值锚点)");
+        println!("<PREFIX>_SUPPLY_EXCLUDED_ADDRESSES=0x...,0x... (不计入流通量的地址，默认为销毁地址)");
+        println!("<PREFIX>_SWAP_FEE_BPS=30 (工厂 swap 手续费率，基点)");
+        println!("<PREFIX>_ROUTER_ADDRESSES=0x...:label,0x... (已知路由/聚合器合约，标签可选)");
+        println!("<PREFIX>_LP_LOCKERS=0x...,0x... (LP 锁仓合约，持有的 LP 视为已锁定)");
+        println!("<PREFIX>_WHALE_MIN_USD / <PREFIX>_WHALE_RESERVE_PCT (覆盖该链的巨鲸阈值)");
+        println!("<PREFIX>_WHALE_PAIRS=0xpair:min_usd:reserve_pct,... (按交易对覆盖，留空的项沿用链级阈值)");
+        println!("MAINTENANCE_INTERVAL=3600");
+        println!("EVENT_RETENTION_DAYS=0 (0 表示永久保留原始事件)");
+        println!("PARTITION_PREMAKE_MONTHS=2");
+        println!("VACUUM_HOT_PARTITIONS=2");
+        println!("ARBITRAGE_RETENTION_DAYS=7");
+        println!("VALUATION_INTERVAL=60");
+        println!("VALUATION_MIN_LIQUIDITY_USD=1000");
+        println!("VALUATION_MAX_HOPS=3");
+        println!("VALUATION_BACKFILL_DAYS=7");
+        println!("SUPPLY_INTERVAL=300");
+        println!("SUPPLY_REFRESH_AFTER=3600");
+        println!("SUPPLY_BATCH_SIZE=200");
+        println!("TRADE_INTERVAL=15");
+        println!("TRADE_BATCH_BLOCKS=5000");
+        println!("MEV_INTERVAL=30");
+        println!("MEV_BATCH_BLOCKS=5000");
+        println!("ALERT_INTERVAL=60");
+        println!("ALERT_LARGE_BURN_PCT=50");
+        println!("ALERT_RESERVE_DROP_PCT=50");
+        println!("ALERT_RESERVE_WINDOW_MINS=60");
+        println!("ALERT_LP_CONCENTRATION_PCT=80");
+        println!("ALERT_MIN_LIQUIDITY_USD=1000");
+        println!("RISK_INTERVAL=300");
+        println!("RISK_WINDOW_HOURS=72");
+        println!("RISK_LP_LOCKED_PCT=80");
+        println!("RISK_BATCH_SIZE=100");
+        println!("TAX_SAMPLE_SWAPS=5");
+        println!("TAX_RESAMPLE_SECS=3600");
+        println!("TRENDING_VOLUME_WEIGHT=0.35");
+        println!("TRENDING_TRADER_WEIGHT=0.25");
+        println!("TRENDING_PRICE_WEIGHT=0.20");
+        println!("TRENDING_LIQUIDITY_WEIGHT=0.20");
+        println!("TRENDING_MIN_LIQUIDITY_USD=5000");
+        println!("TWAP_INTERVAL=60");
+        println!("TWAP_MIN_LIQUIDITY_USD=10000");
+        println!("TWAP_MAX_PAIRS=200");
+        println!("TWAP_RETENTION_HOURS=48");
+        println!("ARBITRAGE_MIN_PROFIT_USD=10 (未扣除 gas)");
+        println!("ARBITRAGE_MIN_LIQUIDITY_USD=10000");
+        println!("ARBITRAGE_MAX_HOPS=3 (2-4)\n");
 
         println!("[支持的链]");
         println!("NOS (chain_id: 2643): NOS_RPC_URL, NOS_FACTORY_ADDRESS");
@@ -146,6 +475,87 @@ where
     }
 }
 
+/// 解析逗号分隔的地址列表，统一为小写
+fn parse_address_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// 解析逗号分隔的 `地址[:标签]` 列表，地址统一为小写
+fn parse_labeled_address_list(value: &str) -> Vec<(String, Option<String>)> {
+    parse_address_list(value)
+        .into_iter()
+        .map(|item| match item.split_once(':') {
+            Some((address, label)) => (
+                address.trim().to_string(),
+                Some(label.trim().to_string()).filter(|l| !l.is_empty()),
+            ),
+            None => (item, None),
+        })
+        .collect()
+}
+
+/// 解析逗号分隔的 `交易对地址:美元阈值:储备比例阈值` 列表，留空的阈值沿用 `default`
+fn parse_whale_pairs(
+    value: &str,
+    default: &WhaleThresholds,
+) -> anyhow::Result<HashMap<String, WhaleThresholds>> {
+    let threshold = |part: Option<&str>, fallback: Decimal| -> anyhow::Result<Decimal> {
+        match part.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(p) => p
+                .parse()
+                .map_err(|e| anyhow::anyhow!("巨鲸阈值 {} 解析失败: {}", p, e)),
+            None => Ok(fallback),
+        }
+    };
+
+    parse_address_list(value)
+        .into_iter()
+        .map(|item| {
+            let mut parts = item.split(':');
+            let pair = parts.next().unwrap_or_default().trim().to_string();
+            let thresholds = WhaleThresholds {
+                min_usd: threshold(parts.next(), default.min_usd)?,
+                reserve_pct: threshold(parts.next(), default.reserve_pct)?,
+            };
+            Ok((pair, thresholds))
+        })
+        .collect()
+}
+
+/// 各链默认的计价代币优先级
+fn default_quote_tokens(chain_id: u64) -> &'static str {
+    match chain_id {
+        // USDC, USDT, DAI, WETH
+        1 => "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48,0xdac17f958d2ee523a2206206994597c13d831ec7,0x6b175474e89094c44da98b954eedeac495271d0f,0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        _ => "",
+    }
+}
+
+/// 各链默认的美元稳定币
+fn default_stable_tokens(chain_id: u64) -> &'static str {
+    match chain_id {
+        // USDC, USDT, DAI
+        1 => "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48,0xdac17f958d2ee523a2206206994597c13d831ec7,0x6b175474e89094c44da98b954eedeac495271d0f",
+        _ => "",
+    }
+}
+
+/// 各链默认的已知路由 / 聚合器合约
+fn default_router_addresses(chain_id: u64) -> &'static str {
+    match chain_id {
+        1 => "0x7a250d5630b4cf539739df2c5dacb4c659f2488d:uniswap_v2_router,0x3fc91a3afd70395cd496c647d5a6cc9d4b2b7fad:uniswap_universal_router,0xef1c6e67703c7bd7107eed8303fbe6ec2554bf6b:uniswap_universal_router_v1,0x1111111254eeb25477b68fb85ed929f73a960582:1inch_v5,0x111111125421ca6dc452d289314280a0f8842a65:1inch_v6,0xdef1c0ded9bec7f1a1670819833240f027b25eff:0x_exchange_proxy",
+        _ => "",
+    }
+}
+
+/// 默认不计入流通量的销毁地址
+const DEFAULT_SUPPLY_EXCLUDED_ADDRESSES: &str =
+    "0x0000000000000000000000000000000000000000,0x000000000000000000000000000000000000dead";
+
 fn required_env_var(key: &str) -> anyhow::Result<String> {
     std::env::var(key).map_err(|_| anyhow::anyhow!("缺少必需配置: {}", key))
 }
\ No newline at end of file
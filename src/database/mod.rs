@@ -34,13 +34,11 @@ impl Database {
         // indexes?;
         // views?;
         // Ok(())
-        let (tables, indexes) = tokio::join!(
-            SystemOperations::create_tables(&self.pool),
-            SystemOperations::create_indexes(&self.pool),
-        );
-
-        tables?;
-        indexes?;
+        // 索引依赖表（以及分区父表）已存在，必须顺序执行
+        SystemOperations::create_tables(&self.pool).await?;
+        SystemOperations::create_indexes(&self.pool).await?;
+        PartitionOperations::premake_partitions(&self.pool, 1).await?;
+        SystemOperations::create_views(&self.pool).await?;
         Ok(())
     }
 }
@@ -0,0 +1,145 @@
+use crate::database::utils::*;
+use crate::types::{SwapEvent, TradeActivity, TradeActivityWindows};
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgConnection, PgPool};
+
+/// 聚合桶宽度（分钟）
+pub const ACTIVITY_BUCKET_MINUTES: i32 = 5;
+
+/// 统计窗口：(列名后缀, 分钟数)
+pub const ACTIVITY_WINDOWS: [(&str, i32); 4] = [("5m", 5), ("1h", 60), ("6h", 360), ("24h", 1440)];
+
+/// 钱包 × 交易对 × 5 分钟的增量聚合（wallet_pair_activity_5m），只保留最近 24 小时。
+/// swap 写入时累加，估值服务补算美元成交量时把补上的部分再累加；buy / sell 为 swap 原始方向（buy = token0 换 token1），
+/// 查询时再按计价方向或代币位置换算成买入 / 卖出
+pub struct ActivityOperations;
+
+impl ActivityOperations {
+    /// 累加一笔新写入的 swap，钱包为交易发起人
+    pub async fn apply_swap(conn: &mut PgConnection, event: &SwapEvent) -> Result<()> {
+        let is_buy = match event.side.as_deref() {
+            Some("buy") => true,
+            Some("sell") => false,
+            _ => return Ok(()),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_pair_activity_5m
+                (chain_id, pair_address, wallet_address, bucket, buy_count, sell_count, buy_volume_usd, sell_volume_usd)
+            SELECT $1, $2, $3, candle_bucket('5m', $4),
+                   CASE WHEN $5 THEN 1 ELSE 0 END,
+                   CASE WHEN $5 THEN 0 ELSE 1 END,
+                   CASE WHEN $5 THEN COALESCE($6, 0) ELSE 0 END,
+                   CASE WHEN $5 THEN 0 ELSE COALESCE($6, 0) END
+            WHERE $4 >= NOW() - INTERVAL '24 hours'
+            ON CONFLICT (chain_id, pair_address, bucket, wallet_address) DO UPDATE SET
+                buy_count = wallet_pair_activity_5m.buy_count + EXCLUDED.buy_count,
+                sell_count = wallet_pair_activity_5m.sell_count + EXCLUDED.sell_count,
+                buy_volume_usd = wallet_pair_activity_5m.buy_volume_usd + EXCLUDED.buy_volume_usd,
+                sell_volume_usd = wallet_pair_activity_5m.sell_volume_usd + EXCLUDED.sell_volume_usd,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(event.chain_id)
+        .bind(&event.pair_address)
+        .bind(&event.sender)
+        .bind(event.timestamp)
+        .bind(is_buy)
+        .bind(event.volume_usd)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 交易对的活跃度，买入 / 卖出指用计价代币买入 / 卖出基础代币
+    pub async fn get_pair_activity(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        quote_side: i32,
+    ) -> Result<TradeActivityWindows> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM (
+                SELECT wallet_address, bucket,
+                       CASE WHEN $3 = 0 THEN buy_count ELSE sell_count END AS buys,
+                       CASE WHEN $3 = 0 THEN sell_count ELSE buy_count END AS sells,
+                       CASE WHEN $3 = 0 THEN buy_volume_usd ELSE sell_volume_usd END AS buy_volume_usd,
+                       CASE WHEN $3 = 0 THEN sell_volume_usd ELSE buy_volume_usd END AS sell_volume_usd
+                FROM wallet_pair_activity_5m
+                WHERE chain_id = $1 AND pair_address = $2
+                AND bucket > NOW() - INTERVAL '1445 minutes'
+            ) a
+            "#,
+            Self::window_columns()
+        );
+
+        let row = sqlx::query(&query)
+            .bind(chain_id)
+            .bind(pair_address)
+            .bind(quote_side)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(row_to_activity_windows(&row))
+    }
+
+    /// 删除 24 小时窗口之外的桶，返回删除的行数
+    pub async fn prune(pool: &PgPool) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM wallet_pair_activity_5m WHERE bucket <= NOW() - INTERVAL '1445 minutes'",
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 各窗口的聚合列，数据源需提供 wallet_address、bucket、buys、sells、buy_volume_usd、sell_volume_usd。
+    /// 窗口包含与之重叠的桶，因此最多多统计一个桶宽度
+    pub fn window_columns() -> String {
+        ACTIVITY_WINDOWS
+            .iter()
+            .map(|(label, minutes)| {
+                let window = format!(
+                    "bucket > NOW() - INTERVAL '{} minutes'",
+                    minutes + ACTIVITY_BUCKET_MINUTES
+                );
+                format!(
+                    r#"
+                COUNT(DISTINCT wallet_address) FILTER (WHERE {window}) AS traders_{label},
+                COUNT(DISTINCT wallet_address) FILTER (WHERE {window} AND buys > 0) AS buyers_{label},
+                COUNT(DISTINCT wallet_address) FILTER (WHERE {window} AND sells > 0) AS sellers_{label},
+                COALESCE(SUM(buys) FILTER (WHERE {window}), 0)::BIGINT AS buys_{label},
+                COALESCE(SUM(sells) FILTER (WHERE {window}), 0)::BIGINT AS sells_{label},
+                COALESCE(SUM(buy_volume_usd) FILTER (WHERE {window}), 0) AS buy_volume_{label},
+                COALESCE(SUM(sell_volume_usd) FILTER (WHERE {window}), 0) AS sell_volume_{label}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// 读取 `window_columns` 生成的列，没有成交的窗口为 0
+pub fn row_to_activity_windows(row: &PgRow) -> TradeActivityWindows {
+    let window = |label: &str| TradeActivity {
+        unique_traders: safe_get_i64(row, &format!("traders_{}", label)),
+        unique_buyers: safe_get_i64(row, &format!("buyers_{}", label)),
+        unique_sellers: safe_get_i64(row, &format!("sellers_{}", label)),
+        buy_count: safe_get_i64(row, &format!("buys_{}", label)),
+        sell_count: safe_get_i64(row, &format!("sells_{}", label)),
+        buy_volume_usd: safe_get_decimal(row, &format!("buy_volume_{}", label)),
+        sell_volume_usd: safe_get_decimal(row, &format!("sell_volume_{}", label)),
+    };
+
+    TradeActivityWindows {
+        m5: window("5m"),
+        h1: window("1h"),
+        h6: window("6h"),
+        h24: window("24h"),
+    }
+}
@@ -0,0 +1,388 @@
+use crate::config::AlertConfig;
+use crate::database::utils::*;
+use crate::types::{Alert, BurnEvent};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+/// 待写入的告警
+#[derive(Debug, Clone)]
+pub struct NewAlert {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub alert_type: &'static str,
+    pub severity: &'static str,
+    pub wallet: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub message: String,
+    pub details: serde_json::Value,
+    /// 同一 (chain_id, dedup_key) 只告警一次
+    pub dedup_key: String,
+}
+
+/// 告警查询条件
+#[derive(Debug, Clone, Default)]
+pub struct AlertFilter {
+    pub chain_id: Option<i32>,
+    pub pair_address: Option<String>,
+    pub alert_type: Option<String>,
+    pub severity: Option<String>,
+}
+
+pub const ALERT_LARGE_BURN: &str = "large_burn";
+pub const ALERT_RESERVE_DROP: &str = "reserve_drop";
+pub const ALERT_LP_CONCENTRATION: &str = "lp_concentration";
+
+/// 单笔 burn 取出比例达到该值（%）时升级为 critical
+const CRITICAL_BURN_PCT: i64 = 90;
+
+pub struct AlertOperations;
+
+impl AlertOperations {
+    /// 写入告警，已存在相同 dedup_key 时返回 None
+    pub async fn insert_alert(pool: &PgPool, alert: &NewAlert) -> Result<Option<Alert>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO alerts (
+                chain_id, pair_address, alert_type, severity, wallet, transaction_hash, block_number,
+                message, details, dedup_key
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (chain_id, dedup_key) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(alert.chain_id)
+        .bind(&alert.pair_address)
+        .bind(alert.alert_type)
+        .bind(alert.severity)
+        .bind(&alert.wallet)
+        .bind(&alert.transaction_hash)
+        .bind(alert.block_number)
+        .bind(&alert.message)
+        .bind(&alert.details)
+        .bind(&alert.dedup_key)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::row_to_alert))
+    }
+
+    /// 新写入的 burn 是否取出了池子的大部分流动性。
+    /// 监听器按日志顺序处理，burn 之前的 Sync 已写入 pair_reserves，取出前的储备 = 当前储备 + 取出数量
+    pub async fn check_large_burn(
+        pool: &PgPool,
+        burn: &BurnEvent,
+        config: &AlertConfig,
+    ) -> Result<Option<Alert>> {
+        let row = sqlx::query(
+            r#"
+            WITH pre AS (
+                SELECT pr.reserve0 + $3 AS reserve0, pr.reserve1 + $4 AS reserve1
+                FROM pair_reserves pr
+                WHERE pr.chain_id = $1 AND pr.pair_address = $2
+            )
+            SELECT
+                trim_scale(round(GREATEST($3 / NULLIF(pre.reserve0, 0), $4 / NULLIF(pre.reserve1, 0)) * 100, 4)) as share_pct,
+                pair_liquidity_usd($1, $2, pre.reserve0, pre.reserve1) as pool_usd,
+                pair_liquidity_usd($1, $2, $3, $4) as removed_usd,
+                (SELECT m.sender FROM mint_events m
+                 WHERE m.chain_id = $1 AND m.pair_address = $2
+                 ORDER BY m.block_number, m.log_index
+                 LIMIT 1) as first_provider
+            FROM pre
+            "#,
+        )
+        .bind(burn.chain_id)
+        .bind(&burn.pair_address)
+        .bind(burn.amount0)
+        .bind(burn.amount1)
+        .fetch_optional(pool)
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let share_pct = match safe_get_optional_decimal(&row, "share_pct") {
+            Some(share) if share >= Decimal::from(config.large_burn_pct) => share,
+            _ => return Ok(None),
+        };
+        let pool_usd = safe_get_optional_decimal(&row, "pool_usd");
+        if pool_usd.unwrap_or(Decimal::ZERO) < Decimal::from(config.min_liquidity_usd) {
+            return Ok(None);
+        }
+
+        let by_first_provider =
+            safe_get_optional_string(&row, "first_provider").as_deref() == Some(burn.sender.as_str());
+        let severity = if by_first_provider || share_pct >= Decimal::from(CRITICAL_BURN_PCT) {
+            "critical"
+        } else {
+            "warning"
+        };
+
+        let alert = NewAlert {
+            chain_id: burn.chain_id,
+            pair_address: burn.pair_address.clone(),
+            alert_type: ALERT_LARGE_BURN,
+            severity,
+            wallet: Some(burn.sender.clone()),
+            transaction_hash: Some(burn.transaction_hash.clone()),
+            block_number: Some(burn.block_number),
+            message: format!(
+                "Burn removed {}% of pool liquidity{}",
+                share_pct.round_dp(2),
+                if by_first_provider { " (by initial liquidity provider)" } else { "" }
+            ),
+            details: serde_json::json!({
+                "share_pct": share_pct,
+                "removed_usd": safe_get_optional_decimal(&row, "removed_usd"),
+                "pool_liquidity_usd": pool_usd,
+                "by_first_provider": by_first_provider,
+            }),
+            dedup_key: format!("{}:{}:{}", ALERT_LARGE_BURN, burn.transaction_hash, burn.log_index),
+        };
+
+        Self::insert_alert(pool, &alert).await
+    }
+
+    /// 窗口内计价代币储备的降幅。窗口开始时的储备 = 当前储备 - 窗口内 mint / burn / swap 带来的净流入，
+    /// 撤池和砸盘（卖出代币换走计价代币）都会体现为计价代币储备下降
+    pub async fn detect_reserve_drops(
+        pool: &PgPool,
+        chain_id: i32,
+        config: &AlertConfig,
+    ) -> Result<Vec<NewAlert>> {
+        let rows = sqlx::query(
+            r#"
+            WITH flows AS (
+                SELECT pair_address, SUM(d0) AS d0, SUM(d1) AS d1
+                FROM (
+                    SELECT pair_address, amount0 AS d0, amount1 AS d1
+                    FROM mint_events
+                    WHERE chain_id = $1 AND timestamp >= NOW() - make_interval(mins => $2)
+                    UNION ALL
+                    SELECT pair_address, -amount0, -amount1
+                    FROM burn_events
+                    WHERE chain_id = $1 AND timestamp >= NOW() - make_interval(mins => $2)
+                    UNION ALL
+                    SELECT pair_address, amount0_in - amount0_out, amount1_in - amount1_out
+                    FROM swap_events
+                    WHERE chain_id = $1 AND timestamp >= NOW() - make_interval(mins => $2)
+                ) f
+                GROUP BY pair_address
+            ),
+            states AS (
+                SELECT
+                    f.pair_address,
+                    CASE WHEN tp.quote_side = 0 THEN pr.reserve0 ELSE pr.reserve1 END AS quote_now,
+                    CASE WHEN tp.quote_side = 0 THEN pr.reserve0 - f.d0 ELSE pr.reserve1 - f.d1 END AS quote_then,
+                    pair_liquidity_usd($1, f.pair_address, pr.reserve0 - f.d0, pr.reserve1 - f.d1) AS liquidity_then_usd,
+                    pr.reserve_usd AS liquidity_now_usd
+                FROM flows f
+                JOIN pair_reserves pr ON pr.chain_id = $1 AND pr.pair_address = f.pair_address
+                JOIN trading_pairs tp ON tp.chain_id = $1 AND tp.address = f.pair_address
+            )
+            SELECT
+                pair_address,
+                trim_scale(round((1 - quote_now / quote_then) * 100, 4)) AS drop_pct,
+                liquidity_then_usd,
+                liquidity_now_usd
+            FROM states
+            WHERE quote_then > 0
+            AND (1 - quote_now / quote_then) * 100 >= $3
+            AND liquidity_then_usd >= $4
+            "#,
+        )
+        .bind(chain_id)
+        .bind(config.reserve_window_mins as i32)
+        .bind(Decimal::from(config.reserve_drop_pct))
+        .bind(Decimal::from(config.min_liquidity_usd))
+        .fetch_all(pool)
+        .await?;
+
+        // 每个窗口内同一交易对只告警一次
+        let bucket = chrono::Utc::now().timestamp() / (config.reserve_window_mins.max(1) as i64 * 60);
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let pair_address = safe_get_string(row, "pair_address");
+                let drop_pct = safe_get_decimal(row, "drop_pct");
+                NewAlert {
+                    chain_id,
+                    dedup_key: format!("{}:{}:{}", ALERT_RESERVE_DROP, pair_address, bucket),
+                    pair_address,
+                    alert_type: ALERT_RESERVE_DROP,
+                    severity: if drop_pct >= Decimal::from(CRITICAL_BURN_PCT) { "critical" } else { "warning" },
+                    wallet: None,
+                    transaction_hash: None,
+                    block_number: None,
+                    message: format!(
+                        "Quote reserve dropped {}% in the last {} minutes",
+                        drop_pct.round_dp(2),
+                        config.reserve_window_mins
+                    ),
+                    details: serde_json::json!({
+                        "drop_pct": drop_pct,
+                        "window_mins": config.reserve_window_mins,
+                        "liquidity_then_usd": safe_get_optional_decimal(row, "liquidity_then_usd"),
+                        "liquidity_now_usd": safe_get_optional_decimal(row, "liquidity_now_usd"),
+                    }),
+                }
+            })
+            .collect())
+    }
+
+    /// 近期活跃的交易对中，首个流动性提供者（通常为部署者）仍持有的 LP 份额。
+    /// 流动性以 sqrt(amount0 × amount1) 计量；不跟踪 LP 代币转账，锁仓的 LP 仍计在提供者名下
+    pub async fn detect_lp_concentration(
+        pool: &PgPool,
+        chain_id: i32,
+        config: &AlertConfig,
+    ) -> Result<Vec<NewAlert>> {
+        let rows = sqlx::query(
+            r#"
+            WITH active AS (
+                SELECT DISTINCT pair_address FROM swap_events
+                WHERE chain_id = $1 AND timestamp >= NOW() - make_interval(mins => $2)
+                UNION
+                SELECT DISTINCT pair_address FROM mint_events
+                WHERE chain_id = $1 AND timestamp >= NOW() - make_interval(mins => $2)
+            ),
+            providers AS (
+                SELECT DISTINCT ON (m.pair_address) m.pair_address, m.sender
+                FROM mint_events m
+                JOIN active a ON a.pair_address = m.pair_address
+                WHERE m.chain_id = $1
+                ORDER BY m.pair_address, m.block_number, m.log_index
+            ),
+            held AS (
+                SELECT
+                    p.pair_address,
+                    p.sender,
+                    COALESCE((SELECT SUM(sqrt(m.amount0 * m.amount1)) FROM mint_events m
+                              WHERE m.chain_id = $1 AND m.pair_address = p.pair_address AND m.sender = p.sender), 0)
+                    - COALESCE((SELECT SUM(sqrt(b.amount0 * b.amount1)) FROM burn_events b
+                                WHERE b.chain_id = $1 AND b.pair_address = p.pair_address AND b.sender = p.sender), 0)
+                        AS liquidity
+                FROM providers p
+            )
+            SELECT
+                h.pair_address,
+                h.sender AS provider,
+                trim_scale(round(LEAST(h.liquidity / sqrt(pr.reserve0 * pr.reserve1), 1) * 100, 4)) AS share_pct,
+                pr.reserve_usd
+            FROM held h
+            JOIN pair_reserves pr ON pr.chain_id = $1 AND pr.pair_address = h.pair_address
+            WHERE pr.reserve0 > 0 AND pr.reserve1 > 0
+            AND h.liquidity / sqrt(pr.reserve0 * pr.reserve1) * 100 >= $3
+            AND pr.reserve_usd >= $4
+            "#,
+        )
+        .bind(chain_id)
+        .bind(config.reserve_window_mins as i32)
+        .bind(Decimal::from(config.lp_concentration_pct))
+        .bind(Decimal::from(config.min_liquidity_usd))
+        .fetch_all(pool)
+        .await?;
+
+        // 同一交易对每个 UTC 日只告警一次
+        let day = chrono::Utc::now().date_naive();
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let pair_address = safe_get_string(row, "pair_address");
+                let share_pct = safe_get_decimal(row, "share_pct");
+                NewAlert {
+                    chain_id,
+                    dedup_key: format!("{}:{}:{}", ALERT_LP_CONCENTRATION, pair_address, day),
+                    pair_address,
+                    alert_type: ALERT_LP_CONCENTRATION,
+                    severity: "warning",
+                    wallet: Some(safe_get_string(row, "provider")),
+                    transaction_hash: None,
+                    block_number: None,
+                    message: format!(
+                        "Initial liquidity provider holds {}% of pool liquidity",
+                        share_pct.round_dp(2)
+                    ),
+                    details: serde_json::json!({
+                        "share_pct": share_pct,
+                        "liquidity_usd": safe_get_optional_decimal(row, "reserve_usd"),
+                    }),
+                }
+            })
+            .collect())
+    }
+
+    /// 告警列表，按时间倒序
+    pub async fn get_alerts(
+        pool: &PgPool,
+        filter: &AlertFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Alert>> {
+        let mut conditions = vec!["1=1".to_string()];
+        let mut param_count = 0;
+
+        if filter.chain_id.is_some() {
+            param_count += 1;
+            conditions.push(format!("chain_id = ${}", param_count));
+        }
+        if filter.pair_address.is_some() {
+            param_count += 1;
+            conditions.push(format!("pair_address = ${}", param_count));
+        }
+        if filter.alert_type.is_some() {
+            param_count += 1;
+            conditions.push(format!("alert_type = ${}", param_count));
+        }
+        if filter.severity.is_some() {
+            param_count += 1;
+            conditions.push(format!("severity = ${}", param_count));
+        }
+
+        let query = format!(
+            "SELECT * FROM alerts WHERE {} ORDER BY created_at DESC LIMIT ${} OFFSET ${}",
+            conditions.join(" AND "),
+            param_count + 1,
+            param_count + 2
+        );
+
+        let mut query_builder = sqlx::query(&query);
+        if let Some(chain_id) = filter.chain_id {
+            query_builder = query_builder.bind(chain_id);
+        }
+        if let Some(pair_address) = &filter.pair_address {
+            query_builder = query_builder.bind(pair_address);
+        }
+        if let Some(alert_type) = &filter.alert_type {
+            query_builder = query_builder.bind(alert_type);
+        }
+        if let Some(severity) = &filter.severity {
+            query_builder = query_builder.bind(severity);
+        }
+
+        let rows = query_builder.bind(limit).bind(offset).fetch_all(pool).await?;
+        Ok(rows.iter().map(Self::row_to_alert).collect())
+    }
+
+    fn row_to_alert(row: &PgRow) -> Alert {
+        Alert {
+            id: safe_get_uuid(row, "id"),
+            chain_id: safe_get_i32(row, "chain_id"),
+            pair_address: safe_get_string(row, "pair_address"),
+            alert_type: safe_get_string(row, "alert_type"),
+            severity: safe_get_string(row, "severity"),
+            wallet: safe_get_optional_string(row, "wallet"),
+            transaction_hash: safe_get_optional_string(row, "transaction_hash"),
+            block_number: row.try_get("block_number").unwrap_or(None),
+            message: safe_get_string(row, "message"),
+            details: row.try_get("details").unwrap_or(serde_json::Value::Null),
+            created_at: safe_get_datetime(row, "created_at"),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::database::utils::*;
+use crate::types::{ArbitrageOpportunity, SwapQuoteHop};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+/// 待写入的套利机会
+#[derive(Debug, Clone)]
+pub struct NewArbitrageOpportunity {
+    pub chain_id: i32,
+    pub block_number: i64,
+    pub start_token: String,
+    pub pairs: Vec<String>,
+    pub amount_in: Decimal,
+    pub amount_out: Decimal,
+    pub profit: Decimal,
+    pub profit_usd: Decimal,
+    pub amount_in_usd: Decimal,
+    pub spread_pct: Decimal,
+    pub return_pct: Decimal,
+    pub route: Vec<SwapQuoteHop>,
+}
+
+/// 套利机会查询条件
+#[derive(Debug, Clone, Default)]
+pub struct ArbitrageFilter {
+    pub chain_id: Option<i32>,
+    /// 环路经过该交易对
+    pub pair_address: Option<String>,
+    pub min_profit_usd: Option<Decimal>,
+}
+
+pub struct ArbitrageOperations;
+
+impl ArbitrageOperations {
+    /// 写入套利机会，同一区块的同一环路已记录时返回 None
+    pub async fn insert_opportunity(
+        pool: &PgPool,
+        opportunity: &NewArbitrageOpportunity,
+    ) -> Result<Option<ArbitrageOpportunity>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO arbitrage_opportunities (
+                chain_id, block_number, start_token, pairs, amount_in, amount_out,
+                profit, profit_usd, amount_in_usd, spread_pct, return_pct, route
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (chain_id, block_number, pairs) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(opportunity.chain_id)
+        .bind(opportunity.block_number)
+        .bind(&opportunity.start_token)
+        .bind(&opportunity.pairs)
+        .bind(opportunity.amount_in)
+        .bind(opportunity.amount_out)
+        .bind(opportunity.profit)
+        .bind(opportunity.profit_usd)
+        .bind(opportunity.amount_in_usd)
+        .bind(opportunity.spread_pct)
+        .bind(opportunity.return_pct)
+        .bind(serde_json::to_value(&opportunity.route)?)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::row_to_opportunity))
+    }
+
+    pub async fn get_opportunities(
+        pool: &PgPool,
+        filter: &ArbitrageFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        let mut conditions = vec!["1=1".to_string()];
+        let mut param_count = 0;
+
+        if filter.chain_id.is_some() {
+            param_count += 1;
+            conditions.push(format!("chain_id = ${}", param_count));
+        }
+        if filter.pair_address.is_some() {
+            param_count += 1;
+            conditions.push(format!("${} = ANY(pairs)", param_count));
+        }
+        if filter.min_profit_usd.is_some() {
+            param_count += 1;
+            conditions.push(format!("profit_usd >= ${}", param_count));
+        }
+
+        let query = format!(
+            "SELECT * FROM arbitrage_opportunities WHERE {} ORDER BY detected_at DESC, profit_usd DESC LIMIT ${} OFFSET ${}",
+            conditions.join(" AND "),
+            param_count + 1,
+            param_count + 2
+        );
+
+        let mut query_builder = sqlx::query(&query);
+        if let Some(chain_id) = filter.chain_id {
+            query_builder = query_builder.bind(chain_id);
+        }
+        if let Some(pair_address) = &filter.pair_address {
+            query_builder = query_builder.bind(pair_address);
+        }
+        if let Some(min_profit_usd) = filter.min_profit_usd {
+            query_builder = query_builder.bind(min_profit_usd);
+        }
+
+        let rows = query_builder.bind(limit).bind(offset).fetch_all(pool).await?;
+        Ok(rows.iter().map(Self::row_to_opportunity).collect())
+    }
+
+    /// 删除超过保留天数的套利机会，返回删除的行数
+    pub async fn prune(pool: &PgPool, retention_days: u32) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM arbitrage_opportunities WHERE detected_at < NOW() - make_interval(days => $1)",
+        )
+        .bind(retention_days as i32)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_opportunity(row: &PgRow) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: safe_get_uuid(row, "id"),
+            chain_id: safe_get_i32(row, "chain_id"),
+            block_number: safe_get_i64(row, "block_number"),
+            start_token: safe_get_string(row, "start_token"),
+            pairs: row.try_get("pairs").unwrap_or_default(),
+            amount_in: safe_get_decimal(row, "amount_in"),
+            amount_out: safe_get_decimal(row, "amount_out"),
+            profit: safe_get_decimal(row, "profit"),
+            profit_usd: safe_get_decimal(row, "profit_usd"),
+            amount_in_usd: safe_get_decimal(row, "amount_in_usd"),
+            spread_pct: safe_get_decimal(row, "spread_pct"),
+            return_pct: safe_get_decimal(row, "return_pct"),
+            route: row
+                .try_get::<serde_json::Value, _>("route")
+                .ok()
+                .and_then(|route| serde_json::from_value(route).ok())
+                .unwrap_or_default(),
+            detected_at: safe_get_datetime(row, "detected_at"),
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use crate::database::utils::*;
+use crate::types::{KLineData, SwapEvent};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use tracing::info;
+
+/// 预聚合K线支持的周期，第一个为基础周期（由原始 swap 直接聚合）
+pub const CANDLE_PERIODS: [&str; 10] = ["1m", "5m", "15m", "30m", "1h", "4h", "1d", "1w", "1M", "1y"];
+
+/// 基础周期，其余周期都可以由它汇总得到
+pub const BASE_CANDLE_PERIOD: &str = "1m";
+
+pub fn is_valid_candle_period(period: &str) -> bool {
+    CANDLE_PERIODS.contains(&period)
+}
+
+/// 周期对应的分区表名，`1M` 与 `1m` 需要区分
+pub fn candle_partition_name(period: &str) -> String {
+    match period {
+        "1M" => "pair_candles_1mo".to_string(),
+        _ => format!("pair_candles_{}", period),
+    }
+}
+
+/// 成交顺序号：同一K线内按它确定开盘和收盘
+pub fn swap_sequence(block_number: i64, log_index: i32) -> i64 {
+    block_number * 100_000 + log_index as i64
+}
+
+/// K线查询条件
+#[derive(Debug, Clone)]
+pub struct CandleQuery<'a> {
+    pub period: &'a str,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub limit: i64,
+    /// 计价方：0 = token0, 1 = token1
+    pub quote_side: i32,
+}
+
+pub struct CandleOperations;
+
+impl CandleOperations {
+    /// 将一笔新写入的 swap 累加到所有周期的K线。
+    /// 价格取 price1（token1 以 token0 计价），成交量为归一化数量。
+    pub async fn apply_swap(conn: &mut PgConnection, event: &SwapEvent) -> Result<()> {
+        let price = match event.price1 {
+            Some(price) if price > Decimal::ZERO => price,
+            _ => return Ok(()),
+        };
+        let periods: Vec<String> = CANDLE_PERIODS.iter().map(|p| p.to_string()).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO pair_candles
+                (chain_id, pair_address, period, bucket_start, open, high, low, close,
+                 volume0, volume1, trade_count, first_seq, last_seq)
+            SELECT $1, $2, periods.period, candle_bucket(periods.period, $3),
+                   $5, $5, $5, $5, $6, $7, 1, $4, $4
+            FROM unnest($8::TEXT[]) AS periods(period)
+            ON CONFLICT (chain_id, pair_address, period, bucket_start) DO UPDATE SET
+                open = CASE WHEN EXCLUDED.first_seq < pair_candles.first_seq
+                            THEN EXCLUDED.open ELSE pair_candles.open END,
+                high = GREATEST(pair_candles.high, EXCLUDED.high),
+                low = LEAST(pair_candles.low, EXCLUDED.low),
+                close = CASE WHEN EXCLUDED.last_seq > pair_candles.last_seq
+                             THEN EXCLUDED.close ELSE pair_candles.close END,
+                volume0 = pair_candles.volume0 + EXCLUDED.volume0,
+                volume1 = pair_candles.volume1 + EXCLUDED.volume1,
+                trade_count = pair_candles.trade_count + EXCLUDED.trade_count,
+                first_seq = LEAST(pair_candles.first_seq, EXCLUDED.first_seq),
+                last_seq = GREATEST(pair_candles.last_seq, EXCLUDED.last_seq),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(event.chain_id)
+        .bind(&event.pair_address)
+        .bind(event.timestamp)
+        .bind(swap_sequence(event.block_number, event.log_index))
+        .bind(price)
+        .bind(event.amount0.unwrap_or_default())
+        .bind(event.amount1.unwrap_or_default())
+        .bind(&periods)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 由原始 swap 重建交易对的基础周期K线（覆盖已有数据）
+    pub async fn rebuild_base_candles(pool: &PgPool, chain_id: i32, pair_address: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO pair_candles
+                (chain_id, pair_address, period, bucket_start, open, high, low, close,
+                 volume0, volume1, trade_count, first_seq, last_seq)
+            SELECT chain_id, pair_address, $3, candle_bucket($3, timestamp),
+                   (array_agg(price ORDER BY seq))[1],
+                   MAX(price),
+                   MIN(price),
+                   (array_agg(price ORDER BY seq DESC))[1],
+                   SUM(volume0), SUM(volume1), COUNT(*), MIN(seq), MAX(seq)
+            FROM (
+                SELECT chain_id, pair_address, timestamp,
+                       block_number * 100000 + log_index AS seq,
+                       price1 AS price,
+                       COALESCE(amount0, 0) AS volume0,
+                       COALESCE(amount1, 0) AS volume1
+                FROM swap_events
+                WHERE chain_id = $1 AND pair_address = $2
+            ) s
+            WHERE price > 0
+            GROUP BY chain_id, pair_address, candle_bucket($3, timestamp)
+            ON CONFLICT (chain_id, pair_address, period, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume0 = EXCLUDED.volume0,
+                volume1 = EXCLUDED.volume1,
+                trade_count = EXCLUDED.trade_count,
+                first_seq = EXCLUDED.first_seq,
+                last_seq = EXCLUDED.last_seq,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(BASE_CANDLE_PERIOD)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 由基础周期K线汇总出 `period` 周期的K线（覆盖已有数据）。
+    /// 基础K线不受事件保留策略影响，因此汇总结果覆盖完整历史。
+    pub async fn rollup_period(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        period: &str,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO pair_candles
+                (chain_id, pair_address, period, bucket_start, open, high, low, close,
+                 volume0, volume1, trade_count, first_seq, last_seq)
+            SELECT chain_id, pair_address, $3, candle_bucket($3, bucket_start),
+                   (array_agg(open ORDER BY bucket_start, first_seq))[1],
+                   MAX(high),
+                   MIN(low),
+                   (array_agg(close ORDER BY bucket_start DESC, last_seq DESC))[1],
+                   SUM(volume0), SUM(volume1), SUM(trade_count), MIN(first_seq), MAX(last_seq)
+            FROM pair_candles
+            WHERE chain_id = $1 AND pair_address = $2 AND period = $4
+            GROUP BY chain_id, pair_address, candle_bucket($3, bucket_start)
+            ON CONFLICT (chain_id, pair_address, period, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume0 = EXCLUDED.volume0,
+                volume1 = EXCLUDED.volume1,
+                trade_count = EXCLUDED.trade_count,
+                first_seq = EXCLUDED.first_seq,
+                last_seq = EXCLUDED.last_seq,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(period)
+        .bind(BASE_CANDLE_PERIOD)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 重建交易对所有周期的K线
+    pub async fn rebuild_pair(pool: &PgPool, chain_id: i32, pair_address: &str) -> Result<()> {
+        Self::rebuild_base_candles(pool, chain_id, pair_address).await?;
+
+        for period in CANDLE_PERIODS.iter().filter(|p| **p != BASE_CANDLE_PERIOD) {
+            Self::rollup_period(pool, chain_id, pair_address, period).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 为已有 swap 但还没有K线的交易对回填K线（如升级前的历史数据），返回处理的交易对数量
+    pub async fn backfill_missing(pool: &PgPool, batch_size: i64) -> Result<usize> {
+        let rows = sqlx::query(
+            r#"
+            SELECT tp.chain_id, tp.address
+            FROM trading_pairs tp
+            WHERE EXISTS (
+                SELECT 1 FROM swap_events se
+                WHERE se.chain_id = tp.chain_id AND se.pair_address = tp.address
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM pair_candles pc
+                WHERE pc.chain_id = tp.chain_id AND pc.pair_address = tp.address AND pc.period = $1
+            )
+            LIMIT $2
+            "#,
+        )
+        .bind(BASE_CANDLE_PERIOD)
+        .bind(batch_size)
+        .fetch_all(pool)
+        .await?;
+
+        for row in &rows {
+            let chain_id = safe_get_i32(row, "chain_id");
+            let address = safe_get_string(row, "address");
+            Self::rebuild_pair(pool, chain_id, &address).await?;
+            info!("🕯️ 链 {} 交易对 {} K线回填完成", chain_id, address);
+        }
+
+        Ok(rows.len())
+    }
+
+    /// 查询K线，按时间倒序返回 [start, end] 范围内最近的 `limit` 根。
+    /// 开盘价取上一根K线的收盘价以保证连续性，最高/最低价随之修正。
+    /// K线按 token0 计价存储，`quote_side` 为 token1 时取倒数并使用 token1 成交量。
+    pub async fn get_candles(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        query: &CandleQuery<'_>,
+    ) -> Result<Vec<KLineData>> {
+        let rows = sqlx::query(
+            r#"
+            WITH candles AS (
+                SELECT c.bucket_start, c.high, c.low, c.close, c.volume0, c.volume1, c.trade_count,
+                       COALESCE((
+                           SELECT prev.close
+                           FROM pair_candles prev
+                           WHERE prev.chain_id = c.chain_id
+                             AND prev.pair_address = c.pair_address
+                             AND prev.period = c.period
+                             AND prev.bucket_start < c.bucket_start
+                           ORDER BY prev.bucket_start DESC
+                           LIMIT 1
+                       ), c.open) AS open
+                FROM pair_candles c
+                WHERE c.chain_id = $1 AND c.pair_address = $2 AND c.period = $3
+                  AND ($4::TIMESTAMPTZ IS NULL OR c.bucket_start >= candle_bucket($3, $4))
+                  AND ($5::TIMESTAMPTZ IS NULL OR c.bucket_start <= $5)
+                ORDER BY c.bucket_start DESC
+                LIMIT $6
+            )
+            SELECT bucket_start AS timestamp,
+                   CASE WHEN $7 = 0 THEN open ELSE trim_scale(round(1 / open, 28)) END AS open,
+                   CASE WHEN $7 = 0 THEN GREATEST(high, open) ELSE trim_scale(round(1 / LEAST(low, open), 28)) END AS high,
+                   CASE WHEN $7 = 0 THEN LEAST(low, open) ELSE trim_scale(round(1 / GREATEST(high, open), 28)) END AS low,
+                   CASE WHEN $7 = 0 THEN close ELSE trim_scale(round(1 / close, 28)) END AS close,
+                   CASE WHEN $7 = 0 THEN volume0 ELSE volume1 END AS volume,
+                   trade_count
+            FROM candles
+            ORDER BY bucket_start DESC
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(query.period)
+        .bind(query.start)
+        .bind(query.end)
+        .bind(query.limit)
+        .bind(query.quote_side)
+        .fetch_all(pool)
+        .await?;
+
+        let mut klines = Vec::new();
+        for row in rows {
+            klines.push(KLineData {
+                timestamp: safe_get_datetime(&row, "timestamp"),
+                open: safe_get_decimal(&row, "open"),
+                high: safe_get_decimal(&row, "high"),
+                low: safe_get_decimal(&row, "low"),
+                close: safe_get_decimal(&row, "close"),
+                volume: safe_get_decimal(&row, "volume"),
+                trade_count: safe_get_i64(&row, "trade_count"),
+            });
+        }
+
+        Ok(klines)
+    }
+}
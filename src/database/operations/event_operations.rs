@@ -1,6 +1,7 @@
+use crate::database::utils::*;
 use crate::types::{BurnEvent, LastProcessedBlock, MintEvent, SwapEvent};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
 pub struct EventOperations;
 
@@ -27,15 +28,54 @@ impl EventOperations {
         Ok(())
     }
 
-    pub async fn insert_swap_event(pool: &PgPool, event: &SwapEvent) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    /// 写入 swap 事件，返回是否为新记录（重复事件返回 false）。
+    /// 归一化数量、双向成交价、买卖方向和美元成交量在写入时计算，并回填到 `event`。
+    pub async fn insert_swap_event(conn: &mut PgConnection, event: &mut SwapEvent) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query(
             r#"
+        WITH pair AS (
+            SELECT
+                power(10::numeric, COALESCE((SELECT token0_decimals FROM trading_pairs WHERE chain_id = $1 AND address = $2), 18)) AS scale0,
+                power(10::numeric, COALESCE((SELECT token1_decimals FROM trading_pairs WHERE chain_id = $1 AND address = $2), 18)) AS scale1,
+                pair_fee_rate($1) AS fee_rate
+        ),
+        normalized AS (
+            SELECT pair.*,
+                swap_volume_usd($1, $2, ($4 + $6) / scale0, ($5 + $7) / scale1) AS volume_usd
+            FROM pair
+        )
         INSERT INTO swap_events 
         (chain_id, pair_address, sender, amount0_in, amount1_in, 
          amount0_out, amount1_out, to_address, block_number, 
-         transaction_hash, log_index, timestamp)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-        ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING
+         transaction_hash, log_index, timestamp,
+         amount0, amount1, price0, price1, side, volume_usd, fee_amount, fee_usd, event_sender)
+        SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+            -- 限制在 28 位小数以内，保证能解码为 rust_decimal::Decimal
+            trim_scale(round(($4 + $6) / scale0, 28)),
+            trim_scale(round(($5 + $7) / scale1, 28)),
+            trim_scale(round(CASE
+                WHEN $4 > 0 AND $7 > 0 THEN ($7 * scale0) / ($4 * scale1)
+                WHEN $5 > 0 AND $6 > 0 THEN ($5 * scale0) / ($6 * scale1)
+            END, 28)),
+            trim_scale(round(CASE
+                WHEN $4 > 0 AND $7 > 0 THEN ($4 * scale1) / ($7 * scale0)
+                WHEN $5 > 0 AND $6 > 0 THEN ($6 * scale1) / ($5 * scale0)
+            END, 28)),
+            CASE
+                WHEN $4 > 0 AND $7 > 0 THEN 'buy'
+                WHEN $5 > 0 AND $6 > 0 THEN 'sell'
+            END,
+            volume_usd,
+            -- LP 手续费按输入数量收取
+            trim_scale(round(CASE
+                WHEN $4 > 0 AND $7 > 0 THEN $4 / scale0
+                WHEN $5 > 0 AND $6 > 0 THEN $5 / scale1
+            END * fee_rate, 28)),
+            trim_scale(round(volume_usd * fee_rate, 28)),
+            $13
+        FROM normalized
+        ON CONFLICT (chain_id, transaction_hash, log_index, timestamp) DO NOTHING
+        RETURNING amount0, amount1, price0, price1, side, volume_usd, fee_amount, fee_usd
         "#,
         )
         .bind(event.chain_id)
@@ -50,19 +90,35 @@ impl EventOperations {
         .bind(&event.transaction_hash)
         .bind(event.log_index)
         .bind(event.timestamp)
-        .execute(pool)
+        .bind(&event.event_sender)
+        .fetch_optional(&mut *conn)
         .await?;
 
-        Ok(())
+        match row {
+            Some(row) => {
+                event.amount0 = safe_get_optional_decimal(&row, "amount0");
+                event.amount1 = safe_get_optional_decimal(&row, "amount1");
+                event.price0 = safe_get_optional_decimal(&row, "price0");
+                event.price1 = safe_get_optional_decimal(&row, "price1");
+                event.side = safe_get_optional_string(&row, "side");
+                event.volume_usd = safe_get_optional_decimal(&row, "volume_usd");
+                event.fee_amount = safe_get_optional_decimal(&row, "fee_amount");
+                event.fee_usd = safe_get_optional_decimal(&row, "fee_usd");
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
-    pub async fn insert_burn_event(pool: &PgPool, event: &BurnEvent) -> Result<()> {
-        sqlx::query(
+    /// 写入 burn 事件，返回是否为新记录（重复事件返回 false）。美元价值在写入时计算，并回填到 `event`
+    pub async fn insert_burn_event(conn: &mut PgConnection, event: &mut BurnEvent) -> Result<bool> {
+        let row = sqlx::query(
         r#"
         INSERT INTO burn_events 
-        (chain_id, pair_address, sender, amount0, amount1, to_address, block_number, transaction_hash, log_index, timestamp)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-        ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING
+        (chain_id, pair_address, sender, amount0, amount1, to_address, block_number, transaction_hash, log_index, timestamp, value_usd, event_sender)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, pair_liquidity_usd($1, $2, $4, $5), $11)
+        ON CONFLICT (chain_id, transaction_hash, log_index, timestamp) DO NOTHING
+        RETURNING value_usd
         "#,
         )
         .bind(event.chain_id)
@@ -75,19 +131,28 @@ impl EventOperations {
         .bind(&event.transaction_hash)
         .bind(event.log_index)
         .bind(event.timestamp)
-        .execute(pool)
+        .bind(&event.event_sender)
+        .fetch_optional(&mut *conn)
         .await?;
 
-        Ok(())
+        match row {
+            Some(row) => {
+                event.value_usd = safe_get_optional_decimal(&row, "value_usd");
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
-    pub async fn insert_mint_event(pool: &PgPool, event: &MintEvent) -> Result<()> {
-        sqlx::query(
+    /// 写入 mint 事件，返回是否为新记录（重复事件返回 false）。美元价值在写入时计算，并回填到 `event`
+    pub async fn insert_mint_event(conn: &mut PgConnection, event: &mut MintEvent) -> Result<bool> {
+        let row = sqlx::query(
         r#"
         INSERT INTO mint_events 
-        (chain_id, pair_address, sender, amount0, amount1, block_number, transaction_hash, log_index, timestamp)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING
+        (chain_id, pair_address, sender, amount0, amount1, block_number, transaction_hash, log_index, timestamp, value_usd, event_sender)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, pair_liquidity_usd($1, $2, $4, $5), $10)
+        ON CONFLICT (chain_id, transaction_hash, log_index, timestamp) DO NOTHING
+        RETURNING value_usd
         "#,
         )
         .bind(event.chain_id)
@@ -99,10 +164,17 @@ impl EventOperations {
         .bind(&event.transaction_hash)
         .bind(event.log_index)
         .bind(event.timestamp)
-        .execute(pool)
+        .bind(&event.event_sender)
+        .fetch_optional(&mut *conn)
         .await?;
 
-        Ok(())
+        match row {
+            Some(row) => {
+                event.value_usd = safe_get_optional_decimal(&row, "value_usd");
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
     pub async fn get_last_processed_block(
         pool: &PgPool,
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::info;
+
+/// 未配置工厂费率时的默认值（基点），与 pair_fee_rate 保持一致
+pub const DEFAULT_SWAP_FEE_BPS: i32 = 30;
+
+pub struct FeeOperations;
+
+impl FeeOperations {
+    /// 用配置覆盖链的工厂手续费率；费率变化时重算该链所有 swap 的手续费及排行榜聚合中的手续费
+    pub async fn sync_factory_fee(
+        pool: &PgPool,
+        chain_id: i32,
+        factory_address: &str,
+        fee_bps: i32,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let previous: i32 = sqlx::query("SELECT fee_bps FROM factory_fees WHERE chain_id = $1")
+            .bind(chain_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .map(|row| row.get("fee_bps"))
+            .unwrap_or(DEFAULT_SWAP_FEE_BPS);
+
+        sqlx::query(
+            r#"
+            INSERT INTO factory_fees (chain_id, factory_address, fee_bps)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (chain_id) DO UPDATE SET
+                factory_address = EXCLUDED.factory_address,
+                fee_bps = EXCLUDED.fee_bps,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(factory_address.to_lowercase())
+        .bind(fee_bps)
+        .execute(&mut *tx)
+        .await?;
+
+        if previous != fee_bps {
+            let result = sqlx::query(
+                r#"
+                UPDATE swap_events
+                SET fee_amount = trim_scale(round(CASE WHEN side = 'buy' THEN amount0 ELSE amount1 END * pair_fee_rate(chain_id), 28)),
+                    fee_usd = trim_scale(round(volume_usd * pair_fee_rate(chain_id), 28))
+                WHERE chain_id = $1 AND side IS NOT NULL
+                "#,
+            )
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+            // 排行榜聚合中的手续费随之重算
+            sqlx::query(
+                r#"
+                UPDATE wallet_pair_daily_stats d
+                SET fees_paid_usd = COALESCE(f.fees_paid_usd, 0), updated_at = NOW()
+                FROM (
+                    SELECT d2.pair_address, d2.wallet_address, d2.day, SUM(se.fee_usd) as fees_paid_usd
+                    FROM wallet_pair_daily_stats d2
+                    LEFT JOIN swap_events se
+                        ON se.chain_id = d2.chain_id AND se.pair_address = d2.pair_address
+                        AND se.sender = d2.wallet_address
+                        AND se.timestamp >= d2.day::timestamp AT TIME ZONE 'UTC'
+                        AND se.timestamp < (d2.day + 1)::timestamp AT TIME ZONE 'UTC'
+                    WHERE d2.chain_id = $1
+                    GROUP BY d2.pair_address, d2.wallet_address, d2.day
+                ) f
+                WHERE d.chain_id = $1 AND d.pair_address = f.pair_address
+                  AND d.wallet_address = f.wallet_address AND d.day = f.day
+                "#,
+            )
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+            info!(
+                "💸 链 {} 手续费率 {} -> {} 基点，重算 {} 条 swap 的手续费",
+                chain_id,
+                previous,
+                fee_bps,
+                result.rows_affected()
+            );
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
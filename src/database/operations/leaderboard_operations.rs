@@ -0,0 +1,277 @@
+use crate::database::utils::*;
+use crate::types::{BurnEvent, LeaderboardEntry, MintEvent, SwapEvent};
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+
+/// 排行榜范围
+#[derive(Debug, Clone)]
+pub enum LeaderboardScope {
+    /// 整条链
+    Chain,
+    /// 单个交易对（小写地址）
+    Pair(String),
+    /// 代币参与的所有交易对（小写地址）
+    Token(String),
+}
+
+/// 排行榜排序指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardSort {
+    Volume,
+    Trades,
+    Pnl,
+    Liquidity,
+}
+
+impl LeaderboardSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "volume" => Some(Self::Volume),
+            "trades" => Some(Self::Trades),
+            "pnl" => Some(Self::Pnl),
+            "liquidity" => Some(Self::Liquidity),
+            _ => None,
+        }
+    }
+
+    fn order_column(&self) -> &'static str {
+        match self {
+            Self::Volume => "volume_usd",
+            Self::Trades => "trade_count",
+            Self::Pnl => "pnl_usd",
+            Self::Liquidity => "net_liquidity_usd",
+        }
+    }
+}
+
+/// 排行榜窗口（按 UTC 自然日对齐）：`1d` 为当天，`7d` / `30d` 含当天在内的最近 N 天，`all` 为全部历史。
+/// 返回天数，`all` 为 None
+pub fn parse_leaderboard_window(window: &str) -> Option<Option<i32>> {
+    match window {
+        "1d" => Some(Some(1)),
+        "7d" => Some(Some(7)),
+        "30d" => Some(Some(30)),
+        "all" => Some(None),
+        _ => None,
+    }
+}
+
+/// 钱包 × 交易对 × 日的增量聚合（wallet_pair_daily_stats）。
+/// 事件写入时累加，估值服务补算美元值时把补上的部分再累加，排行榜查询只读聚合表
+pub struct LeaderboardOperations;
+
+impl LeaderboardOperations {
+    /// 累加一笔新写入的 swap；钱包为交易发起人，amount*_net 为钱包视角的代币净流入
+    pub async fn apply_swap(conn: &mut PgConnection, event: &SwapEvent) -> Result<()> {
+        let (side, amount0, amount1) = match (&event.side, event.amount0, event.amount1) {
+            (Some(side), Some(amount0), Some(amount1)) => (side, amount0, amount1),
+            _ => return Ok(()),
+        };
+        // buy = token0 换 token1
+        let (net0, net1) = if side == "buy" {
+            (-amount0, amount1)
+        } else {
+            (amount0, -amount1)
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_pair_daily_stats
+                (chain_id, pair_address, wallet_address, day, trade_count, volume_usd, fees_paid_usd, amount0_net, amount1_net)
+            VALUES ($1, $2, $3, ($4 AT TIME ZONE 'UTC')::date, 1, COALESCE($5, 0), COALESCE($6, 0), $7, $8)
+            ON CONFLICT (chain_id, pair_address, wallet_address, day) DO UPDATE SET
+                trade_count = wallet_pair_daily_stats.trade_count + 1,
+                volume_usd = wallet_pair_daily_stats.volume_usd + EXCLUDED.volume_usd,
+                fees_paid_usd = wallet_pair_daily_stats.fees_paid_usd + EXCLUDED.fees_paid_usd,
+                amount0_net = wallet_pair_daily_stats.amount0_net + EXCLUDED.amount0_net,
+                amount1_net = wallet_pair_daily_stats.amount1_net + EXCLUDED.amount1_net,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(event.chain_id)
+        .bind(&event.pair_address)
+        .bind(&event.sender)
+        .bind(event.timestamp)
+        .bind(event.volume_usd)
+        .bind(event.fee_usd)
+        .bind(net0)
+        .bind(net1)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 累加一笔新写入的 mint（按交易发起人归属），美元价值取写入时计算的值，未估值的部分由估值服务补算后累加
+    pub async fn apply_mint(conn: &mut PgConnection, event: &MintEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_pair_daily_stats
+                (chain_id, pair_address, wallet_address, day, mint_count, liquidity_added_usd)
+            VALUES ($1, $2, $3, ($4 AT TIME ZONE 'UTC')::date, 1, COALESCE($5, 0))
+            ON CONFLICT (chain_id, pair_address, wallet_address, day) DO UPDATE SET
+                mint_count = wallet_pair_daily_stats.mint_count + 1,
+                liquidity_added_usd = wallet_pair_daily_stats.liquidity_added_usd + EXCLUDED.liquidity_added_usd,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(event.chain_id)
+        .bind(&event.pair_address)
+        .bind(&event.sender)
+        .bind(event.timestamp)
+        .bind(event.value_usd)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 累加一笔新写入的 burn（按交易发起人归属），美元价值取写入时计算的值，未估值的部分由估值服务补算后累加
+    pub async fn apply_burn(conn: &mut PgConnection, event: &BurnEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO wallet_pair_daily_stats
+                (chain_id, pair_address, wallet_address, day, burn_count, liquidity_removed_usd)
+            VALUES ($1, $2, $3, ($4 AT TIME ZONE 'UTC')::date, 1, COALESCE($5, 0))
+            ON CONFLICT (chain_id, pair_address, wallet_address, day) DO UPDATE SET
+                burn_count = wallet_pair_daily_stats.burn_count + 1,
+                liquidity_removed_usd = wallet_pair_daily_stats.liquidity_removed_usd + EXCLUDED.liquidity_removed_usd,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(event.chain_id)
+        .bind(&event.pair_address)
+        .bind(&event.sender)
+        .bind(event.timestamp)
+        .bind(event.value_usd)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 按指标排序的钱包排行。PnL 为窗口内代币净流入按当前美元价格估值
+    /// （成交时两侧等值，因此等于相对“不交易”的盈亏，未扣手续费）
+    pub async fn get_leaderboard(
+        pool: &PgPool,
+        chain_id: i32,
+        scope: &LeaderboardScope,
+        sort: LeaderboardSort,
+        days: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        let mut conditions = vec!["d.chain_id = $1".to_string()];
+        let mut param_count = 1;
+
+        if days.is_some() {
+            param_count += 1;
+            conditions.push(format!(
+                "d.day > (NOW() AT TIME ZONE 'UTC')::date - ${}",
+                param_count
+            ));
+        }
+        let scope_address = match scope {
+            LeaderboardScope::Chain => None,
+            LeaderboardScope::Pair(address) => {
+                param_count += 1;
+                conditions.push(format!("d.pair_address = ${}", param_count));
+                Some(address)
+            }
+            LeaderboardScope::Token(address) => {
+                param_count += 1;
+                conditions.push(format!(
+                    "(lower(tp.token0) = ${0} OR lower(tp.token1) = ${0})",
+                    param_count
+                ));
+                Some(address)
+            }
+        };
+        // 交易类排行只看有成交的钱包，流动性排行只看做过 mint 的钱包
+        let having = match sort {
+            LeaderboardSort::Liquidity => "SUM(mint_count) > 0",
+            _ => "SUM(trade_count) > 0",
+        };
+
+        let query = format!(
+            r#"
+            WITH per_pair AS (
+                SELECT
+                    d.wallet_address,
+                    d.pair_address,
+                    lower(tp.token0) as token0,
+                    lower(tp.token1) as token1,
+                    SUM(d.trade_count) as trade_count,
+                    SUM(d.volume_usd) as volume_usd,
+                    SUM(d.fees_paid_usd) as fees_paid_usd,
+                    SUM(d.amount0_net) as amount0_net,
+                    SUM(d.amount1_net) as amount1_net,
+                    SUM(d.mint_count) as mint_count,
+                    SUM(d.burn_count) as burn_count,
+                    SUM(d.liquidity_added_usd) as liquidity_added_usd,
+                    SUM(d.liquidity_removed_usd) as liquidity_removed_usd,
+                    MAX(d.day) as last_active_day
+                FROM wallet_pair_daily_stats d
+                JOIN trading_pairs tp ON tp.chain_id = d.chain_id AND tp.address = d.pair_address
+                WHERE {}
+                GROUP BY d.wallet_address, d.pair_address, tp.token0, tp.token1
+            )
+            SELECT
+                p.wallet_address,
+                COUNT(*) FILTER (WHERE p.trade_count > 0 OR p.mint_count > 0) as pair_count,
+                SUM(p.trade_count)::BIGINT as trade_count,
+                SUM(p.volume_usd) as volume_usd,
+                SUM(p.fees_paid_usd) as fees_paid_usd,
+                trim_scale(round(SUM(
+                    p.amount0_net * COALESCE(u0.price_usd, 0) + p.amount1_net * COALESCE(u1.price_usd, 0)
+                ), 18)) as pnl_usd,
+                SUM(p.mint_count)::BIGINT as mint_count,
+                SUM(p.burn_count)::BIGINT as burn_count,
+                SUM(p.liquidity_added_usd) as liquidity_added_usd,
+                SUM(p.liquidity_removed_usd) as liquidity_removed_usd,
+                SUM(p.liquidity_added_usd - p.liquidity_removed_usd) as net_liquidity_usd,
+                MAX(p.last_active_day)::timestamp AT TIME ZONE 'UTC' as last_active_day
+            FROM per_pair p
+            LEFT JOIN token_usd_prices u0 ON u0.chain_id = $1 AND u0.token_address = p.token0
+            LEFT JOIN token_usd_prices u1 ON u1.chain_id = $1 AND u1.token_address = p.token1
+            GROUP BY p.wallet_address
+            HAVING {}
+            ORDER BY {} DESC NULLS LAST, p.wallet_address
+            LIMIT ${}
+            "#,
+            conditions.join(" AND "),
+            having,
+            sort.order_column(),
+            param_count + 1
+        );
+
+        let mut query_builder = sqlx::query(&query).bind(chain_id);
+        if let Some(days) = days {
+            query_builder = query_builder.bind(days);
+        }
+        if let Some(address) = scope_address {
+            query_builder = query_builder.bind(address);
+        }
+
+        let rows = query_builder.bind(limit).fetch_all(pool).await?;
+
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| LeaderboardEntry {
+                rank: i as i64 + 1,
+                wallet_address: safe_get_string(row, "wallet_address"),
+                pair_count: safe_get_i64(row, "pair_count"),
+                trade_count: safe_get_i64(row, "trade_count"),
+                volume_usd: safe_get_decimal(row, "volume_usd"),
+                fees_paid_usd: safe_get_decimal(row, "fees_paid_usd"),
+                pnl_usd: safe_get_decimal(row, "pnl_usd"),
+                mint_count: safe_get_i64(row, "mint_count"),
+                burn_count: safe_get_i64(row, "burn_count"),
+                liquidity_added_usd: safe_get_decimal(row, "liquidity_added_usd"),
+                liquidity_removed_usd: safe_get_decimal(row, "liquidity_removed_usd"),
+                net_liquidity_usd: safe_get_decimal(row, "net_liquidity_usd"),
+                last_active_day: safe_get_optional_datetime(row, "last_active_day"),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,343 @@
+use crate::database::utils::*;
+use crate::types::{Sandwich, SandwichVictim};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Row};
+
+/// 夹子检测用的 swap，按资金流向拆成 token_in / token_out，数量已按精度归一化，地址小写
+#[derive(Debug, Clone)]
+pub struct MevSwap {
+    pub transaction_hash: String,
+    pub log_index: i32,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    pub pair_address: String,
+    pub tx_origin: String,
+    pub event_sender: Option<String>,
+    /// event_sender 是否为已知路由 / 聚合器合约
+    pub via_router: bool,
+    pub to_address: String,
+    pub side: String,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub amount_out: Decimal,
+    pub volume_usd: Option<Decimal>,
+}
+
+/// 待写入的夹子攻击
+#[derive(Debug, Clone)]
+pub struct SandwichRow {
+    pub pair_address: String,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    pub attacker: String,
+    pub frontrun: MevSwap,
+    pub backrun: MevSwap,
+    pub victims: Vec<MevSwap>,
+    pub profit_token: String,
+    pub profit_token_symbol: Option<String>,
+    pub profit_amount: Decimal,
+    pub profit_usd: Option<Decimal>,
+}
+
+/// 夹子攻击查询条件
+#[derive(Debug, Clone, Default)]
+pub struct SandwichFilter {
+    pub chain_id: Option<i32>,
+    pub pair_address: Option<String>,
+    pub attacker: Option<String>,
+    /// 受害钱包（交易发起人）
+    pub victim: Option<String>,
+}
+
+pub struct MevOperations;
+
+impl MevOperations {
+    /// 区块区间 (from_block, to_block] 内方向已知的 swap，按区块、交易对和日志顺序排列
+    pub async fn get_block_swaps(
+        pool: &PgPool,
+        chain_id: i32,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<MevSwap>> {
+        // side: buy = 用 token0 换 token1，sell = 用 token1 换 token0
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                se.transaction_hash,
+                se.log_index,
+                se.block_number,
+                se.timestamp,
+                se.pair_address,
+                se.sender as tx_origin,
+                se.event_sender,
+                kr.address IS NOT NULL as via_router,
+                se.to_address,
+                se.side,
+                CASE WHEN se.side = 'buy' THEN lower(tp.token0) ELSE lower(tp.token1) END as token_in,
+                CASE WHEN se.side = 'buy' THEN tp.token0_symbol ELSE tp.token1_symbol END as token_in_symbol,
+                CASE WHEN se.side = 'buy' THEN se.amount0 ELSE se.amount1 END as amount_in,
+                CASE WHEN se.side = 'buy' THEN lower(tp.token1) ELSE lower(tp.token0) END as token_out,
+                CASE WHEN se.side = 'buy' THEN se.amount1 ELSE se.amount0 END as amount_out,
+                se.volume_usd
+            FROM swap_events se
+            JOIN trading_pairs tp ON tp.chain_id = se.chain_id AND tp.address = se.pair_address
+            LEFT JOIN known_routers kr ON kr.chain_id = se.chain_id AND kr.address = se.event_sender
+            WHERE se.chain_id = $1
+            AND se.block_number > $2
+            AND se.block_number <= $3
+            AND se.side IS NOT NULL
+            ORDER BY se.block_number, se.pair_address, se.log_index
+            "#,
+        )
+        .bind(chain_id)
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| MevSwap {
+                transaction_hash: safe_get_string(row, "transaction_hash"),
+                log_index: safe_get_i32(row, "log_index"),
+                block_number: safe_get_i64(row, "block_number"),
+                timestamp: safe_get_datetime(row, "timestamp"),
+                pair_address: safe_get_string(row, "pair_address"),
+                tx_origin: safe_get_string(row, "tx_origin"),
+                event_sender: safe_get_optional_string(row, "event_sender"),
+                via_router: safe_get_bool(row, "via_router"),
+                to_address: safe_get_string(row, "to_address"),
+                side: safe_get_string(row, "side"),
+                token_in: safe_get_string(row, "token_in"),
+                token_in_symbol: safe_get_optional_string(row, "token_in_symbol"),
+                amount_in: safe_get_decimal(row, "amount_in"),
+                token_out: safe_get_string(row, "token_out"),
+                amount_out: safe_get_decimal(row, "amount_out"),
+                volume_usd: safe_get_optional_decimal(row, "volume_usd"),
+            })
+            .collect())
+    }
+
+    /// 用检测结果替换区块区间 (from_block, to_block] 内的夹子攻击及 swap 标记，重复处理同一区间是幂等的
+    pub async fn replace_sandwiches(
+        pool: &PgPool,
+        chain_id: i32,
+        from_block: u64,
+        to_block: u64,
+        sandwiches: &[SandwichRow],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        for table in ["sandwiches", "sandwich_swaps"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE chain_id = $1 AND block_number > $2 AND block_number <= $3",
+                table
+            ))
+            .bind(chain_id)
+            .bind(from_block as i64)
+            .bind(to_block as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for sandwich in sandwiches {
+            let victim_volume_usd: Option<Decimal> =
+                sandwich.victims.iter().map(|v| v.volume_usd).sum();
+
+            sqlx::query(
+                r#"
+                INSERT INTO sandwiches (
+                    chain_id, pair_address, block_number, timestamp, attacker,
+                    frontrun_tx, frontrun_log_index, backrun_tx, backrun_log_index,
+                    victim_count, victim_volume_usd, profit_token, profit_token_symbol, profit_amount, profit_usd
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                "#,
+            )
+            .bind(chain_id)
+            .bind(&sandwich.pair_address)
+            .bind(sandwich.block_number)
+            .bind(sandwich.timestamp)
+            .bind(&sandwich.attacker)
+            .bind(&sandwich.frontrun.transaction_hash)
+            .bind(sandwich.frontrun.log_index)
+            .bind(&sandwich.backrun.transaction_hash)
+            .bind(sandwich.backrun.log_index)
+            .bind(sandwich.victims.len() as i32)
+            .bind(victim_volume_usd)
+            .bind(&sandwich.profit_token)
+            .bind(&sandwich.profit_token_symbol)
+            .bind(sandwich.profit_amount)
+            .bind(sandwich.profit_usd)
+            .execute(&mut *tx)
+            .await?;
+
+            let roles = std::iter::once(("frontrun", &sandwich.frontrun))
+                .chain(sandwich.victims.iter().map(|v| ("victim", v)))
+                .chain(std::iter::once(("backrun", &sandwich.backrun)));
+
+            for (role, swap) in roles {
+                sqlx::query(
+                    r#"
+                    INSERT INTO sandwich_swaps (
+                        chain_id, transaction_hash, log_index, block_number, role, wallet, volume_usd,
+                        frontrun_tx, frontrun_log_index
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING
+                    "#,
+                )
+                .bind(chain_id)
+                .bind(&swap.transaction_hash)
+                .bind(swap.log_index)
+                .bind(swap.block_number)
+                .bind(role)
+                .bind(&swap.tx_origin)
+                .bind(swap.volume_usd)
+                .bind(&sandwich.frontrun.transaction_hash)
+                .bind(sandwich.frontrun.log_index)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// 夹子攻击列表，按时间倒序。检测时 frontrun 尚未定价的，按当前美元价格估算获利
+    pub async fn get_sandwiches(
+        pool: &PgPool,
+        filter: &SandwichFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Sandwich>> {
+        let mut conditions = vec!["1=1".to_string()];
+        let mut param_count = 0;
+
+        if filter.chain_id.is_some() {
+            param_count += 1;
+            conditions.push(format!("s.chain_id = ${}", param_count));
+        }
+        if filter.pair_address.is_some() {
+            param_count += 1;
+            conditions.push(format!("s.pair_address = ${}", param_count));
+        }
+        if filter.attacker.is_some() {
+            param_count += 1;
+            conditions.push(format!("s.attacker = ${}", param_count));
+        }
+        if filter.victim.is_some() {
+            param_count += 1;
+            conditions.push(format!(
+                r#"EXISTS (
+                    SELECT 1 FROM sandwich_swaps vs
+                    WHERE vs.chain_id = s.chain_id
+                    AND vs.frontrun_tx = s.frontrun_tx
+                    AND vs.frontrun_log_index = s.frontrun_log_index
+                    AND vs.role = 'victim'
+                    AND vs.wallet = ${}
+                )"#,
+                param_count
+            ));
+        }
+
+        let query = format!(
+            r#"
+            SELECT
+                s.*,
+                tp.token0_symbol,
+                tp.token1_symbol,
+                COALESCE(s.profit_usd, trim_scale(round(s.profit_amount * u.price_usd, 18))) as estimated_profit_usd,
+                v.victim_txs,
+                v.victim_log_indexes,
+                v.victim_wallets,
+                v.victim_volumes
+            FROM sandwiches s
+            LEFT JOIN trading_pairs tp ON tp.chain_id = s.chain_id AND tp.address = s.pair_address
+            LEFT JOIN token_usd_prices u ON u.chain_id = s.chain_id AND u.token_address = s.profit_token
+            LEFT JOIN LATERAL (
+                SELECT
+                    array_agg(ss.transaction_hash ORDER BY ss.log_index) as victim_txs,
+                    array_agg(ss.log_index ORDER BY ss.log_index) as victim_log_indexes,
+                    array_agg(ss.wallet ORDER BY ss.log_index) as victim_wallets,
+                    array_agg(ss.volume_usd ORDER BY ss.log_index) as victim_volumes
+                FROM sandwich_swaps ss
+                WHERE ss.chain_id = s.chain_id
+                AND ss.frontrun_tx = s.frontrun_tx
+                AND ss.frontrun_log_index = s.frontrun_log_index
+                AND ss.role = 'victim'
+            ) v ON true
+            WHERE {}
+            ORDER BY s.timestamp DESC, s.frontrun_log_index DESC
+            LIMIT ${} OFFSET ${}
+            "#,
+            conditions.join(" AND "),
+            param_count + 1,
+            param_count + 2
+        );
+
+        let mut query_builder = sqlx::query(&query);
+        if let Some(chain_id) = filter.chain_id {
+            query_builder = query_builder.bind(chain_id);
+        }
+        if let Some(pair_address) = &filter.pair_address {
+            query_builder = query_builder.bind(pair_address);
+        }
+        if let Some(attacker) = &filter.attacker {
+            query_builder = query_builder.bind(attacker);
+        }
+        if let Some(victim) = &filter.victim {
+            query_builder = query_builder.bind(victim);
+        }
+
+        let rows = query_builder.bind(limit).bind(offset).fetch_all(pool).await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let txs: Vec<String> = row.try_get("victim_txs").unwrap_or_default();
+                let log_indexes: Vec<i32> = row.try_get("victim_log_indexes").unwrap_or_default();
+                let wallets: Vec<String> = row.try_get("victim_wallets").unwrap_or_default();
+                let volumes: Vec<Option<Decimal>> = row.try_get("victim_volumes").unwrap_or_default();
+                let victims = txs
+                    .into_iter()
+                    .zip(log_indexes)
+                    .zip(wallets)
+                    .zip(volumes)
+                    .map(|(((transaction_hash, log_index), wallet), volume_usd)| SandwichVictim {
+                        transaction_hash,
+                        log_index,
+                        wallet,
+                        volume_usd,
+                    })
+                    .collect();
+
+                Sandwich {
+                    chain_id: safe_get_i32(row, "chain_id"),
+                    pair_address: safe_get_string(row, "pair_address"),
+                    token0_symbol: safe_get_optional_string(row, "token0_symbol"),
+                    token1_symbol: safe_get_optional_string(row, "token1_symbol"),
+                    block_number: safe_get_i64(row, "block_number"),
+                    timestamp: safe_get_datetime(row, "timestamp"),
+                    attacker: safe_get_string(row, "attacker"),
+                    frontrun_tx: safe_get_string(row, "frontrun_tx"),
+                    frontrun_log_index: safe_get_i32(row, "frontrun_log_index"),
+                    backrun_tx: safe_get_string(row, "backrun_tx"),
+                    backrun_log_index: safe_get_i32(row, "backrun_log_index"),
+                    victims,
+                    victim_volume_usd: safe_get_optional_decimal(row, "victim_volume_usd"),
+                    profit_token: safe_get_string(row, "profit_token"),
+                    profit_token_symbol: safe_get_optional_string(row, "profit_token_symbol"),
+                    profit_amount: safe_get_decimal(row, "profit_amount"),
+                    profit_usd: safe_get_optional_decimal(row, "estimated_profit_usd"),
+                }
+            })
+            .collect())
+    }
+}
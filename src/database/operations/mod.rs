@@ -6,6 +6,26 @@ pub mod stats_operations;
 pub mod metadata_operations;
 pub mod system_operations;
 pub mod price_operations;
+pub mod partition_operations;
+pub mod candle_operations;
+pub mod quote_operations;
+pub mod valuation_operations;
+pub mod supply_operations;
+pub mod fee_operations;
+pub mod leaderboard_operations;
+pub mod router_operations;
+pub mod trade_operations;
+pub mod mev_operations;
+pub mod wash_operations;
+pub mod alert_operations;
+pub mod risk_operations;
+pub mod tax_operations;
+pub mod trending_operations;
+pub mod activity_operations;
+pub mod twap_operations;
+pub mod route_operations;
+pub mod arbitrage_operations;
+pub mod whale_operations;
 
 // Re-export all operations for backward compatibility
 pub use trading_operations::*;
@@ -16,6 +36,26 @@ pub use stats_operations::*;
 pub use metadata_operations::*;
 pub use system_operations::*;
 pub use price_operations::*;
+pub use partition_operations::*;
+pub use candle_operations::*;
+pub use quote_operations::*;
+pub use valuation_operations::*;
+pub use supply_operations::*;
+pub use fee_operations::*;
+pub use leaderboard_operations::*;
+pub use router_operations::*;
+pub use trade_operations::*;
+pub use mev_operations::*;
+pub use wash_operations::*;
+pub use alert_operations::*;
+pub use risk_operations::*;
+pub use tax_operations::*;
+pub use trending_operations::*;
+pub use activity_operations::*;
+pub use twap_operations::*;
+pub use route_operations::*;
+pub use arbitrage_operations::*;
+pub use whale_operations::*;
 
 // Event type constants
 pub const EVENT_TYPE_SWAP: &str = "swap";
@@ -23,3 +63,7 @@ pub const EVENT_TYPE_MINT: &str = "mint";
 pub const EVENT_TYPE_BURN: &str = "burn";
 pub const EVENT_TYPE_FACTORY: &str = "factory";
 pub const EVENT_TYPE_UNIFIED: &str = "unified";
+/// 多跳成交重建进度（不对应链上事件）
+pub const EVENT_TYPE_TRADES: &str = "trades";
+/// 夹子攻击检测进度（不对应链上事件）
+pub const EVENT_TYPE_MEV: &str = "mev";
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, TimeZone, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{info, warn};
+
+/// 按月分区的事件表
+pub const PARTITIONED_EVENT_TABLES: [&str; 3] = ["swap_events", "mint_events", "burn_events"];
+
+/// 事件表的一个分区
+#[derive(Debug, Clone)]
+pub struct EventPartition {
+    pub name: String,
+    /// 分区覆盖月份的起始时间，默认分区为 None
+    pub month_start: Option<DateTime<Utc>>,
+}
+
+impl EventPartition {
+    pub fn month_end(&self) -> Option<DateTime<Utc>> {
+        self.month_start.map(|start| start + Months::new(1))
+    }
+}
+
+pub struct PartitionOperations;
+
+impl PartitionOperations {
+    /// 确保 `parent` 表存在覆盖 `ts` 所在月份的分区，返回分区名
+    pub async fn ensure_partition(pool: &PgPool, parent: &str, ts: DateTime<Utc>) -> Result<String> {
+        let name = sqlx::query_scalar::<_, String>("SELECT ensure_monthly_partition($1, $2)")
+            .bind(parent)
+            .bind(ts)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(name)
+    }
+
+    /// 为所有事件表创建 `ts` 所在月份的分区
+    pub async fn ensure_event_partitions(pool: &PgPool, ts: DateTime<Utc>) -> Result<()> {
+        for table in PARTITIONED_EVENT_TABLES {
+            Self::ensure_partition(pool, table, ts).await?;
+        }
+        Ok(())
+    }
+
+    /// 预先创建当前月及之后 `months_ahead` 个月的分区
+    pub async fn premake_partitions(pool: &PgPool, months_ahead: u32) -> Result<()> {
+        let now = Utc::now();
+        for offset in 0..=months_ahead {
+            if let Some(ts) = now.checked_add_months(Months::new(offset)) {
+                Self::ensure_event_partitions(pool, ts).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 列出 `parent` 表的所有分区（按月份升序，默认分区排在最后）
+    pub async fn list_partitions(pool: &PgPool, parent: &str) -> Result<Vec<EventPartition>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT child.relname AS name
+            FROM pg_inherits i
+            JOIN pg_class parent ON parent.oid = i.inhparent
+            JOIN pg_class child ON child.oid = i.inhrelid
+            WHERE parent.relname = $1
+            "#,
+        )
+        .bind(parent)
+        .fetch_all(pool)
+        .await?;
+
+        let mut partitions: Vec<EventPartition> = rows
+            .iter()
+            .map(|row| {
+                let name: String = row.get("name");
+                let month_start = parse_partition_month(parent, &name);
+                EventPartition { name, month_start }
+            })
+            .collect();
+
+        partitions.sort_by_key(|p| p.month_start.unwrap_or(DateTime::<Utc>::MAX_UTC));
+        Ok(partitions)
+    }
+
+    /// 删除整月都早于保留期的分区，并清理默认分区中的过期数据。
+    /// 只作用于原始事件表，K线等汇总表不受影响。
+    pub async fn drop_expired_partitions(pool: &PgPool, retention_days: u32) -> Result<Vec<String>> {
+        let mut dropped = Vec::new();
+        if retention_days == 0 {
+            return Ok(dropped);
+        }
+
+        let cutoff = Utc::now() - Duration::days(retention_days as i64);
+
+        for table in PARTITIONED_EVENT_TABLES {
+            for partition in Self::list_partitions(pool, table).await? {
+                match partition.month_end() {
+                    Some(end) if end <= cutoff => {
+                        sqlx::query(&format!("DROP TABLE IF EXISTS \"{}\"", partition.name))
+                            .execute(pool)
+                            .await?;
+                        info!("🗑️ 删除过期分区 {} (保留 {} 天)", partition.name, retention_days);
+                        dropped.push(partition.name);
+                    }
+                    Some(_) => {}
+                    None => {
+                        let result = sqlx::query(&format!(
+                            "DELETE FROM \"{}\" WHERE timestamp < $1",
+                            partition.name
+                        ))
+                        .bind(cutoff)
+                        .execute(pool)
+                        .await?;
+
+                        if result.rows_affected() > 0 {
+                            warn!(
+                                "🗑️ 默认分区 {} 清理了 {} 条过期数据",
+                                partition.name,
+                                result.rows_affected()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(dropped)
+    }
+}
+
+/// 从 `<parent>_pYYYYMM` 形式的分区名解析出月份起始时间
+fn parse_partition_month(parent: &str, name: &str) -> Option<DateTime<Utc>> {
+    let suffix = name.strip_prefix(parent)?.strip_prefix("_p")?;
+    if suffix.len() != 6 {
+        return None;
+    }
+    let year: i32 = suffix[..4].parse().ok()?;
+    let month: u32 = suffix[4..].parse().ok()?;
+    let date = NaiveDate::from_ymd_opt(year, month, 1)?;
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?))
+}
+
+/// 分区对应的月份键，用于在监听器内缓存已创建的分区
+pub fn partition_month_key(ts: DateTime<Utc>) -> i32 {
+    ts.year() * 100 + ts.month() as i32
+}
@@ -0,0 +1,83 @@
+use crate::types::QuoteSelection;
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::info;
+
+/// 以 token0 计价（价格取 price1，成交量取 amount0）
+pub const QUOTE_SIDE_TOKEN0: i32 = 0;
+/// 以 token1 计价（价格取 price0，成交量取 amount1）
+pub const QUOTE_SIDE_TOKEN1: i32 = 1;
+
+pub struct QuoteOperations;
+
+impl QuoteOperations {
+    /// 用配置覆盖链的计价代币列表，并刷新该链所有交易对的计价方
+    pub async fn sync_quote_tokens(pool: &PgPool, chain_id: i32, tokens: &[String]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM quote_tokens WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (priority, token) in tokens.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO quote_tokens (chain_id, token_address, priority)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (chain_id, token_address) DO NOTHING
+                "#,
+            )
+            .bind(chain_id)
+            .bind(token.to_lowercase())
+            .bind(priority as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE trading_pairs
+            SET quote_side = pair_quote_side(chain_id, token0, token1)
+            WHERE chain_id = $1 AND quote_side <> pair_quote_side(chain_id, token0, token1)
+            "#,
+        )
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!(
+            "💱 链 {} 计价代币已同步 ({} 个)，更新 {} 个交易对的计价方",
+            chain_id,
+            tokens.len(),
+            result.rows_affected()
+        );
+        Ok(())
+    }
+
+    /// 解析接口请求的计价方，`auto` 时读取交易对记录；交易对不存在时按 token0 计价
+    pub async fn resolve_quote_side(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        selection: QuoteSelection,
+    ) -> Result<i32> {
+        match selection {
+            QuoteSelection::Token0 => Ok(QUOTE_SIDE_TOKEN0),
+            QuoteSelection::Token1 => Ok(QUOTE_SIDE_TOKEN1),
+            QuoteSelection::Auto => {
+                let side = sqlx::query_scalar::<_, i32>(
+                    "SELECT quote_side FROM trading_pairs WHERE chain_id = $1 AND address = $2",
+                )
+                .bind(chain_id)
+                .bind(pair_address)
+                .fetch_optional(pool)
+                .await?;
+
+                Ok(side.unwrap_or(QUOTE_SIDE_TOKEN0))
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::database::utils::*;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// 由风险服务定期重算的标记，其余标记来自合约字节码检查
+pub const DYNAMIC_RISK_FLAGS: [&str; 2] = ["fee_on_transfer", "lp_unlocked"];
+
+/// 需要检查风险的交易对（地址小写）
+#[derive(Debug, Clone)]
+pub struct RiskTarget {
+    pub pair_address: String,
+    /// 非计价方代币
+    pub token_address: String,
+    /// 尚未完成字节码检查
+    pub unchecked: bool,
+}
+
+pub struct RiskOperations;
+
+impl RiskOperations {
+    /// 写入字节码检查结果，保留已有的动态标记并重算风险分
+    pub async fn set_token_risk(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        token_address: &str,
+        static_flags: &[String],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE trading_pairs tp
+            SET (risk_token, risk_flags, risk_score, risk_checked_at) = (
+                SELECT $3, f.flags, token_risk_score(f.flags), NOW()
+                FROM (
+                    SELECT $4::TEXT[] || ARRAY(SELECT x FROM unnest(tp.risk_flags) x WHERE x = ANY($5)) AS flags
+                ) f
+            )
+            WHERE tp.chain_id = $1 AND tp.address = $2
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(token_address)
+        .bind(static_flags)
+        .bind(&DYNAMIC_RISK_FLAGS[..])
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 创建时间在窗口内的交易对，未检查过的优先，其次是最久未检查的
+    pub async fn get_risk_targets(
+        pool: &PgPool,
+        chain_id: i32,
+        window_hours: u32,
+        limit: i64,
+    ) -> Result<Vec<RiskTarget>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                address AS pair_address,
+                COALESCE(risk_token, CASE WHEN quote_side = 0 THEN token1 ELSE token0 END) AS token_address,
+                risk_token IS NULL AS unchecked
+            FROM trading_pairs
+            WHERE chain_id = $1
+            AND created_at >= NOW() - make_interval(hours => $2)
+            ORDER BY risk_token IS NULL DESC, risk_checked_at ASC NULLS FIRST
+            LIMIT $3
+            "#,
+        )
+        .bind(chain_id)
+        .bind(window_hours as i32)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| RiskTarget {
+                pair_address: safe_get_string(row, "pair_address"),
+                token_address: safe_get_string(row, "token_address"),
+                unchecked: safe_get_bool(row, "unchecked"),
+            })
+            .collect())
+    }
+
+    /// 重算动态标记：
+    /// - fee_on_transfer：swap 监听器采样到转账税，或至少两笔交易在本交易对内连续卖出两次风险代币，
+    ///   即合约在用户卖出时自动卖出税款（swapback）
+    /// - lp_unlocked：已锁定的 LP 比例低于阈值，池子还没有流动性时（`lp_locked_pct` 为 None）不标记
+    pub async fn update_dynamic_risk(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        lp_locked_pct: Option<Decimal>,
+        lp_locked_threshold: u32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE trading_pairs tp
+            SET (risk_flags, risk_score, lp_locked_pct, risk_checked_at) = (
+                SELECT f.flags, token_risk_score(f.flags), $3, NOW()
+                FROM (
+                    SELECT ARRAY(SELECT x FROM unnest(tp.risk_flags) x WHERE x <> ALL($5))
+                        || CASE WHEN EXISTS (
+                            SELECT 1 FROM token_transfer_taxes t
+                            WHERE t.chain_id = tp.chain_id AND t.token_address = tp.risk_token AND t.max_tax_rate > 0
+                        ) OR (
+                            SELECT COUNT(*) FROM (
+                                SELECT s.transaction_hash
+                                FROM swap_events s
+                                WHERE s.chain_id = tp.chain_id
+                                AND s.pair_address = tp.address
+                                AND s.timestamp >= tp.created_at
+                                AND s.side = CASE WHEN tp.quote_side = 0 THEN 'sell' ELSE 'buy' END
+                                GROUP BY s.transaction_hash
+                                HAVING COUNT(*) >= 2
+                            ) t
+                        ) >= 2 THEN ARRAY['fee_on_transfer'] ELSE '{}'::TEXT[] END
+                        || CASE WHEN $3 < $4 THEN ARRAY['lp_unlocked'] ELSE '{}'::TEXT[] END AS flags
+                ) f
+            )
+            WHERE tp.chain_id = $1 AND tp.address = $2
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(lp_locked_pct)
+        .bind(Decimal::from(lp_locked_threshold))
+        .bind(&DYNAMIC_RISK_FLAGS[..])
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
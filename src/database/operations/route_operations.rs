@@ -0,0 +1,72 @@
+use crate::database::utils::*;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// 报价用的交易对，地址小写，储备量为原始整数的十进制字符串
+#[derive(Debug, Clone)]
+pub struct RoutePool {
+    pub pair_address: String,
+    pub token0: String,
+    pub token1: String,
+    pub token0_decimals: i32,
+    pub token1_decimals: i32,
+    pub reserve0: String,
+    pub reserve1: String,
+    /// 工厂手续费率（基点）
+    pub fee_bps: i32,
+    pub token0_price_usd: Option<Decimal>,
+    pub token1_price_usd: Option<Decimal>,
+}
+
+pub struct RouteOperations;
+
+impl RouteOperations {
+    /// 链上所有两侧储备量都大于 0 的交易对，指定 `min_liquidity_usd` 时只返回美元 TVL 不低于该值的交易对
+    pub async fn get_route_pools(
+        pool: &PgPool,
+        chain_id: i32,
+        min_liquidity_usd: Option<Decimal>,
+    ) -> Result<Vec<RoutePool>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT lower(tp.address) AS pair_address,
+                   lower(tp.token0) AS token0,
+                   lower(tp.token1) AS token1,
+                   COALESCE(tp.token0_decimals, 18) AS token0_decimals,
+                   COALESCE(tp.token1_decimals, 18) AS token1_decimals,
+                   pr.reserve0::TEXT AS reserve0,
+                   pr.reserve1::TEXT AS reserve1,
+                   (pair_fee_rate(tp.chain_id) * 10000)::INTEGER AS fee_bps,
+                   u0.price_usd AS token0_price_usd,
+                   u1.price_usd AS token1_price_usd
+            FROM trading_pairs tp
+            JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+            LEFT JOIN token_usd_prices u0 ON u0.chain_id = tp.chain_id AND u0.token_address = lower(tp.token0)
+            LEFT JOIN token_usd_prices u1 ON u1.chain_id = tp.chain_id AND u1.token_address = lower(tp.token1)
+            WHERE tp.chain_id = $1 AND pr.reserve0 > 0 AND pr.reserve1 > 0
+            AND ($2::DECIMAL IS NULL OR pr.reserve_usd >= $2)
+            "#,
+        )
+        .bind(chain_id)
+        .bind(min_liquidity_usd)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| RoutePool {
+                pair_address: safe_get_string(row, "pair_address"),
+                token0: safe_get_string(row, "token0"),
+                token1: safe_get_string(row, "token1"),
+                token0_decimals: safe_get_i32(row, "token0_decimals"),
+                token1_decimals: safe_get_i32(row, "token1_decimals"),
+                reserve0: safe_get_string(row, "reserve0"),
+                reserve1: safe_get_string(row, "reserve1"),
+                fee_bps: safe_get_i32(row, "fee_bps"),
+                token0_price_usd: safe_get_optional_decimal(row, "token0_price_usd"),
+                token1_price_usd: safe_get_optional_decimal(row, "token1_price_usd"),
+            })
+            .collect())
+    }
+}
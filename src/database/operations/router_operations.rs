@@ -0,0 +1,41 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::info;
+
+pub struct RouterOperations;
+
+impl RouterOperations {
+    /// 用配置覆盖链的已知路由 / 聚合器合约列表
+    pub async fn sync_known_routers(
+        pool: &PgPool,
+        chain_id: i32,
+        routers: &[(String, Option<String>)],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM known_routers WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (address, label) in routers {
+            sqlx::query(
+                r#"
+                INSERT INTO known_routers (chain_id, address, label)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (chain_id, address) DO UPDATE SET label = EXCLUDED.label, updated_at = NOW()
+                "#,
+            )
+            .bind(chain_id)
+            .bind(address.to_lowercase())
+            .bind(label)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        info!("🧭 链 {} 已知路由合约已同步 ({} 个)", chain_id, routers.len());
+        Ok(())
+    }
+}
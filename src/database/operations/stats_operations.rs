@@ -21,14 +21,10 @@ impl StatsOperations {
                 tp.chain_id,
                 COUNT(DISTINCT tp.address) as total_pairs,
                 COALESCE(SUM(
-                    CASE 
-                        WHEN se.amount0_in > 0 THEN se.amount0_in
-                        WHEN se.amount1_in > 0 THEN se.amount1_in  
-                        ELSE 0
-                    END
+                    COALESCE(se.volume_usd, 0)
                 ), 0) as total_volume_24h,
                 COUNT(DISTINCT CASE 
-                    WHEN se.created_at >= NOW() - INTERVAL '24 hours' THEN tp.address 
+                    WHEN se.timestamp >= NOW() - INTERVAL '24 hours' THEN tp.address 
                 END) as active_pairs_24h
             FROM trading_pairs tp
             LEFT JOIN swap_events se ON se.pair_address = tp.address 
@@ -48,7 +44,10 @@ impl StatsOperations {
             END as chain_name,
             total_pairs,
             total_volume_24h,
-            0 as total_liquidity,
+            COALESCE(
+                (SELECT SUM(pr.reserve_usd) FROM pair_reserves pr
+                 WHERE pr.chain_id = chain_activity.chain_id), 0
+            ) as total_liquidity,
             active_pairs_24h
         FROM chain_activity
         ORDER BY chain_id
@@ -0,0 +1,221 @@
+use crate::database::utils::*;
+use crate::types::{SupplyExclusion, TokenSupply};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// 需要刷新供应量的代币（地址小写）
+#[derive(Debug, Clone)]
+pub struct SupplyTarget {
+    pub token_address: String,
+    pub decimals: i32,
+}
+
+pub struct SupplyOperations;
+
+impl SupplyOperations {
+    /// 出现在交易对中、还没有供应量记录或记录早于 `refresh_after_secs` 秒的代币，最久未尝试的优先。
+    /// 读取失败的代币按连续失败次数退避（最多 8 个刷新周期），避免无法读取的代币一直排在最前
+    pub async fn get_tokens_due(
+        pool: &PgPool,
+        chain_id: i32,
+        refresh_after_secs: u64,
+        limit: i64,
+    ) -> Result<Vec<SupplyTarget>> {
+        let rows = sqlx::query(
+            r#"
+            WITH tokens AS (
+                SELECT lower(token0) AS token_address, MAX(token0_decimals) AS decimals
+                FROM trading_pairs WHERE chain_id = $1 GROUP BY lower(token0)
+                UNION ALL
+                SELECT lower(token1), MAX(token1_decimals)
+                FROM trading_pairs WHERE chain_id = $1 GROUP BY lower(token1)
+            ),
+            candidates AS (
+                SELECT token_address, COALESCE(MAX(decimals), 18) AS decimals
+                FROM tokens
+                GROUP BY token_address
+            )
+            SELECT c.token_address, c.decimals
+            FROM candidates c
+            LEFT JOIN token_supply s ON s.chain_id = $1 AND s.token_address = c.token_address
+            LEFT JOIN token_supply_failures f ON f.chain_id = $1 AND f.token_address = c.token_address
+            WHERE (s.updated_at IS NULL OR s.updated_at < NOW() - make_interval(secs => $2))
+              AND (f.failed_at IS NULL OR f.failed_at < NOW() - make_interval(secs => $2 * LEAST(f.attempts, 8)))
+            ORDER BY GREATEST(s.updated_at, f.failed_at) ASC NULLS FIRST
+            LIMIT $3
+            "#,
+        )
+        .bind(chain_id)
+        .bind(refresh_after_secs as f64)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SupplyTarget {
+                token_address: safe_get_string(row, "token_address"),
+                decimals: safe_get_i32(row, "decimals"),
+            })
+            .collect())
+    }
+
+    pub async fn upsert_token_supply(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+        total_supply: Decimal,
+        excluded_supply: Decimal,
+        block_number: i64,
+    ) -> Result<()> {
+        let circulating_supply = (total_supply - excluded_supply).max(Decimal::ZERO);
+
+        sqlx::query(
+            r#"
+            INSERT INTO token_supply (chain_id, token_address, total_supply, circulating_supply, excluded_supply, block_number)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                total_supply = EXCLUDED.total_supply,
+                circulating_supply = EXCLUDED.circulating_supply,
+                excluded_supply = EXCLUDED.excluded_supply,
+                block_number = EXCLUDED.block_number,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(token_address)
+        .bind(total_supply)
+        .bind(circulating_supply)
+        .bind(excluded_supply)
+        .bind(block_number)
+        .execute(pool)
+        .await?;
+
+        sqlx::query("DELETE FROM token_supply_failures WHERE chain_id = $1 AND token_address = $2")
+            .bind(chain_id)
+            .bind(token_address)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 记录一次读取失败（totalSupply 调用回退或数量超出可表示范围），连续失败次数加一
+    pub async fn record_failure(pool: &PgPool, chain_id: i32, token_address: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_supply_failures (chain_id, token_address, failed_at, attempts)
+            VALUES ($1, $2, NOW(), 1)
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                failed_at = NOW(),
+                attempts = token_supply_failures.attempts + 1
+            "#,
+        )
+        .bind(chain_id)
+        .bind(token_address)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_token_supply(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+    ) -> Result<Option<TokenSupply>> {
+        let row = sqlx::query(
+            "SELECT * FROM token_supply WHERE chain_id = $1 AND token_address = $2",
+        )
+        .bind(chain_id)
+        .bind(token_address.to_lowercase())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| TokenSupply {
+            chain_id: safe_get_i32(&row, "chain_id"),
+            token_address: safe_get_string(&row, "token_address"),
+            total_supply: safe_get_decimal(&row, "total_supply"),
+            circulating_supply: safe_get_decimal(&row, "circulating_supply"),
+            excluded_supply: safe_get_decimal(&row, "excluded_supply"),
+            block_number: safe_get_i64(&row, "block_number"),
+            updated_at: safe_get_datetime(&row, "updated_at"),
+        }))
+    }
+
+    /// 单个代币配置的排除地址（不含链级默认地址）
+    pub async fn get_exclusions(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+    ) -> Result<Vec<SupplyExclusion>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT holder_address, label FROM token_supply_exclusions
+            WHERE chain_id = $1 AND token_address = $2
+            ORDER BY created_at
+            "#,
+        )
+        .bind(chain_id)
+        .bind(token_address.to_lowercase())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SupplyExclusion {
+                holder_address: safe_get_string(row, "holder_address"),
+                label: safe_get_optional_string(row, "label"),
+            })
+            .collect())
+    }
+
+    /// 整体替换代币的排除地址，并让供应量在下一轮立即重新读取
+    pub async fn replace_exclusions(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+        exclusions: &[SupplyExclusion],
+    ) -> Result<()> {
+        let token_address = token_address.to_lowercase();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM token_supply_exclusions WHERE chain_id = $1 AND token_address = $2")
+            .bind(chain_id)
+            .bind(&token_address)
+            .execute(&mut *tx)
+            .await?;
+
+        for exclusion in exclusions {
+            sqlx::query(
+                r#"
+                INSERT INTO token_supply_exclusions (chain_id, token_address, holder_address, label)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (chain_id, token_address, holder_address) DO UPDATE SET label = EXCLUDED.label
+                "#,
+            )
+            .bind(chain_id)
+            .bind(&token_address)
+            .bind(exclusion.holder_address.to_lowercase())
+            .bind(&exclusion.label)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("UPDATE token_supply SET updated_at = 'epoch' WHERE chain_id = $1 AND token_address = $2")
+            .bind(chain_id)
+            .bind(&token_address)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM token_supply_failures WHERE chain_id = $1 AND token_address = $2")
+            .bind(chain_id)
+            .bind(&token_address)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
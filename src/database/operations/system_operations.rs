@@ -1,5 +1,11 @@
+use crate::database::operations::activity_operations::ActivityOperations;
+use crate::database::operations::candle_operations::{candle_partition_name, CANDLE_PERIODS};
+use crate::database::operations::partition_operations::{
+    PartitionOperations, PARTITIONED_EVENT_TABLES,
+};
 use crate::types::*;
 use anyhow::Result;
+use chrono::Utc;
 use sqlx::PgPool;
 use tracing::debug;
 
@@ -26,6 +32,12 @@ impl SystemOperations {
                 block_number BIGINT NOT NULL,
                 transaction_hash VARCHAR(66) NOT NULL,
                 created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                quote_side INTEGER NOT NULL DEFAULT 0, -- 计价代币：0 = token0, 1 = token1
+                risk_token VARCHAR(42), -- 风险评分针对的代币（非计价方）
+                risk_flags TEXT[] NOT NULL DEFAULT '{}',
+                risk_score INTEGER, -- 0-100，越高风险越大，未检查时为 NULL
+                lp_locked_pct DECIMAL,
+                risk_checked_at TIMESTAMPTZ,
                 CONSTRAINT valid_token_pair CHECK (token0 < token1),
                 UNIQUE (chain_id, address)
             )
@@ -34,14 +46,147 @@ impl SystemOperations {
         .execute(pool)
         .await?;
 
-        // Create swap_events table with foreign key
+        // 计价代币优先级（来自 <PREFIX>_QUOTE_TOKENS 配置，priority 越小越优先）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS quote_tokens (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                priority INTEGER NOT NULL,
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 根据计价代币优先级判断交易对的计价方，两边都不是计价代币时默认 token0
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION pair_quote_side(p_chain_id INTEGER, p_token0 TEXT, p_token1 TEXT)
+            RETURNS INTEGER AS $$
+                SELECT CASE
+                    WHEN q1.priority IS NOT NULL AND (q0.priority IS NULL OR q1.priority < q0.priority) THEN 1
+                    ELSE 0
+                END
+                FROM (SELECT
+                    (SELECT priority FROM quote_tokens WHERE chain_id = p_chain_id AND token_address = lower(p_token0)) AS priority
+                ) q0,
+                (SELECT
+                    (SELECT priority FROM quote_tokens WHERE chain_id = p_chain_id AND token_address = lower(p_token1)) AS priority
+                ) q1
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 按风险标记累加的新代币风险分，封顶 100
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION token_risk_score(p_flags TEXT[])
+            RETURNS INTEGER AS $$
+                SELECT LEAST(100, COALESCE(SUM(CASE f
+                    WHEN 'no_code' THEN 100
+                    WHEN 'proxy' THEN 25
+                    WHEN 'mintable' THEN 25
+                    WHEN 'blacklist' THEN 20
+                    WHEN 'pausable' THEN 15
+                    WHEN 'fee_on_transfer' THEN 20
+                    WHEN 'lp_unlocked' THEN 20
+                    ELSE 0
+                END), 0))::INTEGER
+                FROM unnest(p_flags) AS f
+            $$ LANGUAGE sql IMMUTABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 各链工厂的 swap 手续费率（来自 <PREFIX>_SWAP_FEE_BPS 配置）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS factory_fees (
+                chain_id INTEGER PRIMARY KEY,
+                factory_address VARCHAR(42) NOT NULL,
+                fee_bps INTEGER NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 交易对的手续费率（小数），未配置时按 Uniswap V2 的 0.3%
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION pair_fee_rate(p_chain_id INTEGER)
+            RETURNS NUMERIC AS $$
+                SELECT COALESCE((SELECT fee_bps FROM factory_fees WHERE chain_id = p_chain_id), 30)::NUMERIC / 10000
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 按月分区的辅助函数，事件表依赖它创建分区
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION ensure_monthly_partition(parent TEXT, ts TIMESTAMPTZ)
+            RETURNS TEXT AS $$
+            DECLARE
+                month_start TIMESTAMPTZ := date_trunc('month', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+                month_end TIMESTAMPTZ := month_start + INTERVAL '1 month';
+                partition_name TEXT := format('%s_p%s', parent, to_char(month_start AT TIME ZONE 'UTC', 'YYYYMM'));
+                default_name TEXT := format('%s_default', parent);
+                has_rows BOOLEAN := FALSE;
+            BEGIN
+                IF to_regclass(partition_name) IS NOT NULL THEN
+                    RETURN partition_name;
+                END IF;
+
+                IF to_regclass(default_name) IS NOT NULL THEN
+                    EXECUTE format(
+                        'SELECT EXISTS (SELECT 1 FROM %I WHERE timestamp >= %L AND timestamp < %L)',
+                        default_name, month_start, month_end
+                    ) INTO has_rows;
+                END IF;
+
+                IF has_rows THEN
+                    -- 默认分区中已有该月数据：先建独立表并迁移数据，再挂载为分区
+                    EXECUTE format('CREATE TABLE %I (LIKE %I INCLUDING DEFAULTS INCLUDING CONSTRAINTS)', partition_name, parent);
+                    EXECUTE format(
+                        'WITH moved AS (DELETE FROM %I WHERE timestamp >= %L AND timestamp < %L RETURNING *) INSERT INTO %I SELECT * FROM moved',
+                        default_name, month_start, month_end, partition_name
+                    );
+                    EXECUTE format(
+                        'ALTER TABLE %I ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+                        parent, partition_name, month_start, month_end
+                    );
+                ELSE
+                    EXECUTE format(
+                        'CREATE TABLE IF NOT EXISTS %I PARTITION OF %I FOR VALUES FROM (%L) TO (%L)',
+                        partition_name, parent, month_start, month_end
+                    );
+                END IF;
+
+                RETURN partition_name;
+            END;
+            $$ LANGUAGE plpgsql
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // Create swap_events table, partitioned by month on timestamp
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS swap_events (
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                id UUID NOT NULL DEFAULT gen_random_uuid(),
                 chain_id INTEGER NOT NULL,
                 pair_address VARCHAR(42) NOT NULL,
-                sender VARCHAR(42) NOT NULL,
+                sender VARCHAR(42) NOT NULL, -- 交易发起人 (tx.from)
+                event_sender VARCHAR(42), -- 事件中的 sender，通常为路由合约
                 amount0_in DECIMAL(78, 0) NOT NULL,
                 amount1_in DECIMAL(78, 0) NOT NULL,
                 amount0_out DECIMAL(78, 0) NOT NULL,
@@ -51,8 +196,19 @@ impl SystemOperations {
                 transaction_hash VARCHAR(66) NOT NULL,
                 log_index INTEGER NOT NULL,
                 timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
-                UNIQUE(chain_id, transaction_hash, log_index)
-            )
+                -- 以下为写入时按代币精度计算的归一化字段
+                amount0 DECIMAL,
+                amount1 DECIMAL,
+                price0 DECIMAL,
+                price1 DECIMAL,
+                side VARCHAR(4),
+                volume_usd DECIMAL, -- 美元成交量，由估值服务的代币价格计算
+                fee_amount DECIMAL, -- LP 手续费，以输入代币计（归一化）
+                fee_usd DECIMAL,
+                transfer_tax_rate DECIMAL, -- 风险代币的转账税率（0-1），仅在采样检测到转账税时写入
+                PRIMARY KEY (id, timestamp),
+                UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+            ) PARTITION BY RANGE (timestamp)
             "#
         )
         .execute(pool)
@@ -127,10 +283,11 @@ impl SystemOperations {
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS burn_events (
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                id UUID NOT NULL DEFAULT gen_random_uuid(),
                 chain_id INTEGER NOT NULL,
                 pair_address VARCHAR(42) NOT NULL,
-                sender VARCHAR(42) NOT NULL,
+                sender VARCHAR(42) NOT NULL, -- 交易发起人 (tx.from)
+                event_sender VARCHAR(42), -- 事件中的 sender，通常为路由合约
                 amount0 DECIMAL(78, 0) NOT NULL,
                 amount1 DECIMAL(78, 0) NOT NULL,
                 to_address VARCHAR(42) NOT NULL,
@@ -138,8 +295,10 @@ impl SystemOperations {
                 transaction_hash VARCHAR(66) NOT NULL,
                 log_index INTEGER NOT NULL,
                 timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                UNIQUE(chain_id, transaction_hash, log_index)
-            )
+                value_usd DECIMAL, -- 移除流动性的美元价值
+                PRIMARY KEY (id, timestamp),
+                UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+            ) PARTITION BY RANGE (timestamp)
             "#
         )
         .execute(pool)
@@ -149,18 +308,21 @@ impl SystemOperations {
         sqlx::query!(
             r#"
             CREATE TABLE IF NOT EXISTS mint_events (
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                id UUID NOT NULL DEFAULT gen_random_uuid(),
                 chain_id INTEGER NOT NULL,
                 pair_address VARCHAR(42) NOT NULL,
-                sender VARCHAR(42) NOT NULL,
+                sender VARCHAR(42) NOT NULL, -- 交易发起人 (tx.from)
+                event_sender VARCHAR(42), -- 事件中的 sender，通常为路由合约
                 amount0 DECIMAL(78, 0) NOT NULL,
                 amount1 DECIMAL(78, 0) NOT NULL,
                 block_number BIGINT NOT NULL,
                 transaction_hash VARCHAR(66) NOT NULL,
                 log_index INTEGER NOT NULL,
                 timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                UNIQUE(chain_id, transaction_hash, log_index)
-            )
+                value_usd DECIMAL, -- 添加流动性的美元价值
+                PRIMARY KEY (id, timestamp),
+                UNIQUE(chain_id, transaction_hash, log_index, timestamp)
+            ) PARTITION BY RANGE (timestamp)
             "#
         )
         .execute(pool)
@@ -185,6 +347,554 @@ impl SystemOperations {
         .execute(pool)
         .await?;
 
+        // 交易对最新储备量（来自 Sync 事件的原始数量），reserve_usd 为美元 TVL
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS pair_reserves (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                reserve0 DECIMAL(78, 0) NOT NULL,
+                reserve1 DECIMAL(78, 0) NOT NULL,
+                reserve_usd DECIMAL,
+                block_number BIGINT NOT NULL,
+                log_index INTEGER NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 估值服务推导出的代币美元价格（地址小写）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_usd_prices (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                price_usd DECIMAL NOT NULL,
+                source VARCHAR(10) NOT NULL, -- stable | anchor | route
+                via_pair VARCHAR(42),
+                hops INTEGER NOT NULL,
+                liquidity_usd DECIMAL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 链上 totalSupply 及扣除排除地址余额后的流通量（按精度归一化，地址小写）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_supply (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                total_supply DECIMAL NOT NULL,
+                circulating_supply DECIMAL NOT NULL,
+                excluded_supply DECIMAL NOT NULL DEFAULT 0,
+                block_number BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 供应量读取失败的代币：记录最近失败时间和连续失败次数，刷新时按次数退避，成功后删除
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_supply_failures (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                attempts INTEGER NOT NULL DEFAULT 1,
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 单个代币不计入流通量的持有地址（团队、金库、锁仓合约等），与链级 <PREFIX>_SUPPLY_EXCLUDED_ADDRESSES 叠加
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_supply_exclusions (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                holder_address VARCHAR(42) NOT NULL,
+                label VARCHAR(50),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, token_address, holder_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 各链已知的路由 / 聚合器合约（来自 <PREFIX>_ROUTER_ADDRESSES 配置），用于区分合约与终端用户
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS known_routers (
+                chain_id INTEGER NOT NULL,
+                address VARCHAR(42) NOT NULL,
+                label VARCHAR(50),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 钱包 × 交易对 × UTC 日的增量聚合，供排行榜查询；事件写入和美元值补算时累加
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS wallet_pair_daily_stats (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                wallet_address VARCHAR(42) NOT NULL,
+                day DATE NOT NULL,
+                trade_count BIGINT NOT NULL DEFAULT 0,
+                volume_usd DECIMAL NOT NULL DEFAULT 0,
+                fees_paid_usd DECIMAL NOT NULL DEFAULT 0,
+                amount0_net DECIMAL NOT NULL DEFAULT 0,
+                amount1_net DECIMAL NOT NULL DEFAULT 0,
+                mint_count BIGINT NOT NULL DEFAULT 0,
+                burn_count BIGINT NOT NULL DEFAULT 0,
+                liquidity_added_usd DECIMAL NOT NULL DEFAULT 0,
+                liquidity_removed_usd DECIMAL NOT NULL DEFAULT 0,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address, wallet_address, day)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 由同一交易内首尾相接的 swap 重建的逻辑成交（如 A -> WETH -> B），数量已按精度归一化
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS trades (
+                chain_id INTEGER NOT NULL,
+                transaction_hash VARCHAR(66) NOT NULL,
+                first_log_index INTEGER NOT NULL,
+                log_indexes INTEGER[] NOT NULL,
+                block_number BIGINT NOT NULL,
+                timestamp TIMESTAMPTZ NOT NULL,
+                tx_origin VARCHAR(42) NOT NULL,
+                event_sender VARCHAR(42),
+                recipient VARCHAR(42) NOT NULL,
+                token_in VARCHAR(42) NOT NULL,
+                token_in_symbol VARCHAR(20),
+                amount_in DECIMAL NOT NULL,
+                token_out VARCHAR(42) NOT NULL,
+                token_out_symbol VARCHAR(20),
+                amount_out DECIMAL NOT NULL,
+                path TEXT[] NOT NULL,
+                pairs TEXT[] NOT NULL,
+                hop_count INTEGER NOT NULL,
+                PRIMARY KEY (chain_id, transaction_hash, first_log_index)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 夹子攻击：同一区块同一交易对内，攻击者抢跑 (frontrun) 与尾随 (backrun) 夹住受害者 swap
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS sandwiches (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                block_number BIGINT NOT NULL,
+                timestamp TIMESTAMPTZ NOT NULL,
+                attacker VARCHAR(42) NOT NULL,
+                frontrun_tx VARCHAR(66) NOT NULL,
+                frontrun_log_index INTEGER NOT NULL,
+                backrun_tx VARCHAR(66) NOT NULL,
+                backrun_log_index INTEGER NOT NULL,
+                victim_count INTEGER NOT NULL,
+                victim_volume_usd DECIMAL,
+                profit_token VARCHAR(42) NOT NULL,
+                profit_token_symbol VARCHAR(20),
+                profit_amount DECIMAL NOT NULL,
+                profit_usd DECIMAL,
+                PRIMARY KEY (chain_id, frontrun_tx, frontrun_log_index)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 参与夹子攻击的 swap 及其角色（frontrun / victim / backrun）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS sandwich_swaps (
+                chain_id INTEGER NOT NULL,
+                transaction_hash VARCHAR(66) NOT NULL,
+                log_index INTEGER NOT NULL,
+                block_number BIGINT NOT NULL,
+                role VARCHAR(10) NOT NULL,
+                wallet VARCHAR(42) NOT NULL,
+                volume_usd DECIMAL,
+                frontrun_tx VARCHAR(66) NOT NULL,
+                frontrun_log_index INTEGER NOT NULL,
+                PRIMARY KEY (chain_id, transaction_hash, log_index)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 交易对近 24 小时的刷量指标与评分，由估值服务每轮重算
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS pair_wash_stats (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                volume_usd DECIMAL NOT NULL,
+                organic_volume_usd DECIMAL NOT NULL,
+                trade_count BIGINT NOT NULL,
+                trader_count BIGINT NOT NULL,
+                round_trip_volume_usd DECIMAL NOT NULL,
+                circular_volume_usd DECIMAL NOT NULL,
+                top_wallet_share DECIMAL NOT NULL,
+                top5_wallet_share DECIMAL NOT NULL,
+                wash_score DECIMAL NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 代币转账税与 rebase 检测结果，由 swap 监听器采样写入
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_transfer_taxes (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                buy_tax_rate DECIMAL, -- 最近一次从交易对转出时的税率（0-1）
+                sell_tax_rate DECIMAL, -- 最近一次转入交易对时的税率（0-1）
+                max_tax_rate DECIMAL NOT NULL DEFAULT 0,
+                sampled_swaps BIGINT NOT NULL DEFAULT 0,
+                taxed_swaps BIGINT NOT NULL DEFAULT 0,
+                rebase_events BIGINT NOT NULL DEFAULT 0, -- 储备量在 swap / mint / burn 之外发生变化的次数
+                last_sampled_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 钱包 × 交易对 × 5 分钟的成交聚合，swap 写入时累加，维护服务删除 24 小时之前的桶
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS wallet_pair_activity_5m (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                wallet_address VARCHAR(42) NOT NULL,
+                bucket TIMESTAMPTZ NOT NULL,
+                buy_count BIGINT NOT NULL DEFAULT 0, -- swap 原始方向，buy = token0 换 token1
+                sell_count BIGINT NOT NULL DEFAULT 0,
+                buy_volume_usd DECIMAL NOT NULL DEFAULT 0,
+                sell_volume_usd DECIMAL NOT NULL DEFAULT 0,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address, bucket, wallet_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // TWAP 采样：price*_cumulative 为采样区块时间戳处的累计价格（UQ112x112，按 2^256 回绕），
+        // 已按 getReserves 补上 blockTimestampLast 之后的累计部分
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS pair_twap_samples (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_timestamp TIMESTAMPTZ NOT NULL,
+                price0_cumulative NUMERIC(78, 0) NOT NULL,
+                price1_cumulative NUMERIC(78, 0) NOT NULL,
+                reserve0 NUMERIC(78, 0) NOT NULL,
+                reserve1 NUMERIC(78, 0) NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address, block_number)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 热门代币评分及各分项，由估值服务每轮刷新代币统计视图后重算
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_trending (
+                chain_id INTEGER NOT NULL,
+                token_address VARCHAR(42) NOT NULL,
+                score DECIMAL NOT NULL, -- 0-100
+                volume_1h_usd DECIMAL NOT NULL, -- 最近 1 小时自然成交量
+                volume_baseline_usd DECIMAL NOT NULL, -- 此前 24 小时平均每小时自然成交量
+                volume_acceleration DECIMAL NOT NULL,
+                traders_1h BIGINT NOT NULL,
+                traders_baseline DECIMAL NOT NULL, -- 此前 24 小时平均每小时独立交易者
+                trader_growth DECIMAL NOT NULL,
+                price_change_1h DECIMAL NOT NULL,
+                liquidity_usd DECIMAL NOT NULL,
+                volume_component DECIMAL NOT NULL, -- 以下各分项均为 0-1
+                trader_component DECIMAL NOT NULL,
+                price_component DECIMAL NOT NULL,
+                liquidity_component DECIMAL NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, token_address)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 循环套利机会，数量均按精度归一化，route 为各跳明细（SwapQuoteHop）
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                chain_id INTEGER NOT NULL,
+                block_number BIGINT NOT NULL,
+                start_token VARCHAR(42) NOT NULL,
+                pairs TEXT[] NOT NULL, -- 按兑换顺序
+                amount_in DECIMAL NOT NULL,
+                amount_out DECIMAL NOT NULL,
+                profit DECIMAL NOT NULL,
+                profit_usd DECIMAL NOT NULL,
+                amount_in_usd DECIMAL NOT NULL,
+                spread_pct DECIMAL NOT NULL,
+                return_pct DECIMAL NOT NULL,
+                route JSONB NOT NULL,
+                detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE (chain_id, block_number, pairs)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 巨鲸交易，由 swap 监听器写入事件时按链 / 交易对阈值标记，数量按精度归一化
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS whale_trades (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                event_type VARCHAR(10) NOT NULL, -- swap | mint | burn
+                side VARCHAR(4), -- swap 原始方向
+                wallet VARCHAR(42) NOT NULL,
+                amount0 DECIMAL NOT NULL,
+                amount1 DECIMAL NOT NULL,
+                value_usd DECIMAL,
+                reserve_pct DECIMAL,
+                trigger VARCHAR(10) NOT NULL, -- usd | reserve | both
+                transaction_hash VARCHAR(66) NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index INTEGER NOT NULL,
+                timestamp TIMESTAMPTZ NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE (chain_id, transaction_hash, log_index)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 撤池 / 跑路告警，dedup_key 保证同一事件只告警一次
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS alerts (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                alert_type VARCHAR(30) NOT NULL,
+                severity VARCHAR(10) NOT NULL,
+                wallet VARCHAR(42),
+                transaction_hash VARCHAR(66),
+                block_number BIGINT,
+                message TEXT NOT NULL,
+                details JSONB NOT NULL DEFAULT '{}',
+                dedup_key VARCHAR(200) NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE (chain_id, dedup_key)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 按原始数量计算交易对两侧代币的美元价值，只有一侧有价格时按两侧等值估算
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION pair_liquidity_usd(p_chain_id INTEGER, p_pair TEXT, p_raw0 NUMERIC, p_raw1 NUMERIC)
+            RETURNS NUMERIC AS $$
+                SELECT trim_scale(round(CASE
+                    WHEN u0.price_usd IS NOT NULL AND u1.price_usd IS NOT NULL
+                        THEN p_raw0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) * u0.price_usd
+                           + p_raw1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) * u1.price_usd
+                    WHEN u0.price_usd IS NOT NULL
+                        THEN 2 * p_raw0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) * u0.price_usd
+                    WHEN u1.price_usd IS NOT NULL
+                        THEN 2 * p_raw1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) * u1.price_usd
+                END, 28))
+                FROM trading_pairs tp
+                LEFT JOIN token_usd_prices u0 ON u0.chain_id = tp.chain_id AND u0.token_address = lower(tp.token0)
+                LEFT JOIN token_usd_prices u1 ON u1.chain_id = tp.chain_id AND u1.token_address = lower(tp.token1)
+                WHERE tp.chain_id = p_chain_id AND tp.address = p_pair
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 按归一化数量计算 swap 的美元成交量，取离锚点更近（跳数更少）的一侧
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION swap_volume_usd(p_chain_id INTEGER, p_pair TEXT, p_amount0 NUMERIC, p_amount1 NUMERIC)
+            RETURNS NUMERIC AS $$
+                SELECT trim_scale(round(CASE
+                    WHEN u0.price_usd IS NOT NULL AND (u1.price_usd IS NULL OR u0.hops <= u1.hops)
+                        THEN p_amount0 * u0.price_usd
+                    ELSE p_amount1 * u1.price_usd
+                END, 28))
+                FROM trading_pairs tp
+                LEFT JOIN token_usd_prices u0 ON u0.chain_id = tp.chain_id AND u0.token_address = lower(tp.token0)
+                LEFT JOIN token_usd_prices u1 ON u1.chain_id = tp.chain_id AND u1.token_address = lower(tp.token1)
+                WHERE tp.chain_id = p_chain_id AND tp.address = p_pair
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 事件表的默认分区，兜底尚未创建月分区的数据
+        sqlx::query!("CREATE TABLE IF NOT EXISTS swap_events_default PARTITION OF swap_events DEFAULT")
+            .execute(pool)
+            .await?;
+        sqlx::query!("CREATE TABLE IF NOT EXISTS mint_events_default PARTITION OF mint_events DEFAULT")
+            .execute(pool)
+            .await?;
+        sqlx::query!("CREATE TABLE IF NOT EXISTS burn_events_default PARTITION OF burn_events DEFAULT")
+            .execute(pool)
+            .await?;
+
+        // K线时间桶函数：统一以 UTC 计算，周线从周一开始
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION candle_bucket(period TEXT, ts TIMESTAMPTZ)
+            RETURNS TIMESTAMPTZ AS $$
+                SELECT CASE period
+                    WHEN '1m' THEN date_bin('1 minute', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '5m' THEN date_bin('5 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '15m' THEN date_bin('15 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '30m' THEN date_bin('30 minutes', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '1h' THEN date_bin('1 hour', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '4h' THEN date_bin('4 hours', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '1d' THEN date_bin('1 day', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '1w' THEN date_bin('7 days', ts, TIMESTAMPTZ '2000-01-03 00:00:00+00')
+                    WHEN '1M' THEN date_trunc('month', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                    WHEN '1y' THEN date_trunc('year', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                END
+            $$ LANGUAGE sql IMMUTABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 预聚合K线表，按周期分区（pair_candles_1m、pair_candles_5m ...）
+        // first_seq / last_seq = block_number * 100000 + log_index，用于确定开盘/收盘成交
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS pair_candles (
+                chain_id INTEGER NOT NULL,
+                pair_address VARCHAR(42) NOT NULL,
+                period VARCHAR(3) NOT NULL,
+                bucket_start TIMESTAMPTZ NOT NULL,
+                open DECIMAL NOT NULL,
+                high DECIMAL NOT NULL,
+                low DECIMAL NOT NULL,
+                close DECIMAL NOT NULL,
+                volume0 DECIMAL NOT NULL DEFAULT 0,
+                volume1 DECIMAL NOT NULL DEFAULT 0,
+                trade_count BIGINT NOT NULL DEFAULT 0,
+                first_seq BIGINT NOT NULL,
+                last_seq BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (chain_id, pair_address, period, bucket_start)
+            ) PARTITION BY LIST (period)
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        for period in CANDLE_PERIODS {
+            sqlx::query(&format!(
+                "CREATE TABLE IF NOT EXISTS \"{}\" PARTITION OF pair_candles FOR VALUES IN ('{}')",
+                candle_partition_name(period),
+                period
+            ))
+            .execute(pool)
+            .await?;
+        }
+
+        // 交易对在 ts 时刻的价格（price1 方向）：取 ts 之前已收盘的最后一根 1m K线的收盘价；
+        // ts 时还没有成交（新交易对）时取之后第一根K线的开盘价
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION pair_price_at(p_chain_id INTEGER, p_pair TEXT, p_ts TIMESTAMPTZ)
+            RETURNS NUMERIC AS $$
+                SELECT COALESCE(
+                    (SELECT close FROM pair_candles
+                     WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+                       AND bucket_start <= p_ts - INTERVAL '1 minute'
+                     ORDER BY bucket_start DESC LIMIT 1),
+                    (SELECT open FROM pair_candles
+                     WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+                       AND bucket_start > p_ts - INTERVAL '1 minute'
+                     ORDER BY bucket_start ASC LIMIT 1)
+                )
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
+        // 交易对最近 p_window 的价格涨跌幅（%），p_quote_side 为 1 时按 price0 方向计算；从未成交时为 NULL
+        sqlx::query!(
+            r#"
+            CREATE OR REPLACE FUNCTION pair_price_change(p_chain_id INTEGER, p_pair TEXT, p_window INTERVAL, p_quote_side INTEGER)
+            RETURNS NUMERIC AS $$
+                SELECT CASE WHEN cur > 0 AND ref > 0 THEN
+                    trim_scale(round(CASE WHEN p_quote_side = 0
+                        THEN (cur - ref) / ref
+                        ELSE (ref - cur) / cur
+                    END * 100, 8))
+                END
+                FROM (SELECT
+                    (SELECT close FROM pair_candles
+                     WHERE chain_id = p_chain_id AND pair_address = p_pair AND period = '1m'
+                     ORDER BY bucket_start DESC LIMIT 1) AS cur,
+                    pair_price_at(p_chain_id, p_pair, NOW() - p_window) AS ref
+                ) p
+            $$ LANGUAGE sql STABLE
+            "#
+        )
+        .execute(pool)
+        .await?;
+
         Ok(())
     }
 
@@ -198,6 +908,10 @@ impl SystemOperations {
             "CREATE INDEX IF NOT EXISTS idx_trading_pairs_tokens ON trading_pairs(chain_id, token0, token1)"
         ).execute(pool).await?;
 
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_trading_pairs_risk_token ON trading_pairs(chain_id, risk_token)"
+        ).execute(pool).await?;
+
         // Indexes for swap_events
         sqlx::query!(
             "CREATE INDEX IF NOT EXISTS idx_swap_events_pair ON swap_events(chain_id, pair_address)"
@@ -221,6 +935,13 @@ impl SystemOperations {
         .execute(pool)
         .await?;
 
+        // 多跳成交重建按区块区间扫描
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_swap_events_block ON swap_events(chain_id, block_number)"
+        )
+        .execute(pool)
+        .await?;
+
         // Indexes for liquidity_events
         sqlx::query!(
             "CREATE INDEX IF NOT EXISTS idx_liquidity_events_pair ON liquidity_events(chain_id, pair_address)"
@@ -249,45 +970,194 @@ impl SystemOperations {
         .execute(pool)
         .await?;
 
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_wallet_pair_daily_stats_chain_day ON wallet_pair_daily_stats(chain_id, day)"
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_trades_time ON trades(chain_id, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_trades_block ON trades(chain_id, block_number)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_trades_origin ON trades(tx_origin, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_trades_recipient ON trades(recipient, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_sandwiches_time ON sandwiches(chain_id, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_sandwiches_block ON sandwiches(chain_id, block_number)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_sandwiches_attacker ON sandwiches(attacker, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_sandwich_swaps_block ON sandwich_swaps(chain_id, block_number)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_sandwich_swaps_wallet ON sandwich_swaps(wallet, role)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_alerts_time ON alerts(chain_id, created_at DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_alerts_pair ON alerts(chain_id, pair_address, created_at DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_token_trending_score ON token_trending(chain_id, score DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_wallet_pair_activity_5m_bucket ON wallet_pair_activity_5m(bucket)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_pair_twap_samples_time ON pair_twap_samples(chain_id, pair_address, block_timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_arbitrage_opportunities_time ON arbitrage_opportunities(chain_id, detected_at DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_whale_trades_time ON whale_trades(chain_id, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
+        sqlx::query!("CREATE INDEX IF NOT EXISTS idx_whale_trades_pair ON whale_trades(chain_id, pair_address, timestamp DESC)")
+            .execute(pool)
+            .await?;
+
         Ok(())
     }
 
     pub async fn create_views(pool: &PgPool) -> Result<(), sqlx::Error> {
-        // Create materialized view for token statistics
-        sqlx::query(
-        "CREATE MATERIALIZED VIEW IF NOT EXISTS token_stats_mv AS 
-         SELECT
-             tp.chain_id, 
-             COALESCE(tp.token0, tp.token1) as token_address, 
-             COALESCE(tp.token0_symbol, tp.token1_symbol) as symbol, 
-             COUNT(DISTINCT tp.address) as pair_count, 
-             COUNT(se.transaction_hash) as swap_count, 
-             SUM(COALESCE(se.volume_usd, 0)) as total_volume, 
-             AVG(COALESCE(se.price, 0)) as avg_price, 
-             MAX(se.created_at) as last_trade 
-         FROM trading_pairs tp 
-         LEFT JOIN swap_events se ON tp.chain_id = se.chain_id AND tp.address = se.pair_address 
-         GROUP BY tp.chain_id, COALESCE(tp.token0, tp.token1), COALESCE(tp.token0_symbol, tp.token1_symbol)"
-    )
-    .execute(pool)
-    .await?;
-
-        // Create materialized view for pair statistics
+        // 代币维度统计：交易对的两侧各展开一行后按代币聚合，覆盖代币参与的所有交易对。
+        // 价格为各池隐含美元价格（对手代币美元价 × 储备比）按池 TVL 加权，没有可用池时回退到 token_usd_prices；
+        // 涨跌幅取流动性最大的池，代币为 token0 时按 price0 方向计算；自然成交量为各池成交量扣除刷量评分部分之和；
+        // 独立交易者和买卖拆分来自 wallet_pair_activity_5m，买入指换入该代币，同一钱包在多个池交易只计一次
+        sqlx::query(&format!(
+            r#"
+            CREATE MATERIALIZED VIEW IF NOT EXISTS token_stats_mv AS
+            WITH sides AS (
+                SELECT tp.chain_id, tp.address AS pair_address, tp.created_at,
+                       lower(tp.token0) AS token_address, lower(tp.token1) AS other_token,
+                       tp.token0_symbol AS symbol, tp.token0_name AS name, tp.token0_decimals AS decimals,
+                       tp.token1_decimals AS other_decimals,
+                       pr.reserve0 AS reserve, pr.reserve1 AS other_reserve, pr.reserve_usd,
+                       1 AS price_side
+                FROM trading_pairs tp
+                LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+                UNION ALL
+                SELECT tp.chain_id, tp.address, tp.created_at,
+                       lower(tp.token1), lower(tp.token0),
+                       tp.token1_symbol, tp.token1_name, tp.token1_decimals,
+                       tp.token0_decimals,
+                       pr.reserve1, pr.reserve0, pr.reserve_usd,
+                       0
+                FROM trading_pairs tp
+                LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+            ),
+            pools AS (
+                SELECT s.*,
+                       CASE WHEN s.reserve > 0 AND s.other_reserve > 0 THEN
+                           u.price_usd * (s.other_reserve / power(10::numeric, COALESCE(s.other_decimals, 18)))
+                                       / (s.reserve / power(10::numeric, COALESCE(s.decimals, 18)))
+                       END AS pool_price_usd,
+                       v.volume_1h, v.volume_24h, v.trade_count_24h,
+                       COALESCE(w.organic_volume_usd, 0) AS organic_volume_24h
+                FROM sides s
+                LEFT JOIN token_usd_prices u ON u.chain_id = s.chain_id AND u.token_address = s.other_token
+                LEFT JOIN pair_wash_stats w ON w.chain_id = s.chain_id AND w.pair_address = s.pair_address
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(SUM(se.volume_usd) FILTER (WHERE se.timestamp >= NOW() - INTERVAL '1 hour'), 0) AS volume_1h,
+                           COALESCE(SUM(se.volume_usd), 0) AS volume_24h,
+                           COUNT(*) AS trade_count_24h
+                    FROM swap_events se
+                    WHERE se.chain_id = s.chain_id AND se.pair_address = s.pair_address
+                      AND se.timestamp >= NOW() - INTERVAL '24 hours'
+                ) v
+            ),
+            tokens AS (
+                SELECT chain_id, token_address,
+                       (array_agg(symbol ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE symbol IS NOT NULL))[1] AS symbol,
+                       (array_agg(name ORDER BY reserve_usd DESC NULLS LAST) FILTER (WHERE name IS NOT NULL))[1] AS name,
+                       MAX(decimals) AS decimals,
+                       COUNT(*)::INTEGER AS pair_count,
+                       MIN(created_at) AS listed_at,
+                       COALESCE(SUM(reserve_usd), 0) AS liquidity_usd,
+                       SUM(volume_1h) AS volume_1h_usd,
+                       SUM(volume_24h) AS volume_24h_usd,
+                       SUM(organic_volume_24h) AS organic_volume_24h_usd,
+                       SUM(trade_count_24h) AS trade_count_24h,
+                       SUM(pool_price_usd * reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0)
+                           / NULLIF(SUM(reserve_usd) FILTER (WHERE pool_price_usd IS NOT NULL AND reserve_usd > 0), 0)
+                           AS weighted_price_usd,
+                       (array_agg(pair_address ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_pair,
+                       (array_agg(price_side ORDER BY reserve_usd DESC NULLS LAST, pair_address))[1] AS main_side
+                FROM pools
+                GROUP BY chain_id, token_address
+            ),
+            activity AS (
+                SELECT s.chain_id, s.token_address, a.wallet_address, a.bucket,
+                       CASE WHEN s.price_side = 0 THEN a.buy_count ELSE a.sell_count END AS buys,
+                       CASE WHEN s.price_side = 0 THEN a.sell_count ELSE a.buy_count END AS sells,
+                       CASE WHEN s.price_side = 0 THEN a.buy_volume_usd ELSE a.sell_volume_usd END AS buy_volume_usd,
+                       CASE WHEN s.price_side = 0 THEN a.sell_volume_usd ELSE a.buy_volume_usd END AS sell_volume_usd
+                FROM sides s
+                JOIN wallet_pair_activity_5m a ON a.chain_id = s.chain_id AND a.pair_address = s.pair_address
+                WHERE a.bucket > NOW() - INTERVAL '1445 minutes'
+            ),
+            token_activity AS (
+                SELECT chain_id, token_address, {activity_columns}
+                FROM activity
+                GROUP BY chain_id, token_address
+            )
+            SELECT t.chain_id, t.token_address, t.symbol, t.name, t.decimals,
+                   t.pair_count, t.listed_at, t.main_pair,
+                   trim_scale(round(COALESCE(t.weighted_price_usd, u.price_usd), 28)) AS price_usd,
+                   pair_price_change(t.chain_id, t.main_pair, INTERVAL '1 hour', t.main_side) AS price_change_1h,
+                   pair_price_change(t.chain_id, t.main_pair, INTERVAL '24 hours', t.main_side) AS price_change_24h,
+                   pair_price_change(t.chain_id, t.main_pair, INTERVAL '7 days', t.main_side) AS price_change_7d,
+                   t.liquidity_usd, t.volume_1h_usd, t.volume_24h_usd, t.trade_count_24h,
+                   t.organic_volume_24h_usd,
+                   -- 按成交量加权的刷量评分，即非自然成交量占比
+                   CASE WHEN t.volume_24h_usd > 0 THEN
+                       trim_scale(round((1 - LEAST(t.organic_volume_24h_usd / t.volume_24h_usd, 1)) * 100, 4))
+                   ELSE 0 END AS wash_score,
+                   ta.traders_5m, ta.buyers_5m, ta.sellers_5m, ta.buys_5m, ta.sells_5m, ta.buy_volume_5m, ta.sell_volume_5m,
+                   ta.traders_1h, ta.buyers_1h, ta.sellers_1h, ta.buys_1h, ta.sells_1h, ta.buy_volume_1h, ta.sell_volume_1h,
+                   ta.traders_6h, ta.buyers_6h, ta.sellers_6h, ta.buys_6h, ta.sells_6h, ta.buy_volume_6h, ta.sell_volume_6h,
+                   ta.traders_24h, ta.buyers_24h, ta.sellers_24h, ta.buys_24h, ta.sells_24h, ta.buy_volume_24h, ta.sell_volume_24h,
+                   NOW() AS refreshed_at
+            FROM tokens t
+            LEFT JOIN token_usd_prices u ON u.chain_id = t.chain_id AND u.token_address = t.token_address
+            LEFT JOIN token_activity ta ON ta.chain_id = t.chain_id AND ta.token_address = t.token_address
+            "#,
+            activity_columns = ActivityOperations::window_columns(),
+        ))
+        .execute(pool)
+        .await?;
+
+        // REFRESH ... CONCURRENTLY 需要唯一索引
         sqlx::query(
-            "CREATE MATERIALIZED VIEW IF NOT EXISTS pair_stats_mv AS 
-         SELECT 
-             tp.chain_id, 
-             tp.address as pair_address, 
-             tp.token0_symbol, 
-             tp.token1_symbol, 
-             COUNT(se.transaction_hash) as swap_count, 
-             SUM(COALESCE(se.volume_usd, 0)) as total_volume, 
-             AVG(COALESCE(se.price, 0)) as avg_price, 
-             MIN(se.created_at) as first_trade, 
-             MAX(se.created_at) as last_trade 
-         FROM trading_pairs tp 
-         LEFT JOIN swap_events se ON tp.chain_id = se.chain_id AND tp.address = se.pair_address 
-         GROUP BY tp.chain_id, tp.address, tp.token0_symbol, tp.token1_symbol",
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_token_stats_mv_token ON token_stats_mv(chain_id, token_address)",
         )
         .execute(pool)
         .await?;
@@ -302,29 +1172,54 @@ impl SystemOperations {
         Ok(true)
     }
 
-    pub async fn vacuum_analyze(pool: &PgPool) -> Result<()> {
+    /// 分区感知的 VACUUM ANALYZE
+    ///
+    /// 事件表只清理最近 `hot_partitions` 个月分区和默认分区，历史分区不再写入无需重复清理；
+    /// 分区父表不会被 autovacuum 分析，这里单独 ANALYZE 以保证规划器统计信息。
+    pub async fn vacuum_analyze(pool: &PgPool, hot_partitions: usize) -> Result<()> {
         sqlx::query!("VACUUM (ANALYZE, VERBOSE) trading_pairs")
             .execute(pool)
             .await?;
-        sqlx::query!("VACUUM (ANALYZE, VERBOSE) swap_events")
-            .execute(pool)
-            .await?;
         sqlx::query!("VACUUM (ANALYZE, VERBOSE) liquidity_events")
             .execute(pool)
             .await?;
         sqlx::query!("VACUUM (ANALYZE, VERBOSE) token_metadata")
             .execute(pool)
             .await?;
+
+        for table in PARTITIONED_EVENT_TABLES {
+            let partitions = PartitionOperations::list_partitions(pool, table).await?;
+            let (monthly, default): (Vec<_>, Vec<_>) =
+                partitions.into_iter().partition(|p| p.month_start.is_some());
+
+            let now = Utc::now();
+            let hot = monthly
+                .into_iter()
+                .filter(|p| p.month_start.is_some_and(|start| start <= now))
+                .rev()
+                .take(hot_partitions)
+                .chain(default);
+
+            for partition in hot {
+                debug!("VACUUM 分区 {}", partition.name);
+                sqlx::query(&format!("VACUUM (ANALYZE) \"{}\"", partition.name))
+                    .execute(pool)
+                    .await?;
+            }
+
+            sqlx::query(&format!("ANALYZE {}", table))
+                .execute(pool)
+                .await?;
+        }
+
         Ok(())
     }
 
+    /// 刷新代币统计物化视图，由估值服务在每轮估值完成后调用
     pub async fn refresh_materialized_views(pool: &PgPool) -> Result<()> {
         sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY token_stats_mv")
             .execute(pool)
             .await?;
-        sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY pair_stats_mv")
-            .execute(pool)
-            .await?;
         Ok(())
     }
 }
@@ -0,0 +1,202 @@
+use crate::config::TaxConfig;
+use crate::database::utils::*;
+use crate::types::{SwapEvent, TokenTransferTax};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// 累计至少这么多次储备量漂移后视为 rebase / 反射代币
+pub const MIN_REBASE_EVENTS: i64 = 2;
+
+/// 交易对中需要检测转账税的代币（非计价方）
+#[derive(Debug, Clone)]
+pub struct TaxTarget {
+    pub token_address: String,
+    /// 代币在交易对中的位置：0 = token0, 1 = token1
+    pub token_index: i32,
+    /// 是否需要读取交易回执采样
+    pub due: bool,
+}
+
+pub struct TaxOperations;
+
+impl TaxOperations {
+    /// 未采样够、已检测到转账税或距上次采样超过 `resample_secs` 的代币需要采样
+    pub async fn get_tax_target(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        config: &TaxConfig,
+    ) -> Result<Option<TaxTarget>> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                lower(CASE WHEN tp.quote_side = 0 THEN tp.token1 ELSE tp.token0 END) AS token_address,
+                CASE WHEN tp.quote_side = 0 THEN 1 ELSE 0 END AS token_index,
+                (
+                    t.token_address IS NULL
+                    OR t.sampled_swaps < $3
+                    OR COALESCE(t.buy_tax_rate, 0) > 0
+                    OR COALESCE(t.sell_tax_rate, 0) > 0
+                    OR t.last_sampled_at IS NULL
+                    OR t.last_sampled_at < NOW() - make_interval(secs => $4)
+                ) AS due
+            FROM trading_pairs tp
+            LEFT JOIN token_transfer_taxes t
+                ON t.chain_id = tp.chain_id
+                AND t.token_address = lower(CASE WHEN tp.quote_side = 0 THEN tp.token1 ELSE tp.token0 END)
+            WHERE tp.chain_id = $1 AND tp.address = $2
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(config.sample_swaps)
+        .bind(config.resample_secs as f64)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| TaxTarget {
+            token_address: safe_get_string(&row, "token_address"),
+            token_index: safe_get_i32(&row, "token_index"),
+            due: safe_get_bool(&row, "due"),
+        }))
+    }
+
+    /// 记录一次采样结果，`direction` 为 buy（代币从交易对转出）或 sell（代币转入交易对）
+    pub async fn record_tax_sample(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+        direction: &str,
+        rate: Decimal,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_transfer_taxes (
+                chain_id, token_address, buy_tax_rate, sell_tax_rate, max_tax_rate,
+                sampled_swaps, taxed_swaps, last_sampled_at
+            )
+            VALUES (
+                $1, $2,
+                CASE WHEN $3 = 'buy' THEN $4 END,
+                CASE WHEN $3 = 'sell' THEN $4 END,
+                $4, 1, CASE WHEN $4 > 0 THEN 1 ELSE 0 END, NOW()
+            )
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                buy_tax_rate = CASE WHEN $3 = 'buy' THEN $4 ELSE token_transfer_taxes.buy_tax_rate END,
+                sell_tax_rate = CASE WHEN $3 = 'sell' THEN $4 ELSE token_transfer_taxes.sell_tax_rate END,
+                max_tax_rate = GREATEST(token_transfer_taxes.max_tax_rate, $4),
+                sampled_swaps = token_transfer_taxes.sampled_swaps + 1,
+                taxed_swaps = token_transfer_taxes.taxed_swaps + CASE WHEN $4 > 0 THEN 1 ELSE 0 END,
+                last_sampled_at = NOW(),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(token_address)
+        .bind(direction)
+        .bind(rate)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 标记受转账税影响的 swap，供分析时按实际到账数量修正
+    pub async fn mark_swap_tax(pool: &PgPool, swap: &SwapEvent, rate: Decimal) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE swap_events SET transfer_tax_rate = $5
+            WHERE chain_id = $1 AND transaction_hash = $2 AND log_index = $3 AND timestamp = $4
+            "#,
+        )
+        .bind(swap.chain_id)
+        .bind(&swap.transaction_hash)
+        .bind(swap.log_index)
+        .bind(swap.timestamp)
+        .bind(rate)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 记录一次储备量漂移（代币余额在 swap / mint / burn 之外变化）
+    pub async fn record_rebase_event(pool: &PgPool, chain_id: i32, token_address: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_transfer_taxes (chain_id, token_address, rebase_events)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                rebase_events = token_transfer_taxes.rebase_events + 1,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(token_address)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 没有 swap / mint / burn 的独立 Sync（外部调用 sync()）改变了风险代币一侧的储备量时记一次漂移。
+    /// 须在写入新储备量之前调用，返回是否记录
+    pub async fn record_reserve_drift(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        reserve0: &str,
+        reserve1: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO token_transfer_taxes (chain_id, token_address, rebase_events)
+            SELECT $1, lower(CASE WHEN tp.quote_side = 0 THEN tp.token1 ELSE tp.token0 END), 1
+            FROM trading_pairs tp
+            JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+            WHERE tp.chain_id = $1 AND tp.address = $2
+            AND CASE WHEN tp.quote_side = 0 THEN pr.reserve1 <> $4::NUMERIC ELSE pr.reserve0 <> $3::NUMERIC END
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                rebase_events = token_transfer_taxes.rebase_events + 1,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(reserve0)
+        .bind(reserve1)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_token_transfer_tax(
+        pool: &PgPool,
+        chain_id: i32,
+        token_address: &str,
+    ) -> Result<Option<TokenTransferTax>> {
+        let row = sqlx::query(
+            "SELECT * FROM token_transfer_taxes WHERE chain_id = $1 AND token_address = $2",
+        )
+        .bind(chain_id)
+        .bind(token_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            let rebase_events = safe_get_i64(&row, "rebase_events");
+            TokenTransferTax {
+                buy_tax_rate: safe_get_optional_decimal(&row, "buy_tax_rate"),
+                sell_tax_rate: safe_get_optional_decimal(&row, "sell_tax_rate"),
+                max_tax_rate: safe_get_decimal(&row, "max_tax_rate"),
+                sampled_swaps: safe_get_i64(&row, "sampled_swaps"),
+                taxed_swaps: safe_get_i64(&row, "taxed_swaps"),
+                rebasing: rebase_events >= MIN_REBASE_EVENTS,
+                rebase_events,
+                last_sampled_at: safe_get_optional_datetime(&row, "last_sampled_at"),
+            }
+        }))
+    }
+}
@@ -1,11 +1,68 @@
+use crate::database::activity_operations::row_to_activity_windows;
 use crate::database::metadata_operations::MetadataOperations;
+use crate::database::tax_operations::TaxOperations;
 use crate::database::utils::*;
-use crate::types::{TokenDetail, TokenListItem, TokenMetadata, TokenPriceInfo, TradingPairInfo};
+use crate::types::{
+    TokenDetail, TokenListItem, TokenPriceInfo, TradingPairInfo, TrendingScore, TrendingToken,
+};
 use anyhow::Result;
+use rust_decimal::Decimal;
 use sqlx::{postgres::PgRow, PgPool, Row};
-use std::collections::HashMap;
 pub struct TokenOperations;
 
+/// 代币列表公共的查询列，数据来自 token_stats_mv（按代币聚合所有交易对）
+const TOKEN_LIST_COLUMNS: &str = r#"
+            ts.chain_id,
+            CASE
+                WHEN ts.chain_id = 1 THEN 'Ethereum'
+                WHEN ts.chain_id = 56 THEN 'BSC'
+                WHEN ts.chain_id = 137 THEN 'Polygon'
+                WHEN ts.chain_id = 42161 THEN 'Arbitrum'
+                WHEN ts.chain_id = 2559 THEN 'Kto'
+                WHEN ts.chain_id = 2643 THEN 'NOS'
+                ELSE 'Unknown'
+            END as chain_name,
+            ts.token_address,
+            COALESCE(tm.symbol, ts.symbol, 'UNKNOWN') as symbol,
+            COALESCE(tm.name, ts.name, 'Unknown Token') as name,
+            tm.logo_url,
+            tm.website_url,
+            tm.description,
+            COALESCE(ts.price_usd, 0) as price_usd,
+            COALESCE(ts.price_change_1h, 0) as price_change_1h,
+            COALESCE(ts.price_change_24h, 0) as price_change_24h,
+            COALESCE(ts.price_change_7d, 0) as price_change_7d,
+            ts.volume_1h_usd as volume_1h,
+            ts.volume_24h_usd as volume_24h,
+            ts.trade_count_24h::BIGINT as trade_count_24h,
+            COALESCE(ts.organic_volume_24h_usd, 0) as organic_volume_24h,
+            COALESCE(ts.wash_score, 0) as wash_score,
+            risk.risk_score,
+            COALESCE(risk.risk_flags, '{}') as risk_flags,
+            ts.traders_5m, ts.buyers_5m, ts.sellers_5m, ts.buys_5m, ts.sells_5m, ts.buy_volume_5m, ts.sell_volume_5m,
+            ts.traders_1h, ts.buyers_1h, ts.sellers_1h, ts.buys_1h, ts.sells_1h, ts.buy_volume_1h, ts.sell_volume_1h,
+            ts.traders_6h, ts.buyers_6h, ts.sellers_6h, ts.buys_6h, ts.sells_6h, ts.buy_volume_6h, ts.sell_volume_6h,
+            ts.traders_24h, ts.buyers_24h, ts.sellers_24h, ts.buys_24h, ts.sells_24h, ts.buy_volume_24h, ts.sell_volume_24h,
+            ts.pair_count,
+            trim_scale(round(ts.price_usd * sup.circulating_supply, 8)) as market_cap,
+            trim_scale(round(ts.price_usd * COALESCE(tm.max_supply, sup.total_supply, tm.total_supply), 8)) as fdv,
+            ts.liquidity_usd as liquidity,
+            ts.refreshed_at as last_updated
+"#;
+
+/// 代币列表公共的关联：元数据和链上供应量
+const TOKEN_LIST_JOINS: &str = r#"
+        LEFT JOIN token_metadata tm ON tm.chain_id = ts.chain_id AND lower(tm.address) = ts.token_address
+        LEFT JOIN token_supply sup ON sup.chain_id = ts.chain_id AND sup.token_address = ts.token_address
+        LEFT JOIN LATERAL (
+            SELECT tp.risk_score, tp.risk_flags
+            FROM trading_pairs tp
+            WHERE tp.chain_id = ts.chain_id AND tp.risk_token = ts.token_address AND tp.risk_score IS NOT NULL
+            ORDER BY tp.created_at
+            LIMIT 1
+        ) risk ON TRUE
+"#;
+
 impl TokenOperations {
     pub async fn get_token_list(
         pool: &PgPool,
@@ -14,217 +71,93 @@ impl TokenOperations {
         offset: i32,
         sort_by: &str,
         order: &str,
+        max_wash_score: Option<Decimal>,
     ) -> Result<Vec<TokenListItem>> {
         let order_clause = match order.to_lowercase().as_str() {
             "asc" => "ASC",
             _ => "DESC",
         };
 
-        // 定义排序表达式而不是列名
-        let sort_expression = match sort_by {
-            "price" => "current_price",
-            "volume" => {
-                r#"(SELECT SUM(
-                        CASE 
-                            WHEN amount0_in > 0 THEN amount0_in
-                            WHEN amount1_in > 0 THEN amount1_in  
-                            ELSE 0
-                        END
-                    ) FROM swap_events 
-                     WHERE pair_address = ts.pair_address AND chain_id = ts.chain_id
-                     AND timestamp >= NOW() - INTERVAL '24 hours')"#
-            }
-            "market_cap" => "market_cap",
-            "liquidity" => "total_liquidity",
-            _ => {
-                r#"(SELECT SUM(
-                        CASE 
-                            WHEN amount0_in > 0 THEN amount0_in
-                            WHEN amount1_in > 0 THEN amount1_in  
-                            ELSE 0
-                        END
-                    ) FROM swap_events 
-                     WHERE pair_address = ts.pair_address AND chain_id = ts.chain_id
-                     AND timestamp >= NOW() - INTERVAL '24 hours')"#
-            }
+        let sort_column = match sort_by {
+            "price" => "ts.price_usd",
+            "volume_1h" => "ts.volume_1h_usd",
+            "trades" => "ts.trade_count_24h",
+            "liquidity" => "ts.liquidity_usd",
+            "market_cap" => "ts.price_usd * sup.circulating_supply",
+            "fdv" => "ts.price_usd * COALESCE(tm.max_supply, sup.total_supply, tm.total_supply)",
+            "pairs" => "ts.pair_count",
+            "price_change_1h" => "ts.price_change_1h",
+            "price_change_24h" => "ts.price_change_24h",
+            "price_change_7d" => "ts.price_change_7d",
+            "organic_volume" | "organic_volume_24h" => "ts.organic_volume_24h_usd",
+            "wash_score" => "ts.wash_score",
+            _ => "ts.volume_24h_usd",
         };
 
-        let chain_filter = if let Some(chain_id) = chain_id {
-            format!("WHERE tp.chain_id = {}", chain_id)
-        } else {
+        let mut conditions = Vec::new();
+        if let Some(chain_id) = chain_id {
+            conditions.push(format!("ts.chain_id = {}", chain_id));
+        }
+        if let Some(max_wash_score) = max_wash_score {
+            conditions.push(format!("COALESCE(ts.wash_score, 0) <= {}", max_wash_score));
+        }
+        let chain_filter = if conditions.is_empty() {
             "".to_string()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
         };
 
         let query = format!(
             r#"
-    WITH token_stats AS (
-        SELECT 
-            ROW_NUMBER() OVER (ORDER BY COALESCE(SUM(
-                (SELECT SUM(
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END
-                ) FROM swap_events 
-                 WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                 AND timestamp >= NOW() - INTERVAL '24 hours')
-            ), 0) DESC) as rank,
-            tp.chain_id,
-            CASE 
-                WHEN tp.chain_id = 1 THEN 'Ethereum'
-                WHEN tp.chain_id = 56 THEN 'BSC'
-                WHEN tp.chain_id = 137 THEN 'Polygon'
-                WHEN tp.chain_id = 42161 THEN 'Arbitrum'
-                WHEN tp.chain_id = 2559 THEN 'Kto'
-                WHEN tp.chain_id = 2643 THEN 'NOS'
-                ELSE 'Unknown'
-            END as chain_name,
-            tp.address as pair_address,
-            COALESCE(tp.token0_symbol, 'UNKNOWN') as token0_symbol,
-            COALESCE(tp.token1_symbol, 'UNKNOWN') as token1_symbol,
-            COALESCE(tp.token0_name, 'Unknown Token') as token0_name,
-            COALESCE(tp.token1_name, 'Unknown Token') as token1_name,
-            tm0.logo_url as token0_logo_url,
-            tm1.logo_url as token1_logo_url,
-            tm0.website_url as token0_website_url,
-            tm1.website_url as token1_website_url,
-            tm0.explorer_url as token0_explorer_url,
-            tm1.explorer_url as token1_explorer_url,
-            tm0.description as token0_description,
-            tm1.description as token1_description,
-            tm0.tags as token0_tags,
-            tm1.tags as token1_tags,
-            -- 当前价格
-            COALESCE(
-                (SELECT 
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END
-                 FROM swap_events 
-                 WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                 AND (
-                     (amount0_in > 0 AND amount1_out > 0) OR 
-                     (amount1_in > 0 AND amount0_out > 0)
-                 )
-                 ORDER BY timestamp DESC 
-                 LIMIT 1), 0
-            ) as current_price,
-            -- 1小时价格变化
-            0 as price_change_1h,
-            -- 24小时价格变化
-            0 as price_change_24h,
-            -- 1小时成交量
-            COALESCE(
-                (SELECT SUM(
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END
-                ) FROM swap_events 
-                 WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                 AND timestamp >= NOW() - INTERVAL '1 hour'), 0
-            ) as volume_1h,
-            -- 24小时成交量
-            COALESCE(
-                (SELECT SUM(
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END
-                ) FROM swap_events 
-                 WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                 AND timestamp >= NOW() - INTERVAL '24 hours'), 0
-            ) as volume_24h,
-            0 as fdv,
-            0 as market_cap,
-            0 as total_liquidity,
-            NOW() as last_updated
-        FROM trading_pairs tp
-        LEFT JOIN token_metadata tm0 ON tm0.chain_id = tp.chain_id AND tm0.address = tp.token0
-        LEFT JOIN token_metadata tm1 ON tm1.chain_id = tp.chain_id AND tm1.address = tp.token1
-        {}
-        GROUP BY tp.chain_id, tp.address, tp.token0_symbol, tp.token1_symbol, tp.token0_name, tp.token1_name,
-                 tm0.logo_url, tm1.logo_url, tm0.website_url, tm1.website_url, tm0.explorer_url, tm1.explorer_url,
-                 tm0.description, tm1.description, tm0.tags, tm1.tags
-    )
-    SELECT * FROM token_stats ts
-    ORDER BY {} {}
-    LIMIT {} OFFSET {}
-    "#,
-            chain_filter, sort_expression, order_clause, limit, offset
+        SELECT
+            (ROW_NUMBER() OVER (ORDER BY {sort} {order} NULLS LAST, ts.token_address))::INTEGER as rank,
+            {columns}
+        FROM token_stats_mv ts
+        {joins}
+        {filter}
+        ORDER BY {sort} {order} NULLS LAST, ts.token_address
+        LIMIT {limit} OFFSET {offset}
+        "#,
+            sort = sort_column,
+            order = order_clause,
+            columns = TOKEN_LIST_COLUMNS,
+            joins = TOKEN_LIST_JOINS,
+            filter = chain_filter,
+            limit = limit,
+            offset = offset,
         );
 
         let rows = sqlx::query(&query).fetch_all(pool).await?;
-
-        let mut tokens = Vec::new();
-        for row in rows {
-            let token0_tags: Option<Vec<String>> = safe_get_optional_string(&row, "token0_tags")
-                .and_then(|s| serde_json::from_str(&s).ok());
-            let token1_tags: Option<Vec<String>> = safe_get_optional_string(&row, "token1_tags")
-                .and_then(|s| serde_json::from_str(&s).ok());
-
-            tokens.push(TokenListItem {
-                rank: safe_get_i32(&row, "rank"),
-                chain_id: safe_get_i32(&row, "chain_id"),
-                chain_name: safe_get_string(&row, "chain_name"),
-                pair_address: safe_get_string(&row, "pair_address"),
-                token0_symbol: safe_get_string(&row, "token0_symbol"),
-                token1_symbol: safe_get_string(&row, "token1_symbol"),
-                token0_name: safe_get_string(&row, "token0_name"),
-                token1_name: safe_get_string(&row, "token1_name"),
-                token0_logo_url: safe_get_optional_string(&row, "token0_logo_url"),
-                token1_logo_url: safe_get_optional_string(&row, "token1_logo_url"),
-                token0_website_url: safe_get_optional_string(&row, "token0_website_url"),
-                token1_website_url: safe_get_optional_string(&row, "token1_website_url"),
-                token0_explorer_url: safe_get_optional_string(&row, "token0_explorer_url"),
-                token1_explorer_url: safe_get_optional_string(&row, "token1_explorer_url"),
-                token0_description: safe_get_optional_string(&row, "token0_description"),
-                token1_description: safe_get_optional_string(&row, "token1_description"),
-                token0_tags,
-                token1_tags,
-                price_usd: safe_get_decimal(&row, "current_price"),
-                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
-                price_change_24h: safe_get_decimal(&row, "price_change_24h"),
-                volume_1h: safe_get_decimal(&row, "volume_1h"),
-                volume_24h: safe_get_decimal(&row, "volume_24h"),
-                fdv: safe_get_optional_decimal(&row, "fdv"),
-                market_cap: safe_get_optional_decimal(&row, "market_cap"),
-                liquidity: safe_get_decimal(&row, "total_liquidity"),
-                last_updated: safe_get_datetime(&row, "last_updated"),
-            });
-        }
-
-        Ok(tokens)
+        Ok(rows.iter().map(Self::row_to_token_list_item).collect())
     }
 
+    /// 代币详情：聚合统计 + 参与的所有交易对
+    ///
+    /// 代币既没有元数据也不在任何交易对中时返回 None
     pub async fn get_token_detail(
         pool: &PgPool,
         chain_id: i32,
         address: &str,
     ) -> Result<Option<TokenDetail>> {
-        // 获取token元数据
         let metadata = MetadataOperations::get_token_metadata(pool, chain_id, address).await?;
 
-        if let Some(metadata) = metadata {
-            // 获取价格信息
-            let price_info = Self::get_token_price_info(pool, chain_id, address).await?;
+        let address = address.to_lowercase();
+        let price_info = Self::get_token_price_info(pool, chain_id, &address).await?;
+        let trading_pairs = Self::get_token_trading_pairs(pool, chain_id, &address).await?;
+        let transfer_tax = TaxOperations::get_token_transfer_tax(pool, chain_id, &address).await?;
 
-            // 获取交易对信息
-            let trading_pairs = Self::get_token_trading_pairs(pool, chain_id, address).await?;
-
-            Ok(Some(TokenDetail {
-                metadata,
-                price_info,
-                trading_pairs,
-            }))
-        } else {
-            Ok(None)
+        if metadata.is_none() && price_info.is_none() && trading_pairs.is_empty() {
+            return Ok(None);
         }
+
+        Ok(Some(TokenDetail {
+            chain_id,
+            token_address: address,
+            metadata,
+            price_info,
+            trading_pairs,
+            transfer_tax,
+        }))
     }
 
     async fn get_token_price_info(
@@ -233,54 +166,28 @@ impl TokenOperations {
         address: &str,
     ) -> Result<Option<TokenPriceInfo>> {
         let query = r#"
-        WITH token_prices AS (
-            SELECT 
-                -- 当前价格 (取最新的交易价格)
-                COALESCE(
-                    (SELECT 
-                        CASE 
-                            WHEN se.amount0_in > 0 AND se.amount1_out > 0 AND tp.token0 = $2 THEN se.amount0_in / se.amount1_out
-                            WHEN se.amount1_in > 0 AND se.amount0_out > 0 AND tp.token1 = $2 THEN se.amount0_out / se.amount1_in
-                            WHEN se.amount0_in > 0 AND se.amount1_out > 0 AND tp.token1 = $2 THEN se.amount1_out / se.amount0_in
-                            WHEN se.amount1_in > 0 AND se.amount0_out > 0 AND tp.token0 = $2 THEN se.amount1_in / se.amount0_out
-                            ELSE 0
-                        END
-                     FROM swap_events se
-                     JOIN trading_pairs tp ON tp.address = se.pair_address AND tp.chain_id = se.chain_id
-                     WHERE se.chain_id = $1 AND (tp.token0 = $2 OR tp.token1 = $2)
-                     AND (
-                         (se.amount0_in > 0 AND se.amount1_out > 0) OR 
-                         (se.amount1_in > 0 AND se.amount0_out > 0)
-                     )
-                     ORDER BY se.timestamp DESC 
-                     LIMIT 1), 0
-                ) as current_price,
-                -- 24小时成交量
-                COALESCE(
-                    (SELECT SUM(
-                        CASE 
-                            WHEN se.amount0_in > 0 AND tp.token0 = $2 THEN se.amount0_in
-                            WHEN se.amount1_in > 0 AND tp.token1 = $2 THEN se.amount1_in
-                            WHEN se.amount0_out > 0 AND tp.token0 = $2 THEN se.amount0_out
-                            WHEN se.amount1_out > 0 AND tp.token1 = $2 THEN se.amount1_out
-                            ELSE 0
-                        END
-                    ) FROM swap_events se
-                     JOIN trading_pairs tp ON tp.address = se.pair_address AND tp.chain_id = se.chain_id
-                     WHERE se.chain_id = $1 AND (tp.token0 = $2 OR tp.token1 = $2)
-                     AND se.created_at >= NOW() - INTERVAL '24 hours'), 0
-                ) as volume_24h
-        )
-        SELECT 
-            current_price,
-            0 as price_change_1h,
-            0 as price_change_24h,
-            0 as price_change_7d,
-            volume_24h,
-            NULL as market_cap,
-            NULL as fdv,
-            NOW() as last_updated
-        FROM token_prices
+        SELECT
+            ts.symbol,
+            ts.name,
+            COALESCE(ts.price_usd, 0) as current_price,
+            COALESCE(ts.price_change_1h, 0) as price_change_1h,
+            COALESCE(ts.price_change_24h, 0) as price_change_24h,
+            COALESCE(ts.price_change_7d, 0) as price_change_7d,
+            ts.volume_1h_usd as volume_1h,
+            ts.volume_24h_usd as volume_24h,
+            ts.trade_count_24h::BIGINT as trade_count_24h,
+            ts.pair_count,
+            ts.liquidity_usd as liquidity,
+            ts.main_pair,
+            COALESCE(sup.total_supply, tm.total_supply) as total_supply,
+            sup.circulating_supply,
+            trim_scale(round(ts.price_usd * sup.circulating_supply, 8)) as market_cap,
+            trim_scale(round(ts.price_usd * COALESCE(tm.max_supply, sup.total_supply, tm.total_supply), 8)) as fdv,
+            ts.refreshed_at as last_updated
+        FROM token_stats_mv ts
+        LEFT JOIN token_metadata tm ON tm.chain_id = ts.chain_id AND lower(tm.address) = ts.token_address
+        LEFT JOIN token_supply sup ON sup.chain_id = ts.chain_id AND sup.token_address = ts.token_address
+        WHERE ts.chain_id = $1 AND ts.token_address = $2
     "#;
 
         let row = sqlx::query(query)
@@ -289,75 +196,89 @@ impl TokenOperations {
             .fetch_optional(pool)
             .await?;
 
-        if let Some(row) = row {
-            Ok(Some(TokenPriceInfo {
-                current_price: safe_get_decimal(&row, "current_price"),
-                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
-                price_change_24h: safe_get_decimal(&row, "price_change_24h"),
-                price_change_7d: safe_get_decimal(&row, "price_change_7d"),
-                volume_24h: safe_get_decimal(&row, "volume_24h"),
-                market_cap: safe_get_optional_decimal(&row, "market_cap"),
-                fdv: safe_get_optional_decimal(&row, "fdv"),
-                last_updated: safe_get_datetime(&row, "last_updated"),
-            }))
-        } else {
-            Ok(None)
-        }
+        Ok(row.map(|row| TokenPriceInfo {
+            symbol: safe_get_optional_string(&row, "symbol"),
+            name: safe_get_optional_string(&row, "name"),
+            current_price: safe_get_decimal(&row, "current_price"),
+            price_change_1h: safe_get_decimal(&row, "price_change_1h"),
+            price_change_24h: safe_get_decimal(&row, "price_change_24h"),
+            price_change_7d: safe_get_decimal(&row, "price_change_7d"),
+            volume_1h: safe_get_decimal(&row, "volume_1h"),
+            volume_24h: safe_get_decimal(&row, "volume_24h"),
+            trade_count_24h: safe_get_i64(&row, "trade_count_24h"),
+            pair_count: safe_get_i32(&row, "pair_count"),
+            liquidity: safe_get_decimal(&row, "liquidity"),
+            main_pair: safe_get_optional_string(&row, "main_pair"),
+            total_supply: safe_get_optional_decimal(&row, "total_supply"),
+            circulating_supply: safe_get_optional_decimal(&row, "circulating_supply"),
+            market_cap: safe_get_optional_decimal(&row, "market_cap"),
+            fdv: safe_get_optional_decimal(&row, "fdv"),
+            last_updated: safe_get_datetime(&row, "last_updated"),
+        }))
     }
 
+    /// 代币参与的所有交易对，按池 TVL 降序
     async fn get_token_trading_pairs(
         pool: &PgPool,
         chain_id: i32,
         address: &str,
     ) -> Result<Vec<TradingPairInfo>> {
         let query = r#"
-        SELECT 
-            tp.address as pair_address,
-            CASE 
-                WHEN tp.token0 = $2 THEN COALESCE(tp.token1_symbol, 'UNKNOWN')
-                ELSE COALESCE(tp.token0_symbol, 'UNKNOWN')
-            END as other_token_symbol,
-            CASE 
-                WHEN tp.token0 = $2 THEN COALESCE(tp.token1_name, 'Unknown Token')
-                ELSE COALESCE(tp.token0_name, 'Unknown Token')
-            END as other_token_name,
-            -- 当前价格
+        WITH pools AS (
+            SELECT
+                tp.chain_id,
+                tp.address as pair_address,
+                lower(tp.token0) = $2 as is_token0,
+                CASE WHEN lower(tp.token0) = $2 THEN lower(tp.token1) ELSE lower(tp.token0) END as other_token_address,
+                CASE
+                    WHEN lower(tp.token0) = $2 THEN COALESCE(tp.token1_symbol, 'UNKNOWN')
+                    ELSE COALESCE(tp.token0_symbol, 'UNKNOWN')
+                END as other_token_symbol,
+                CASE
+                    WHEN lower(tp.token0) = $2 THEN COALESCE(tp.token1_name, 'Unknown Token')
+                    ELSE COALESCE(tp.token0_name, 'Unknown Token')
+                END as other_token_name,
+                CASE WHEN lower(tp.token0) = $2 THEN tp.token0_decimals ELSE tp.token1_decimals END as decimals,
+                CASE WHEN lower(tp.token0) = $2 THEN tp.token1_decimals ELSE tp.token0_decimals END as other_decimals,
+                CASE WHEN lower(tp.token0) = $2 THEN pr.reserve0 ELSE pr.reserve1 END as reserve,
+                CASE WHEN lower(tp.token0) = $2 THEN pr.reserve1 ELSE pr.reserve0 END as other_reserve,
+                COALESCE(pr.reserve_usd, 0) as liquidity
+            FROM trading_pairs tp
+            LEFT JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+            WHERE tp.chain_id = $1 AND (lower(tp.token0) = $2 OR lower(tp.token1) = $2)
+        )
+        SELECT
+            p.pair_address,
+            p.other_token_address,
+            p.other_token_symbol,
+            p.other_token_name,
+            -- 以对手代币计的最新成交价
             COALESCE(
-                (SELECT 
-                    CASE 
-                        WHEN se.amount0_in > 0 AND se.amount1_out > 0 AND tp.token0 = $2 THEN se.amount0_in / se.amount1_out
-                        WHEN se.amount1_in > 0 AND se.amount0_out > 0 AND tp.token1 = $2 THEN se.amount0_out / se.amount1_in
-                        WHEN se.amount0_in > 0 AND se.amount1_out > 0 AND tp.token1 = $2 THEN se.amount1_out / se.amount0_in
-                        WHEN se.amount1_in > 0 AND se.amount0_out > 0 AND tp.token0 = $2 THEN se.amount1_in / se.amount0_out
-                        ELSE 0
-                    END
+                (SELECT CASE WHEN p.is_token0 THEN se.price0 ELSE se.price1 END
                  FROM swap_events se
-                 WHERE se.pair_address = tp.address AND se.chain_id = tp.chain_id
-                 AND (
-                     (se.amount0_in > 0 AND se.amount1_out > 0) OR 
-                     (se.amount1_in > 0 AND se.amount0_out > 0)
-                 )
-                 ORDER BY se.timestamp DESC 
+                 WHERE se.pair_address = p.pair_address AND se.chain_id = p.chain_id
+                 AND se.price1 IS NOT NULL
+                 ORDER BY se.timestamp DESC
                  LIMIT 1), 0
             ) as price,
-            -- 24小时成交量
-            COALESCE(
-                (SELECT SUM(
-                    CASE 
-                        WHEN se.amount0_in > 0 AND tp.token0 = $2 THEN se.amount0_in
-                        WHEN se.amount1_in > 0 AND tp.token1 = $2 THEN se.amount1_in
-                        WHEN se.amount0_out > 0 AND tp.token0 = $2 THEN se.amount0_out
-                        WHEN se.amount1_out > 0 AND tp.token1 = $2 THEN se.amount1_out
-                        ELSE 0
-                    END
-                ) FROM swap_events se
-                 WHERE se.pair_address = tp.address AND se.chain_id = tp.chain_id
-                 AND se.timestamp >= NOW() - INTERVAL '24 hours'), 0
-            ) as volume_24h,
-            0 as liquidity
-        FROM trading_pairs tp
-        WHERE tp.chain_id = $1 AND (tp.token0 = $2 OR tp.token1 = $2)
-        ORDER BY volume_24h DESC
+            CASE WHEN p.reserve > 0 AND p.other_reserve > 0 THEN
+                trim_scale(round(
+                    u.price_usd * (p.other_reserve / power(10::numeric, COALESCE(p.other_decimals, 18)))
+                                / (p.reserve / power(10::numeric, COALESCE(p.decimals, 18))), 28))
+            END as price_usd,
+            v.volume_24h,
+            v.trade_count_24h,
+            p.liquidity
+        FROM pools p
+        LEFT JOIN token_usd_prices u ON u.chain_id = p.chain_id AND u.token_address = p.other_token_address
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(SUM(se.volume_usd), 0) as volume_24h,
+                   COUNT(*) as trade_count_24h
+            FROM swap_events se
+            WHERE se.chain_id = p.chain_id AND se.pair_address = p.pair_address
+              AND se.timestamp >= NOW() - INTERVAL '24 hours'
+        ) v
+        ORDER BY p.liquidity DESC, v.volume_24h DESC
     "#;
 
         let rows = sqlx::query(query)
@@ -370,10 +291,13 @@ impl TokenOperations {
         for row in rows {
             pairs.push(TradingPairInfo {
                 pair_address: safe_get_string(&row, "pair_address"),
+                other_token_address: safe_get_string(&row, "other_token_address"),
                 other_token_symbol: safe_get_string(&row, "other_token_symbol"),
                 other_token_name: safe_get_string(&row, "other_token_name"),
                 price: safe_get_decimal(&row, "price"),
+                price_usd: safe_get_optional_decimal(&row, "price_usd"),
                 volume_24h: safe_get_decimal(&row, "volume_24h"),
+                trade_count_24h: safe_get_i64(&row, "trade_count_24h"),
                 liquidity: safe_get_decimal(&row, "liquidity"),
             });
         }
@@ -390,61 +314,31 @@ impl TokenOperations {
         let search_term = format!("%{}%", query.to_lowercase());
 
         let chain_filter = if let Some(chain_id) = chain_id {
-            format!("AND tm.chain_id = {}", chain_id)
+            format!("AND ts.chain_id = {}", chain_id)
         } else {
             "".to_string()
         };
 
         let sql_query = format!(
             r#"
-        SELECT 
-            1 as rank,
-            tm.chain_id,
-            CASE 
-                WHEN tm.chain_id = 1 THEN 'Ethereum'
-                WHEN tm.chain_id = 56 THEN 'BSC'
-                WHEN tm.chain_id = 137 THEN 'Polygon'
-                WHEN tm.chain_id = 42161 THEN 'Arbitrum'
-                ELSE 'Unknown'
-            END as chain_name,
-            '' as pair_address,
-            tm.symbol as token0_symbol,
-            '' as token1_symbol,
-            tm.name as token0_name,
-            '' as token1_name,
-            tm.logo_url as token0_logo_url,
-            NULL as token1_logo_url,
-            tm.website_url as token0_website_url,
-            NULL as token1_website_url,
-            tm.explorer_url as token0_explorer_url,
-            NULL as token1_explorer_url,
-            tm.description as token0_description,
-            NULL as token1_description,
-            tm.tags as token0_tags,
-            NULL as token1_tags,
-            0 as price_usd,
-            0 as price_change_1h,
-            0 as price_change_24h,
-            0 as volume_1h,
-            0 as volume_24h,
-            NULL as fdv,
-            NULL as market_cap,
-            0 as liquidity,
-            tm.updated_at as last_updated
-        FROM token_metadata tm
+        SELECT
+            (ROW_NUMBER() OVER (ORDER BY ts.liquidity_usd DESC))::INTEGER as rank,
+            {}
+        FROM token_stats_mv ts
+        {}
         WHERE (
-            LOWER(tm.symbol) LIKE $1 OR 
-            LOWER(tm.name) LIKE $1 OR 
-            LOWER(tm.address) LIKE $1
+            LOWER(COALESCE(tm.symbol, ts.symbol)) LIKE $1 OR
+            LOWER(COALESCE(tm.name, ts.name)) LIKE $1 OR
+            ts.token_address LIKE $1
         )
         {}
-        ORDER BY 
-            CASE WHEN LOWER(tm.symbol) = LOWER($2) THEN 1 ELSE 2 END,
-            CASE WHEN LOWER(tm.name) = LOWER($2) THEN 1 ELSE 2 END,
-            tm.symbol
+        ORDER BY
+            CASE WHEN LOWER(COALESCE(tm.symbol, ts.symbol)) = LOWER($2) THEN 1 ELSE 2 END,
+            CASE WHEN LOWER(COALESCE(tm.name, ts.name)) = LOWER($2) THEN 1 ELSE 2 END,
+            ts.liquidity_usd DESC
         LIMIT $3
         "#,
-            chain_filter
+            TOKEN_LIST_COLUMNS, TOKEN_LIST_JOINS, chain_filter
         );
 
         let rows = sqlx::query(&sql_query)
@@ -454,151 +348,150 @@ impl TokenOperations {
             .fetch_all(pool)
             .await?;
 
-        let mut tokens = Vec::new();
-        for row in rows {
-            let token0_tags: Option<Vec<String>> = safe_get_optional_string(&row, "token0_tags")
-                .and_then(|s| serde_json::from_str(&s).ok());
-
-            tokens.push(TokenListItem {
-                rank: safe_get_i32(&row, "rank"),
-                chain_id: safe_get_i32(&row, "chain_id"),
-                chain_name: safe_get_string(&row, "chain_name"),
-                pair_address: safe_get_string(&row, "pair_address"),
-                token0_symbol: safe_get_string(&row, "token0_symbol"),
-                token1_symbol: safe_get_string(&row, "token1_symbol"),
-                token0_name: safe_get_string(&row, "token0_name"),
-                token1_name: safe_get_string(&row, "token1_name"),
-                token0_logo_url: safe_get_optional_string(&row, "token0_logo_url"),
-                token1_logo_url: safe_get_optional_string(&row, "token1_logo_url"),
-                token0_website_url: safe_get_optional_string(&row, "token0_website_url"),
-                token1_website_url: safe_get_optional_string(&row, "token1_website_url"),
-                token0_explorer_url: safe_get_optional_string(&row, "token0_explorer_url"),
-                token1_explorer_url: safe_get_optional_string(&row, "token1_explorer_url"),
-                token0_description: safe_get_optional_string(&row, "token0_description"),
-                token1_description: safe_get_optional_string(&row, "token1_description"),
-                token0_tags,
-                token1_tags: None,
-                price_usd: safe_get_decimal(&row, "price_usd"),
-                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
-                price_change_24h: safe_get_decimal(&row, "price_change_24h"),
-                volume_1h: safe_get_decimal(&row, "volume_1h"),
-                volume_24h: safe_get_decimal(&row, "volume_24h"),
-                fdv: safe_get_optional_decimal(&row, "fdv"),
-                market_cap: safe_get_optional_decimal(&row, "market_cap"),
-                liquidity: safe_get_decimal(&row, "liquidity"),
-                last_updated: safe_get_datetime(&row, "last_updated"),
-            });
-        }
-
-        Ok(tokens)
+        Ok(rows.iter().map(Self::row_to_token_list_item).collect())
     }
 
+    /// 热门代币：按估值服务定期计算的动量评分排序（见 `TrendingOperations::refresh_token_trending`），
+    /// `max_wash_score` 可进一步排除刷量评分过高的代币
     pub async fn get_trending_tokens(
         pool: &PgPool,
         chain_id: Option<i32>,
         limit: i32,
-    ) -> Result<Vec<TokenListItem>> {
-        // 基于24小时价格变化和成交量的趋势算法
-        Self::get_token_list(pool, chain_id, limit, 0, "volume", "desc").await
+        max_wash_score: Option<Decimal>,
+    ) -> Result<Vec<TrendingToken>> {
+        let mut conditions = Vec::new();
+        if let Some(chain_id) = chain_id {
+            conditions.push(format!("ts.chain_id = {}", chain_id));
+        }
+        if let Some(max_wash_score) = max_wash_score {
+            conditions.push(format!("COALESCE(ts.wash_score, 0) <= {}", max_wash_score));
+        }
+        let chain_filter = if conditions.is_empty() {
+            "".to_string()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let query = format!(
+            r#"
+        SELECT
+            (ROW_NUMBER() OVER (ORDER BY tt.score DESC, ts.token_address))::INTEGER as rank,
+            {},
+            tt.score as trending_score,
+            tt.volume_1h_usd as trending_volume_1h_usd,
+            tt.volume_baseline_usd as trending_volume_baseline_usd,
+            tt.volume_acceleration as trending_volume_acceleration,
+            tt.traders_1h as trending_traders_1h,
+            tt.traders_baseline as trending_traders_baseline,
+            tt.trader_growth as trending_trader_growth,
+            tt.price_change_1h as trending_price_change_1h,
+            tt.liquidity_usd as trending_liquidity_usd,
+            tt.volume_component as trending_volume_component,
+            tt.trader_component as trending_trader_component,
+            tt.price_component as trending_price_component,
+            tt.liquidity_component as trending_liquidity_component,
+            tt.updated_at as trending_updated_at
+        FROM token_trending tt
+        JOIN token_stats_mv ts ON ts.chain_id = tt.chain_id AND ts.token_address = tt.token_address
+        {}
+        {}
+        ORDER BY tt.score DESC, ts.token_address
+        LIMIT {}
+        "#,
+            TOKEN_LIST_COLUMNS, TOKEN_LIST_JOINS, chain_filter, limit
+        );
+
+        let rows = sqlx::query(&query).fetch_all(pool).await?;
+        Ok(rows
+            .iter()
+            .map(|row| TrendingToken {
+                token: Self::row_to_token_list_item(row),
+                trending: TrendingScore {
+                    score: safe_get_decimal(row, "trending_score"),
+                    volume_1h_usd: safe_get_decimal(row, "trending_volume_1h_usd"),
+                    volume_baseline_usd: safe_get_decimal(row, "trending_volume_baseline_usd"),
+                    volume_acceleration: safe_get_decimal(row, "trending_volume_acceleration"),
+                    traders_1h: safe_get_i64(row, "trending_traders_1h"),
+                    traders_baseline: safe_get_decimal(row, "trending_traders_baseline"),
+                    trader_growth: safe_get_decimal(row, "trending_trader_growth"),
+                    price_change_1h: safe_get_decimal(row, "trending_price_change_1h"),
+                    liquidity_usd: safe_get_decimal(row, "trending_liquidity_usd"),
+                    volume_component: safe_get_decimal(row, "trending_volume_component"),
+                    trader_component: safe_get_decimal(row, "trending_trader_component"),
+                    price_component: safe_get_decimal(row, "trending_price_component"),
+                    liquidity_component: safe_get_decimal(row, "trending_liquidity_component"),
+                    updated_at: safe_get_datetime(row, "trending_updated_at"),
+                },
+            })
+            .collect())
     }
 
+    /// 新代币：按代币首次出现在交易对中的时间排序，`max_risk_score` 排除风险分更高的代币，尚未评分的代币保留
     pub async fn get_new_tokens(
         pool: &PgPool,
         chain_id: Option<i32>,
         limit: i32,
+        max_risk_score: Option<i32>,
     ) -> Result<Vec<TokenListItem>> {
-        let chain_filter = if let Some(chain_id) = chain_id {
-            format!("WHERE tp.chain_id = {}", chain_id)
-        } else {
+        let mut conditions = Vec::new();
+        if let Some(chain_id) = chain_id {
+            conditions.push(format!("ts.chain_id = {}", chain_id));
+        }
+        if let Some(max_risk_score) = max_risk_score {
+            conditions.push(format!("COALESCE(risk.risk_score, 0) <= {}", max_risk_score));
+        }
+        let chain_filter = if conditions.is_empty() {
             "".to_string()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
         };
 
         let query = format!(
             r#"
-        SELECT 
-            ROW_NUMBER() OVER (ORDER BY tp.created_at DESC) as rank,
-            tp.chain_id,
-            CASE 
-                WHEN tp.chain_id = 1 THEN 'Ethereum'
-                WHEN tp.chain_id = 56 THEN 'BSC'
-                WHEN tp.chain_id = 137 THEN 'Polygon'
-                WHEN tp.chain_id = 42161 THEN 'Arbitrum'
-                ELSE 'Unknown'
-            END as chain_name,
-            tp.address as pair_address,
-            COALESCE(tp.token0_symbol, 'UNKNOWN') as token0_symbol,
-            COALESCE(tp.token1_symbol, 'UNKNOWN') as token1_symbol,
-            COALESCE(tp.token0_name, 'Unknown Token') as token0_name,
-            COALESCE(tp.token1_name, 'Unknown Token') as token1_name,
-            tm0.logo_url as token0_logo_url,
-            tm1.logo_url as token1_logo_url,
-            tm0.website_url as token0_website_url,
-            tm1.website_url as token1_website_url,
-            tm0.explorer_url as token0_explorer_url,
-            tm1.explorer_url as token1_explorer_url,
-            tm0.description as token0_description,
-            tm1.description as token1_description,
-            tm0.tags as token0_tags,
-            tm1.tags as token1_tags,
-            0 as price_usd,
-            0 as price_change_1h,
-            0 as price_change_24h,
-            0 as volume_1h,
-            0 as volume_24h,
-            NULL as fdv,
-            NULL as market_cap,
-            0 as liquidity,
-            tp.created_at as last_updated
-        FROM trading_pairs tp
-        LEFT JOIN token_metadata tm0 ON tm0.chain_id = tp.chain_id AND tm0.address = tp.token0
-        LEFT JOIN token_metadata tm1 ON tm1.chain_id = tp.chain_id AND tm1.address = tp.token1
+        SELECT
+            (ROW_NUMBER() OVER (ORDER BY ts.listed_at DESC))::INTEGER as rank,
+            {}
+        FROM token_stats_mv ts
         {}
-        ORDER BY tp.created_at DESC
+        {}
+        ORDER BY ts.listed_at DESC
         LIMIT {}
         "#,
-            chain_filter, limit
+            TOKEN_LIST_COLUMNS, TOKEN_LIST_JOINS, chain_filter, limit
         );
 
         let rows = sqlx::query(&query).fetch_all(pool).await?;
+        Ok(rows.iter().map(Self::row_to_token_list_item).collect())
+    }
 
-        let mut tokens = Vec::new();
-        for row in rows {
-            let token0_tags: Option<Vec<String>> = safe_get_optional_string(&row, "token0_tags")
-                .and_then(|s| serde_json::from_str(&s).ok());
-            let token1_tags: Option<Vec<String>> = safe_get_optional_string(&row, "token1_tags")
-                .and_then(|s| serde_json::from_str(&s).ok());
-
-            tokens.push(TokenListItem {
-                rank: safe_get_i32(&row, "rank"),
-                chain_id: safe_get_i32(&row, "chain_id"),
-                chain_name: safe_get_string(&row, "chain_name"),
-                pair_address: safe_get_string(&row, "pair_address"),
-                token0_symbol: safe_get_string(&row, "token0_symbol"),
-                token1_symbol: safe_get_string(&row, "token1_symbol"),
-                token0_name: safe_get_string(&row, "token0_name"),
-                token1_name: safe_get_string(&row, "token1_name"),
-                token0_logo_url: safe_get_optional_string(&row, "token0_logo_url"),
-                token1_logo_url: safe_get_optional_string(&row, "token1_logo_url"),
-                token0_website_url: safe_get_optional_string(&row, "token0_website_url"),
-                token1_website_url: safe_get_optional_string(&row, "token1_website_url"),
-                token0_explorer_url: safe_get_optional_string(&row, "token0_explorer_url"),
-                token1_explorer_url: safe_get_optional_string(&row, "token1_explorer_url"),
-                token0_description: safe_get_optional_string(&row, "token0_description"),
-                token1_description: safe_get_optional_string(&row, "token1_description"),
-                token0_tags,
-                token1_tags,
-                price_usd: safe_get_decimal(&row, "price_usd"),
-                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
-                price_change_24h: safe_get_decimal(&row, "price_change_24h"),
-                volume_1h: safe_get_decimal(&row, "volume_1h"),
-                volume_24h: safe_get_decimal(&row, "volume_24h"),
-                fdv: safe_get_optional_decimal(&row, "fdv"),
-                market_cap: safe_get_optional_decimal(&row, "market_cap"),
-                liquidity: safe_get_decimal(&row, "liquidity"),
-                last_updated: safe_get_datetime(&row, "last_updated"),
-            });
+    fn row_to_token_list_item(row: &PgRow) -> TokenListItem {
+        TokenListItem {
+            rank: safe_get_i32(row, "rank"),
+            chain_id: safe_get_i32(row, "chain_id"),
+            chain_name: safe_get_string(row, "chain_name"),
+            token_address: safe_get_string(row, "token_address"),
+            symbol: safe_get_string(row, "symbol"),
+            name: safe_get_string(row, "name"),
+            logo_url: safe_get_optional_string(row, "logo_url"),
+            website_url: safe_get_optional_string(row, "website_url"),
+            description: safe_get_optional_string(row, "description"),
+            price_usd: safe_get_decimal(row, "price_usd"),
+            price_change_1h: safe_get_decimal(row, "price_change_1h"),
+            price_change_24h: safe_get_decimal(row, "price_change_24h"),
+            price_change_7d: safe_get_decimal(row, "price_change_7d"),
+            volume_1h: safe_get_decimal(row, "volume_1h"),
+            volume_24h: safe_get_decimal(row, "volume_24h"),
+            trade_count_24h: safe_get_i64(row, "trade_count_24h"),
+            activity: row_to_activity_windows(row),
+            organic_volume_24h: safe_get_decimal(row, "organic_volume_24h"),
+            wash_score: safe_get_decimal(row, "wash_score"),
+            risk_score: safe_get_optional_i32(row, "risk_score"),
+            risk_flags: row.try_get("risk_flags").unwrap_or_default(),
+            pair_count: safe_get_i32(row, "pair_count"),
+            fdv: safe_get_optional_decimal(row, "fdv"),
+            market_cap: safe_get_optional_decimal(row, "market_cap"),
+            liquidity: safe_get_decimal(row, "liquidity"),
+            last_updated: safe_get_datetime(row, "last_updated"),
         }
-
-        Ok(tokens)
     }
 }
@@ -0,0 +1,302 @@
+use crate::database::utils::*;
+use crate::types::Trade;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Row};
+
+/// 一笔 swap 按资金流向拆成的一跳：token_in 进入交易对，token_out 转给 to_address。
+/// 数量已按精度归一化，地址小写
+#[derive(Debug, Clone)]
+pub struct TradeHop {
+    pub transaction_hash: String,
+    pub log_index: i32,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    pub tx_origin: String,
+    pub event_sender: Option<String>,
+    pub pair_address: String,
+    pub to_address: String,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Decimal,
+}
+
+/// 待写入的逻辑成交，由首尾相接的若干跳组成
+#[derive(Debug, Clone)]
+pub struct TradeRow {
+    pub transaction_hash: String,
+    pub log_indexes: Vec<i32>,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    pub tx_origin: String,
+    pub event_sender: Option<String>,
+    pub recipient: String,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Decimal,
+    pub path: Vec<String>,
+    pub pairs: Vec<String>,
+}
+
+/// 成交流查询条件
+#[derive(Debug, Clone, Default)]
+pub struct TradeFilter {
+    pub chain_id: Option<i32>,
+    /// 交易发起人或最终接收方（小写）
+    pub wallet: Option<String>,
+    /// 输入或输出代币（小写）
+    pub token: Option<String>,
+    pub min_hops: Option<i32>,
+}
+
+pub struct TradeOperations;
+
+impl TradeOperations {
+    /// 链上已写入 swap 的下一个区块（大于 `after_block`），没有更新的 swap 时为 None
+    pub async fn get_next_swap_block(
+        pool: &PgPool,
+        chain_id: i32,
+        after_block: u64,
+    ) -> Result<Option<u64>> {
+        let block = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT MIN(block_number) FROM swap_events WHERE chain_id = $1 AND block_number > $2",
+        )
+        .bind(chain_id)
+        .bind(after_block as i64)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(block.map(|b| b as u64))
+    }
+
+    /// 区块区间 (from_block, to_block] 内方向已知的 swap，按交易和日志顺序排列
+    pub async fn get_trade_hops(
+        pool: &PgPool,
+        chain_id: i32,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<TradeHop>> {
+        // side: buy = 用 token0 换 token1，sell = 用 token1 换 token0
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                se.transaction_hash,
+                se.log_index,
+                se.block_number,
+                se.timestamp,
+                se.sender as tx_origin,
+                se.event_sender,
+                se.pair_address,
+                se.to_address,
+                CASE WHEN se.side = 'buy' THEN lower(tp.token0) ELSE lower(tp.token1) END as token_in,
+                CASE WHEN se.side = 'buy' THEN tp.token0_symbol ELSE tp.token1_symbol END as token_in_symbol,
+                CASE WHEN se.side = 'buy' THEN se.amount0 ELSE se.amount1 END as amount_in,
+                CASE WHEN se.side = 'buy' THEN lower(tp.token1) ELSE lower(tp.token0) END as token_out,
+                CASE WHEN se.side = 'buy' THEN tp.token1_symbol ELSE tp.token0_symbol END as token_out_symbol,
+                CASE WHEN se.side = 'buy' THEN se.amount1 ELSE se.amount0 END as amount_out
+            FROM swap_events se
+            JOIN trading_pairs tp ON tp.chain_id = se.chain_id AND tp.address = se.pair_address
+            WHERE se.chain_id = $1
+            AND se.block_number > $2
+            AND se.block_number <= $3
+            AND se.side IS NOT NULL
+            ORDER BY se.block_number, se.transaction_hash, se.log_index
+            "#,
+        )
+        .bind(chain_id)
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TradeHop {
+                transaction_hash: safe_get_string(row, "transaction_hash"),
+                log_index: safe_get_i32(row, "log_index"),
+                block_number: safe_get_i64(row, "block_number"),
+                timestamp: safe_get_datetime(row, "timestamp"),
+                tx_origin: safe_get_string(row, "tx_origin"),
+                event_sender: safe_get_optional_string(row, "event_sender"),
+                pair_address: safe_get_string(row, "pair_address"),
+                to_address: safe_get_string(row, "to_address"),
+                token_in: safe_get_string(row, "token_in"),
+                token_in_symbol: safe_get_optional_string(row, "token_in_symbol"),
+                amount_in: safe_get_decimal(row, "amount_in"),
+                token_out: safe_get_string(row, "token_out"),
+                token_out_symbol: safe_get_optional_string(row, "token_out_symbol"),
+                amount_out: safe_get_decimal(row, "amount_out"),
+            })
+            .collect())
+    }
+
+    /// 用重建结果替换区块区间 (from_block, to_block] 内的成交，重复处理同一区间是幂等的
+    pub async fn replace_trades(
+        pool: &PgPool,
+        chain_id: i32,
+        from_block: u64,
+        to_block: u64,
+        trades: &[TradeRow],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM trades WHERE chain_id = $1 AND block_number > $2 AND block_number <= $3")
+            .bind(chain_id)
+            .bind(from_block as i64)
+            .bind(to_block as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        for trade in trades {
+            sqlx::query(
+                r#"
+                INSERT INTO trades (
+                    chain_id, transaction_hash, first_log_index, log_indexes, block_number, timestamp,
+                    tx_origin, event_sender, recipient,
+                    token_in, token_in_symbol, amount_in, token_out, token_out_symbol, amount_out,
+                    path, pairs, hop_count
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+                "#,
+            )
+            .bind(chain_id)
+            .bind(&trade.transaction_hash)
+            .bind(trade.log_indexes[0])
+            .bind(&trade.log_indexes)
+            .bind(trade.block_number)
+            .bind(trade.timestamp)
+            .bind(&trade.tx_origin)
+            .bind(&trade.event_sender)
+            .bind(&trade.recipient)
+            .bind(&trade.token_in)
+            .bind(&trade.token_in_symbol)
+            .bind(trade.amount_in)
+            .bind(&trade.token_out)
+            .bind(&trade.token_out_symbol)
+            .bind(trade.amount_out)
+            .bind(&trade.path)
+            .bind(&trade.pairs)
+            .bind(trade.pairs.len() as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// 成交流，按时间倒序。美元成交额取第一跳，第一跳未定价时取后续最早已定价的一跳
+    pub async fn get_trades(
+        pool: &PgPool,
+        filter: &TradeFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Trade>> {
+        let mut conditions = vec!["1=1".to_string()];
+        let mut param_count = 0;
+
+        if filter.chain_id.is_some() {
+            param_count += 1;
+            conditions.push(format!("t.chain_id = ${}", param_count));
+        }
+        if filter.wallet.is_some() {
+            param_count += 1;
+            conditions.push(format!(
+                "(t.tx_origin = ${0} OR t.recipient = ${0})",
+                param_count
+            ));
+        }
+        if filter.token.is_some() {
+            param_count += 1;
+            conditions.push(format!(
+                "(t.token_in = ${0} OR t.token_out = ${0})",
+                param_count
+            ));
+        }
+        if filter.min_hops.is_some() {
+            param_count += 1;
+            conditions.push(format!("t.hop_count >= ${}", param_count));
+        }
+
+        let query = format!(
+            r#"
+            SELECT
+                t.*,
+                kr.address IS NOT NULL as via_router,
+                kr.label as router_label,
+                hops.volume_usd,
+                COALESCE(hops.fees_usd, 0) as fees_usd
+            FROM trades t
+            LEFT JOIN known_routers kr
+                ON kr.chain_id = t.chain_id AND kr.address = t.event_sender
+            LEFT JOIN LATERAL (
+                SELECT
+                    (array_agg(se.volume_usd ORDER BY se.log_index) FILTER (WHERE se.volume_usd IS NOT NULL))[1] as volume_usd,
+                    SUM(se.fee_usd) as fees_usd
+                FROM swap_events se
+                WHERE se.chain_id = t.chain_id
+                AND se.timestamp = t.timestamp
+                AND se.transaction_hash = t.transaction_hash
+                AND se.log_index = ANY(t.log_indexes)
+            ) hops ON true
+            WHERE {}
+            ORDER BY t.timestamp DESC, t.first_log_index DESC
+            LIMIT ${} OFFSET ${}
+            "#,
+            conditions.join(" AND "),
+            param_count + 1,
+            param_count + 2
+        );
+
+        let mut query_builder = sqlx::query(&query);
+        if let Some(chain_id) = filter.chain_id {
+            query_builder = query_builder.bind(chain_id);
+        }
+        if let Some(wallet) = &filter.wallet {
+            query_builder = query_builder.bind(wallet);
+        }
+        if let Some(token) = &filter.token {
+            query_builder = query_builder.bind(token);
+        }
+        if let Some(min_hops) = filter.min_hops {
+            query_builder = query_builder.bind(min_hops);
+        }
+
+        let rows = query_builder.bind(limit).bind(offset).fetch_all(pool).await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| Trade {
+                chain_id: safe_get_i32(row, "chain_id"),
+                transaction_hash: safe_get_string(row, "transaction_hash"),
+                log_indexes: row.try_get("log_indexes").unwrap_or_default(),
+                tx_origin: safe_get_string(row, "tx_origin"),
+                event_sender: safe_get_optional_string(row, "event_sender"),
+                via_router: safe_get_bool(row, "via_router"),
+                router_label: safe_get_optional_string(row, "router_label"),
+                recipient: safe_get_string(row, "recipient"),
+                token_in: safe_get_string(row, "token_in"),
+                token_in_symbol: safe_get_optional_string(row, "token_in_symbol"),
+                amount_in: safe_get_decimal(row, "amount_in"),
+                token_out: safe_get_string(row, "token_out"),
+                token_out_symbol: safe_get_optional_string(row, "token_out_symbol"),
+                amount_out: safe_get_decimal(row, "amount_out"),
+                path: row.try_get("path").unwrap_or_default(),
+                pairs: row.try_get("pairs").unwrap_or_default(),
+                hop_count: safe_get_i32(row, "hop_count"),
+                volume_usd: safe_get_optional_decimal(row, "volume_usd"),
+                fees_usd: safe_get_decimal(row, "fees_usd"),
+                block_number: safe_get_i64(row, "block_number"),
+                timestamp: safe_get_datetime(row, "timestamp"),
+            })
+            .collect())
+    }
+}
@@ -1,12 +1,37 @@
+use crate::database::operations::activity_operations::ActivityOperations;
+use crate::database::operations::wash_operations::WashOperations;
 use crate::database::utils::*;
 use crate::types::{
-    KLineData, LiquidityRecord, PairDetail, PairStats, TimeSeriesData, TradeRecord, TradingPair,
+    LiquidityRecord, PairDetail, PairFeeApr, PairStats, TimeSeriesData, TradeRecord, TradingPair,
 };
 use anyhow::Result;
 use rust_decimal::Decimal;
-use sqlx::{PgPool, Row};
+use sqlx::PgPool;
 use tracing::info;
 
+/// 交易对详情默认展示的手续费 APR 窗口
+pub const DEFAULT_FEE_APR_WINDOWS: [&str; 3] = ["24h", "7d", "30d"];
+
+/// 流动性（USD）低于该值的交易对不计算手续费 APR，避免粉尘池子得到无意义的巨大数值
+pub const FEE_APR_MIN_LIQUIDITY_USD: u64 = 100;
+
+/// 手续费年化收益率（%）：fees / days × 365 / liquidity × 100，流动性低于下限或溢出时返回 None
+pub fn fee_apr(fees_usd: Decimal, days: Decimal, liquidity: Decimal) -> Option<Decimal> {
+    if liquidity < Decimal::from(FEE_APR_MIN_LIQUIDITY_USD) || days <= Decimal::ZERO {
+        return None;
+    }
+    fees_usd
+        .checked_div(days)?
+        .checked_mul(Decimal::from(365))?
+        .checked_div(liquidity)?
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .map(|apr| apr.round_dp(4))
+}
+
+/// 手续费 APR 窗口允许的单位和最长时长（365 天），由 `parse_window` 解析
+pub const FEE_WINDOW_UNITS: &[char] = &['h', 'd'];
+pub const FEE_WINDOW_MAX_SECS: i64 = 365 * 86400;
+
 pub struct TradingOperations;
 
 impl TradingOperations {
@@ -16,8 +41,8 @@ impl TradingOperations {
         sqlx::query(
         r#"
         INSERT INTO trading_pairs 
-        (chain_id, address, token0, token1, token0_symbol, token1_symbol, token0_decimals, token1_decimals, token0_name, token1_name, block_number, transaction_hash)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        (chain_id, address, token0, token1, token0_symbol, token1_symbol, token0_decimals, token1_decimals, token0_name, token1_name, block_number, transaction_hash, quote_side)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, pair_quote_side($1, $3, $4))
         ON CONFLICT (chain_id, address) DO NOTHING
         "#,
     )
@@ -73,6 +98,8 @@ impl TradingOperations {
         pool: &PgPool,
         pair_address: &str,
         chain_id: i32,
+        quote_side: i32,
+        fee_windows: &[(String, i64)],
     ) -> Result<Option<PairDetail>, sqlx::Error> {
         info!("get_pair_stats");
         let query = r#"
@@ -100,73 +127,24 @@ impl TradingOperations {
                 -- 当前价格 (最新交易价格)
                 COALESCE(
                     (SELECT 
-                        CASE 
-                            WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 
-                                se.amount0_in::decimal / NULLIF(se.amount1_out::decimal, 0)
-                            WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 
-                                se.amount0_out::decimal / NULLIF(se.amount1_in::decimal, 0)
-                            ELSE 0
-                        END
+                        CASE WHEN $3 = 0 THEN se.price1 ELSE se.price0 END
                      FROM swap_events se 
                      WHERE se.pair_address = $1 AND se.chain_id = $2
-                     AND (
-                         (se.amount0_in > 0 AND se.amount1_out > 0) OR 
-                         (se.amount1_in > 0 AND se.amount0_out > 0)
-                     )
+                     AND se.price1 IS NOT NULL
                      ORDER BY se.timestamp DESC 
                      LIMIT 1), 0
                 ) as current_price,
-                -- 24小时前价格
-                COALESCE(
-                    (SELECT 
-                        CASE 
-                            WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 
-                                se.amount0_in::decimal / NULLIF(se.amount1_out::decimal, 0)
-                            WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 
-                                se.amount0_out::decimal / NULLIF(se.amount1_in::decimal, 0)
-                            ELSE 0
-                        END
-                     FROM swap_events se 
-                     WHERE se.pair_address = $1 AND se.chain_id = $2
-                     AND se.timestamp <= NOW() - INTERVAL '24 hours'
-                     AND (
-                         (se.amount0_in > 0 AND se.amount1_out > 0) OR 
-                         (se.amount1_in > 0 AND se.amount0_out > 0)
-                     )
-                     ORDER BY se.timestamp DESC 
-                     LIMIT 1), 0
-                ) as price_24h_ago,
-                -- 7天前价格
-                COALESCE(
-                    (SELECT 
-                        CASE 
-                            WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 
-                                se.amount0_in::decimal / NULLIF(se.amount1_out::decimal, 0)
-                            WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 
-                                se.amount0_out::decimal / NULLIF(se.amount1_in::decimal, 0)
-                            ELSE 0
-                        END
-                     FROM swap_events se 
-                     WHERE se.pair_address = $1 AND se.chain_id = $2
-                     AND se.timestamp <= NOW() - INTERVAL '7 days'
-                     AND (
-                         (se.amount0_in > 0 AND se.amount1_out > 0) OR 
-                         (se.amount1_in > 0 AND se.amount0_out > 0)
-                     )
-                     ORDER BY se.timestamp DESC 
-                     LIMIT 1), 0
-                ) as price_7d_ago
+                -- 涨跌幅 (基于 1m K线)
+                COALESCE(pair_price_change($2, $1, INTERVAL '1 hour', $3), 0) as price_change_1h,
+                COALESCE(pair_price_change($2, $1, INTERVAL '24 hours', $3), 0) as price_change_24h,
+                COALESCE(pair_price_change($2, $1, INTERVAL '7 days', $3), 0) as price_change_7d
         ),
         volume_stats AS (
             SELECT 
-                -- 24小时成交量 (以token0计算)
+                -- 24小时成交量 (以计价代币计算)
                 COALESCE(
                     (SELECT SUM(
-                        CASE 
-                            WHEN se.amount0_in > 0 THEN se.amount0_in::decimal
-                            WHEN se.amount0_out > 0 THEN se.amount0_out::decimal
-                            ELSE 0
-                        END
+                        COALESCE(CASE WHEN $3 = 0 THEN se.amount0 ELSE se.amount1 END, 0)
                     ) FROM swap_events se 
                      WHERE se.pair_address = $1 AND se.chain_id = $2
                      AND se.timestamp >= NOW() - INTERVAL '24 hours'), 0
@@ -174,11 +152,7 @@ impl TradingOperations {
                 -- 7天成交量
                 COALESCE(
                     (SELECT SUM(
-                        CASE 
-                            WHEN se.amount0_in > 0 THEN se.amount0_in::decimal
-                            WHEN se.amount0_out > 0 THEN se.amount0_out::decimal
-                            ELSE 0
-                        END
+                        COALESCE(CASE WHEN $3 = 0 THEN se.amount0 ELSE se.amount1 END, 0)
                     ) FROM swap_events se 
                      WHERE se.pair_address = $1 AND se.chain_id = $2
                      AND se.timestamp >= NOW() - INTERVAL '7 days'), 0
@@ -201,15 +175,11 @@ impl TradingOperations {
         ),
         liquidity_stats AS (
             SELECT 
-                -- 流动性估算 (基于最近的mint事件)
+                -- 美元 TVL (基于最新储备量)
                 COALESCE(
-                    (SELECT SUM(me.amount0::decimal + me.amount1::decimal) 
-                     FROM mint_events me 
-                     WHERE me.pair_address = $1 AND me.chain_id = $2), 0
-                ) - COALESCE(
-                    (SELECT SUM(be.amount0::decimal + be.amount1::decimal) 
-                     FROM burn_events be 
-                     WHERE be.pair_address = $1 AND be.chain_id = $2), 0
+                    (SELECT pr.reserve_usd
+                     FROM pair_reserves pr
+                     WHERE pr.pair_address = $1 AND pr.chain_id = $2), 0
                 ) as liquidity
         )
         SELECT 
@@ -223,20 +193,14 @@ impl TradingOperations {
             pb.token1_name,
             pb.token0_decimals,
             pb.token1_decimals,
+            CASE WHEN $3 = 0 THEN pb.token0 ELSE pb.token1 END as quote_token,
             ps.current_price,
             vs.volume_24h,
             vs.volume_7d,
             ls.liquidity,
-            CASE 
-                WHEN ps.price_24h_ago > 0 THEN 
-                    ((ps.current_price - ps.price_24h_ago) / ps.price_24h_ago) * 100
-                ELSE 0
-            END as price_change_24h,
-            CASE 
-                WHEN ps.price_7d_ago > 0 THEN 
-                    ((ps.current_price - ps.price_7d_ago) / ps.price_7d_ago) * 100
-                ELSE 0
-            END as price_change_7d,
+            ps.price_change_1h,
+            ps.price_change_24h,
+            ps.price_change_7d,
             ts.tx_count_24h,
             ts.tx_count_7d,
             pb.created_at
@@ -250,6 +214,7 @@ impl TradingOperations {
         let row = sqlx::query(query)
             .bind(pair_address)
             .bind(chain_id)
+            .bind(quote_side)
             .fetch_optional(pool)
             .await?;
 
@@ -265,21 +230,86 @@ impl TradingOperations {
                 token1_name: safe_get_optional_string(&row, "token1_name"),
                 token0_decimals: safe_get_optional_i32(&row, "token0_decimals"),
                 token1_decimals: safe_get_optional_i32(&row, "token1_decimals"),
+                quote_token: safe_get_string(&row, "quote_token"),
                 current_price: safe_get_decimal(&row, "current_price"),
                 volume_24h: safe_get_decimal(&row, "volume_24h"),
                 volume_7d: safe_get_decimal(&row, "volume_7d"),
                 liquidity: safe_get_decimal(&row, "liquidity"),
+                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
                 price_change_24h: safe_get_decimal(&row, "price_change_24h"),
                 price_change_7d: safe_get_decimal(&row, "price_change_7d"),
                 tx_count_24h: safe_get_i64(&row, "tx_count_24h"),
                 tx_count_7d: safe_get_i64(&row, "tx_count_7d"),
                 created_at: safe_get_datetime(&row, "created_at"),
+                fee_apr: Self::get_pair_fee_apr(pool, pair_address, chain_id, fee_windows).await?,
+                wash: WashOperations::get_pair_wash_stats(pool, chain_id, pair_address).await?,
             }))
         } else {
             Ok(None)
         }
     }
 
+    /// 各窗口的手续费收入与年化收益：窗口手续费按实际天数日均后年化，除以当前美元 TVL
+    pub async fn get_pair_fee_apr(
+        pool: &PgPool,
+        pair_address: &str,
+        chain_id: i32,
+        windows: &[(String, i64)],
+    ) -> Result<Vec<PairFeeApr>, sqlx::Error> {
+        if windows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let labels: Vec<String> = windows.iter().map(|(label, _)| label.clone()).collect();
+        let secs: Vec<i64> = windows.iter().map(|(_, secs)| *secs).collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                w.label,
+                COALESCE(fees.fees_usd, 0) as fees_usd,
+                COALESCE(fees.volume_usd, 0) as volume_usd,
+                -- 上线时间不足窗口时按上线时长计，至少 1 小时，避免新池子被放大
+                round(GREATEST(LEAST(w.secs, EXTRACT(EPOCH FROM NOW() - tp.created_at)), 3600)::numeric / 86400, 4) as days,
+                COALESCE(pr.reserve_usd, 0) as liquidity
+            FROM UNNEST($3::TEXT[], $4::BIGINT[]) WITH ORDINALITY AS w(label, secs, ord)
+            JOIN trading_pairs tp ON tp.address = $1 AND tp.chain_id = $2
+            LEFT JOIN pair_reserves pr ON pr.pair_address = tp.address AND pr.chain_id = tp.chain_id
+            CROSS JOIN LATERAL (
+                SELECT SUM(se.fee_usd) as fees_usd, SUM(se.volume_usd) as volume_usd
+                FROM swap_events se
+                WHERE se.pair_address = tp.address AND se.chain_id = tp.chain_id
+                AND se.timestamp >= NOW() - make_interval(secs => w.secs)
+            ) fees
+            ORDER BY w.ord
+            "#,
+        )
+        .bind(pair_address)
+        .bind(chain_id)
+        .bind(&labels)
+        .bind(&secs)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let fees_usd = safe_get_decimal(row, "fees_usd");
+                let days = safe_get_decimal(row, "days");
+                let liquidity = safe_get_decimal(row, "liquidity");
+                let apr = fee_apr(fees_usd, days, liquidity);
+
+                PairFeeApr {
+                    window: safe_get_string(row, "label"),
+                    fees_usd,
+                    volume_usd: safe_get_decimal(row, "volume_usd"),
+                    days,
+                    apr,
+                }
+            })
+            .collect())
+    }
+
     // 交易记录查询 - 包含代币精度信息
     pub async fn get_pair_trades(
         pool: &PgPool,
@@ -287,6 +317,7 @@ impl TradingOperations {
         chain_id: i32,
         limit: i32,
         offset: i32,
+        quote_side: i32,
     ) -> Result<Vec<TradeRecord>> {
         let query = r#"
         SELECT 
@@ -299,25 +330,32 @@ impl TradingOperations {
             tp.token1_decimals,
             se.transaction_hash,
             se.sender,
+            se.event_sender,
             se.to_address,
+            kr.address IS NOT NULL as via_router,
+            kr.label as router_label,
             se.amount0_in,
             se.amount1_in,
             se.amount0_out,
             se.amount1_out,
-            CASE 
-                WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN se.amount0_in / se.amount1_out
-                WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN se.amount0_out / se.amount1_in
-                ELSE 0
-            END as price,
-            CASE 
-                WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 'buy'  -- 用token0买token1
-                WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 'sell' -- 用token1买token0
-                ELSE 'unknown'
+            COALESCE(CASE WHEN $5 = 0 THEN se.price1 ELSE se.price0 END, 0) as price,
+            -- side 记录的是 token1 的买卖方向，以 token1 计价时基础代币为 token0，方向取反
+            CASE
+                WHEN se.side IS NULL THEN 'unknown'
+                WHEN $5 = 0 THEN se.side
+                WHEN se.side = 'buy' THEN 'sell'
+                ELSE 'buy'
             END as trade_type,
+            se.volume_usd,
+            ss.role as mev_role,
+            se.transfer_tax_rate,
             se.block_number,
             se.timestamp
         FROM swap_events se
         LEFT JOIN trading_pairs tp ON tp.address = se.pair_address AND tp.chain_id = se.chain_id
+        LEFT JOIN known_routers kr ON kr.chain_id = se.chain_id AND kr.address = se.event_sender
+        LEFT JOIN sandwich_swaps ss
+            ON ss.chain_id = se.chain_id AND ss.transaction_hash = se.transaction_hash AND ss.log_index = se.log_index
         WHERE se.pair_address = $1 AND se.chain_id = $2
         ORDER BY se.timestamp DESC
         LIMIT $3 OFFSET $4
@@ -328,6 +366,7 @@ impl TradingOperations {
             .bind(chain_id)
             .bind(limit)
             .bind(offset)
+            .bind(quote_side)
             .fetch_all(pool)
             .await?;
 
@@ -343,14 +382,19 @@ impl TradingOperations {
                 token1_decimals: safe_get_optional_i32(&row, "token1_decimals"),
                 transaction_hash: safe_get_string(&row, "transaction_hash"),
                 sender: safe_get_string(&row, "sender"),
+                event_sender: safe_get_optional_string(&row, "event_sender"),
                 to_address: safe_get_string(&row, "to_address"),
+                via_router: safe_get_bool(&row, "via_router"),
+                router_label: safe_get_optional_string(&row, "router_label"),
                 amount0_in: safe_get_decimal(&row, "amount0_in"),
                 amount1_in: safe_get_decimal(&row, "amount1_in"),
                 amount0_out: safe_get_decimal(&row, "amount0_out"),
                 amount1_out: safe_get_decimal(&row, "amount1_out"),
                 price: safe_get_decimal(&row, "price"),
                 trade_type: safe_get_string(&row, "trade_type"),
-                volume_usd: None,
+                volume_usd: safe_get_optional_decimal(&row, "volume_usd"),
+                mev_role: safe_get_optional_string(&row, "mev_role"),
+                transfer_tax_rate: safe_get_optional_decimal(&row, "transfer_tax_rate"),
                 block_number: safe_get_i64(&row, "block_number"),
                 timestamp: safe_get_datetime(&row, "timestamp"),
             });
@@ -364,6 +408,7 @@ impl TradingOperations {
         pool: &PgPool,
         pair_address: &str,
         chain_id: i32,
+        quote_side: i32,
     ) -> Result<Option<PairStats>> {
         let query = r#"
         WITH pair_info AS (
@@ -372,49 +417,25 @@ impl TradingOperations {
                 tp.chain_id,
                 tp.token0_symbol,
                 tp.token1_symbol,
+                CASE WHEN $3 = 0 THEN tp.token0 ELSE tp.token1 END as quote_token,
                 -- 当前价格
                 COALESCE(
                     (SELECT 
-                        CASE 
-                            WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                            WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                            ELSE 0
-                        END
+                        CASE WHEN $3 = 0 THEN price1 ELSE price0 END
                      FROM swap_events 
                      WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                     AND (
-                         (amount0_in > 0 AND amount1_out > 0) OR 
-                         (amount1_in > 0 AND amount0_out > 0)
-                     )
+                     AND price1 IS NOT NULL
                      ORDER BY timestamp DESC 
                      LIMIT 1), 0
                 ) as current_price,
-                -- 24小时前价格
-                COALESCE(
-                    (SELECT 
-                        CASE 
-                            WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                            WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                            ELSE 0
-                        END
-                     FROM swap_events 
-                     WHERE pair_address = tp.address AND chain_id = tp.chain_id
-                     AND timestamp <= NOW() - INTERVAL '24 hours'
-                     AND (
-                         (amount0_in > 0 AND amount1_out > 0) OR 
-                         (amount1_in > 0 AND amount0_out > 0)
-                     )
-                     ORDER BY timestamp DESC 
-                     LIMIT 1), 0
-                ) as price_24h_ago,
+                -- 涨跌幅 (基于 1m K线)
+                COALESCE(pair_price_change(tp.chain_id, tp.address, INTERVAL '1 hour', $3), 0) as price_change_1h,
+                COALESCE(pair_price_change(tp.chain_id, tp.address, INTERVAL '24 hours', $3), 0) as price_change_24h,
+                COALESCE(pair_price_change(tp.chain_id, tp.address, INTERVAL '7 days', $3), 0) as price_change_7d,
                 -- 24小时成交量
                 COALESCE(
                     (SELECT SUM(
-                        CASE 
-                            WHEN amount0_in > 0 THEN amount0_in
-                            WHEN amount1_in > 0 THEN amount1_in  
-                            ELSE 0
-                        END
+                        COALESCE(CASE WHEN $3 = 0 THEN amount0 ELSE amount1 END, 0)
                     ) FROM swap_events 
                      WHERE pair_address = tp.address AND chain_id = tp.chain_id
                      AND timestamp >= NOW() - INTERVAL '24 hours'), 0
@@ -424,8 +445,19 @@ impl TradingOperations {
                     (SELECT COUNT(*) FROM swap_events 
                      WHERE pair_address = tp.address AND chain_id = tp.chain_id
                      AND timestamp >= NOW() - INTERVAL '24 hours'), 0
-                ) as tx_count_24h
+                ) as tx_count_24h,
+                -- LP 手续费收入 (USD)
+                (pair_fee_rate(tp.chain_id) * 10000)::INTEGER as fee_bps,
+                fees.fees_24h,
+                fees.fees_7d
             FROM trading_pairs tp
+            CROSS JOIN LATERAL (
+                SELECT COALESCE(SUM(fee_usd) FILTER (WHERE timestamp >= NOW() - INTERVAL '24 hours'), 0) as fees_24h,
+                       COALESCE(SUM(fee_usd), 0) as fees_7d
+                FROM swap_events
+                WHERE pair_address = tp.address AND chain_id = tp.chain_id
+                AND timestamp >= NOW() - INTERVAL '7 days'
+            ) fees
             WHERE tp.address = $1 AND tp.chain_id = $2
         )
         SELECT 
@@ -433,906 +465,79 @@ impl TradingOperations {
             chain_id,
             COALESCE(token0_symbol, 'UNKNOWN') as token0_symbol,
             COALESCE(token1_symbol, 'UNKNOWN') as token1_symbol,
+            quote_token,
             current_price as price,
             volume_24h,
-            0 as liquidity,
-            CASE 
-                WHEN price_24h_ago > 0 THEN 
-                    ((current_price - price_24h_ago) / price_24h_ago) * 100
-                ELSE 0
-            END as price_change_24h,
-            tx_count_24h
+            COALESCE(
+                (SELECT reserve_usd FROM pair_reserves
+                 WHERE pair_address = pair_info.pair_address AND chain_id = pair_info.chain_id), 0
+            ) as liquidity,
+            price_change_1h,
+            price_change_24h,
+            price_change_7d,
+            tx_count_24h,
+            fee_bps,
+            fees_24h,
+            fees_7d
         FROM pair_info
     "#;
 
         let row = sqlx::query(query)
             .bind(pair_address)
             .bind(chain_id)
+            .bind(quote_side)
             .fetch_optional(pool)
             .await?;
 
         if let Some(row) = row {
+            let liquidity = safe_get_decimal(&row, "liquidity");
+            let fees_24h = safe_get_decimal(&row, "fees_24h");
+            let fees_7d = safe_get_decimal(&row, "fees_7d");
+            let fee_apr = fee_apr(fees_7d, Decimal::from(7), liquidity);
+            let activity =
+                ActivityOperations::get_pair_activity(pool, chain_id, pair_address, quote_side).await?;
+
             Ok(Some(PairStats {
                 pair_address: safe_get_string(&row, "pair_address"),
                 chain_id: safe_get_i32(&row, "chain_id"),
                 token0_symbol: safe_get_string(&row, "token0_symbol"),
                 token1_symbol: safe_get_string(&row, "token1_symbol"),
+                quote_token: safe_get_string(&row, "quote_token"),
                 price: safe_get_decimal(&row, "price"),
                 volume_24h: safe_get_decimal(&row, "volume_24h"),
-                liquidity: safe_get_decimal(&row, "liquidity"),
+                liquidity,
+                price_change_1h: safe_get_decimal(&row, "price_change_1h"),
                 price_change_24h: safe_get_decimal(&row, "price_change_24h"),
+                price_change_7d: safe_get_decimal(&row, "price_change_7d"),
                 tx_count_24h: safe_get_i64(&row, "tx_count_24h"),
+                activity,
+                fee_bps: safe_get_i32(&row, "fee_bps"),
+                fees_24h,
+                fees_7d,
+                fee_apr,
             }))
         } else {
             Ok(None)
         }
     }
 
-    // K线数据查询 - 正确处理价格计算逻辑
-    pub async fn get_kline_data(
-        pool: &PgPool,
-        pair_address: &str,
-        chain_id: i32,
-        interval: &str,
-        limit: i32,
-    ) -> Result<Vec<KLineData>> {
-        // 根据不同的时间区间使用不同的查询
-        let (query, _interval_param) = match interval {
-            "1m" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('minute', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '1 day'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "1 day",
-            ),
-            "5m" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 5) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 5) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 5) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '3 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "3 days",
-            ),
-            "15m" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 15) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 15) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 15) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '7 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "7 days",
-            ),
-            "30m" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 30) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 30) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('minute', timestamp) - INTERVAL '1 minute' * (EXTRACT(minute FROM timestamp)::int % 30) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '14 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "14 days",
-            ),
-            "1h" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('hour', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '30 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "30 days",
-            ),
-            "4h" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('hour', timestamp) - INTERVAL '1 hour' * (EXTRACT(hour FROM timestamp)::int % 4) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) - INTERVAL '1 hour' * (EXTRACT(hour FROM timestamp)::int % 4) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) - INTERVAL '1 hour' * (EXTRACT(hour FROM timestamp)::int % 4) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '90 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "90 days",
-            ),
-            "1d" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('day', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('day', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('day', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '1 year'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "1 year",
-            ),
-            "1w" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('week', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('week', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('week', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '2 years'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "2 years",
-            ),
-            "1M" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('month', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('month', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('month', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '5 years'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "5 years",
-            ),
-            "1y" => (
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('year', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('year', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('year', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            WHERE time_bucket >= NOW() - INTERVAL '5 years'
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "all time",
-            ),
-            _ => (
-                // 默认使用1小时
-                r#"
-            WITH time_series AS (
-                SELECT 
-                    date_trunc('hour', timestamp) as time_bucket,
-                    CASE 
-                        WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-                        WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-                        ELSE 0
-                    END as price,
-                    CASE 
-                        WHEN amount0_in > 0 THEN amount0_in
-                        WHEN amount1_in > 0 THEN amount1_in  
-                        ELSE 0
-                    END as volume,
-                    timestamp,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) ORDER BY timestamp ASC) as rn_first,
-                    ROW_NUMBER() OVER (PARTITION BY date_trunc('hour', timestamp) ORDER BY timestamp DESC) as rn_last
-                FROM swap_events 
-                WHERE pair_address = $1 AND chain_id = $2
-                AND timestamp >= NOW() - INTERVAL '30 days'
-                AND (
-                    (amount0_in > 0 AND amount1_out > 0) OR 
-                    (amount1_in > 0 AND amount0_out > 0)
-                )
-            ),
-            kline_raw AS (
-                SELECT 
-                    time_bucket,
-                    MAX(CASE WHEN rn_first = 1 THEN price END) as first_price,
-                    MAX(price) as high_price,
-                    MIN(price) as low_price,
-                    MAX(CASE WHEN rn_last = 1 THEN price END) as close_price,
-                    SUM(volume) as total_volume,
-                    COUNT(*) as trade_count
-                FROM time_series
-                WHERE price > 0
-                GROUP BY time_bucket
-            ),
-            kline_with_continuity AS (
-                SELECT 
-                    time_bucket,
-                    -- 开盘价：第一个K线使用实际第一笔交易价格，后续使用前一个K线的收盘价
-                    CASE 
-                        WHEN LAG(close_price) OVER (ORDER BY time_bucket) IS NULL 
-                        THEN first_price 
-                        ELSE LAG(close_price) OVER (ORDER BY time_bucket)
-                    END as open_price,
-                    high_price,
-                    low_price,
-                    close_price,
-                    total_volume,
-                    trade_count
-                FROM kline_raw
-                WHERE first_price IS NOT NULL AND close_price IS NOT NULL
-            )
-            SELECT 
-                time_bucket as timestamp,
-                COALESCE(open_price, 0) as open,
-                COALESCE(high_price, 0) as high,
-                COALESCE(low_price, 0) as low,
-                COALESCE(close_price, 0) as close,
-                COALESCE(total_volume, 0) as volume,
-                COALESCE(trade_count, 0) as trade_count
-            FROM kline_with_continuity
-            ORDER BY time_bucket DESC
-            LIMIT $3
-            "#,
-                "30 days",
-            ),
-        };
-
-        let rows = sqlx::query(query)
-            .bind(pair_address)
-            .bind(chain_id)
-            .bind(limit)
-            .fetch_all(pool)
-            .await?;
-
-        let mut klines = Vec::new();
-        for row in rows {
-            klines.push(KLineData {
-                timestamp: safe_get_datetime(&row, "timestamp"),
-                open: safe_get_decimal(&row, "open"),
-                high: safe_get_decimal(&row, "high"),
-                low: safe_get_decimal(&row, "low"),
-                close: safe_get_decimal(&row, "close"),
-                volume: safe_get_decimal(&row, "volume"),
-                trade_count: safe_get_i64(&row, "trade_count"),
-            });
-        }
-
-        Ok(klines)
-    }
-
     pub async fn get_timeseries_data(
         pool: &PgPool,
         pair_address: &str,
         chain_id: i32,
         hours: i32,
+        quote_side: i32,
     ) -> Result<Vec<TimeSeriesData>> {
-        // 首先获取交易对的代币精度信息
-        let decimals_query = r#"
-    SELECT token0_decimals, token1_decimals 
-    FROM trading_pairs 
-    WHERE address = $1 AND chain_id = $2
-    "#;
-
-        let decimals = sqlx::query(decimals_query)
-            .bind(pair_address)
-            .bind(chain_id)
-            .fetch_optional(pool)
-            .await?;
-
-        let (token0_decimals, token1_decimals) = match decimals {
-            Some(row) => (
-                row.try_get::<i32, _>("token0_decimals").unwrap_or(18),
-                row.try_get::<i32, _>("token1_decimals").unwrap_or(18),
-            ),
-            None => (18, 18), // 默认精度为18
-        };
-
+        // 价格与成交量均为写入时按代币精度归一化的数值，以计价代币计
         let query = r#"
     SELECT 
         timestamp,
-        CASE 
-            WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-            WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-            ELSE 0
-        END as price,
-        CASE 
-            WHEN amount0_in > 0 THEN amount0_in
-            WHEN amount1_in > 0 THEN amount1_in  
-            ELSE 0
-        END as raw_volume,
-        CASE 
-            WHEN amount0_in > 0 THEN 0  
-            WHEN amount1_in > 0 THEN 1  
-            ELSE -1
-        END as volume_token_index
+        CASE WHEN $4 = 0 THEN price1 ELSE price0 END as price,
+        COALESCE(CASE WHEN $4 = 0 THEN amount0 ELSE amount1 END, 0) as volume
     FROM swap_events 
     WHERE pair_address = $1 AND chain_id = $2
     AND timestamp >= NOW() - INTERVAL '1 hour' * $3
-    AND (
-        (amount0_in > 0 AND amount1_out > 0) OR 
-        (amount1_in > 0 AND amount0_out > 0)
-    )
-    AND (
-        CASE 
-            WHEN amount0_in > 0 AND amount1_out > 0 THEN amount0_in / amount1_out
-            WHEN amount1_in > 0 AND amount0_out > 0 THEN amount0_out / amount1_in
-            ELSE 0
-        END
-    ) > 0
+    AND price1 > 0
     ORDER BY timestamp ASC
     "#;
 
@@ -1340,27 +545,16 @@ impl TradingOperations {
             .bind(pair_address)
             .bind(chain_id)
             .bind(hours)
+            .bind(quote_side)
             .fetch_all(pool)
             .await?;
 
         let mut timeseries = Vec::new();
         for row in rows {
-            let raw_volume = safe_get_decimal(&row, "raw_volume");
-            let token_index = row.get::<i32, _>("volume_token_index");
-
-            // 根据交易使用的代币去除精度
-            let volume = if token_index == 0 {
-                raw_volume / Decimal::from(10u64.pow(token0_decimals as u32))
-            } else if token_index == 1 {
-                raw_volume / Decimal::from(10u64.pow(token1_decimals as u32))
-            } else {
-                Decimal::ZERO
-            };
-
             timeseries.push(TimeSeriesData {
                 timestamp: safe_get_datetime(&row, "timestamp"),
                 price: safe_get_decimal(&row, "price"),
-                volume: volume.round_dp(0), // 四舍五入到整数
+                volume: safe_get_decimal(&row, "volume").round_dp(0), // 四舍五入到整数
             });
         }
 
@@ -1391,6 +585,7 @@ impl TradingOperations {
                 me.amount0,
                 me.amount1,
                 'mint' as liquidity_type,
+                me.value_usd,
                 me.block_number,
                 me.timestamp
             FROM mint_events me
@@ -1413,6 +608,7 @@ impl TradingOperations {
                 be.amount0,
                 be.amount1,
                 'burn' as liquidity_type,
+                be.value_usd,
                 be.block_number,
                 be.timestamp
             FROM burn_events be
@@ -1448,7 +644,7 @@ impl TradingOperations {
                 amount0: safe_get_decimal(&row, "amount0"),
                 amount1: safe_get_decimal(&row, "amount1"),
                 liquidity_type: safe_get_string(&row, "liquidity_type"),
-                value_usd: None,
+                value_usd: safe_get_optional_decimal(&row, "value_usd"),
                 block_number: safe_get_i64(&row, "block_number"),
                 timestamp: safe_get_datetime(&row, "timestamp"),
             });
@@ -1457,3 +653,25 @@ impl TradingOperations {
         Ok(liquidity_records)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_apr_annualizes_fees_over_liquidity() {
+        // 7 天 70 美元手续费，流动性 36500 美元：10 / 天 × 365 / 36500 = 10%
+        assert_eq!(
+            fee_apr(Decimal::from(70), Decimal::from(7), Decimal::from(36_500)),
+            Some(Decimal::from(10))
+        );
+    }
+
+    #[test]
+    fn fee_apr_skips_dust_pools_and_overflow() {
+        assert_eq!(fee_apr(Decimal::from(70), Decimal::from(7), Decimal::ZERO), None);
+        assert_eq!(fee_apr(Decimal::from(70), Decimal::from(7), Decimal::from(99)), None);
+        assert_eq!(fee_apr(Decimal::from(70), Decimal::ZERO, Decimal::from(1_000)), None);
+        assert_eq!(fee_apr(Decimal::MAX, Decimal::new(1, 6), Decimal::from(100)), None);
+    }
+}
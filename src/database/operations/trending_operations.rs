@@ -0,0 +1,126 @@
+use crate::config::TrendingConfig;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+pub struct TrendingOperations;
+
+impl TrendingOperations {
+    /// 重算单条链的热门代币评分，依赖 pair_wash_stats 和已刷新的 token_stats_mv。
+    ///
+    /// 成交量和交易者只统计自然成交（按交易对刷量评分折算），以最近 1 小时对比此前 24 小时的每小时均值：
+    /// - 成交量加速度 / 交易者增长：ln(1 + x) / ln(11)，10 倍即满分；均值不足 1 时按 1 计
+    /// - 涨幅：1 小时涨幅 50% 即满分，下跌为 0
+    /// - 流动性：log10(流动性 / 下限) / 2，达到下限 100 倍即满分
+    ///
+    /// 最近 1 小时没有自然成交或流动性低于下限的代币不参与排名，返回写入的代币数
+    pub async fn refresh_token_trending(
+        pool: &PgPool,
+        chain_id: i32,
+        config: &TrendingConfig,
+    ) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM token_trending WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            WITH swaps AS (
+                SELECT
+                    se.pair_address,
+                    se.sender,
+                    se.volume_usd * (1 - COALESCE(w.wash_score, 0) / 100) AS organic_usd,
+                    FLOOR(EXTRACT(EPOCH FROM NOW() - se.timestamp) / 3600)::INTEGER AS hours_ago
+                FROM swap_events se
+                LEFT JOIN pair_wash_stats w ON w.chain_id = se.chain_id AND w.pair_address = se.pair_address
+                WHERE se.chain_id = $1
+                AND se.timestamp >= NOW() - INTERVAL '25 hours'
+                AND se.volume_usd IS NOT NULL
+            ),
+            sides AS (
+                SELECT address AS pair_address, lower(token0) AS token_address
+                FROM trading_pairs WHERE chain_id = $1
+                UNION ALL
+                SELECT address, lower(token1)
+                FROM trading_pairs WHERE chain_id = $1
+            ),
+            activity AS (
+                SELECT
+                    s.token_address,
+                    COALESCE(SUM(sw.organic_usd) FILTER (WHERE sw.hours_ago = 0), 0) AS volume_1h,
+                    COALESCE(SUM(sw.organic_usd) FILTER (WHERE sw.hours_ago BETWEEN 1 AND 24), 0) / 24 AS volume_baseline,
+                    COUNT(DISTINCT sw.sender) FILTER (WHERE sw.hours_ago = 0) AS traders_1h,
+                    COUNT(DISTINCT sw.sender || ':' || sw.hours_ago) FILTER (WHERE sw.hours_ago BETWEEN 1 AND 24)::DECIMAL / 24
+                        AS traders_baseline
+                FROM sides s
+                JOIN swaps sw ON sw.pair_address = s.pair_address
+                GROUP BY s.token_address
+            ),
+            metrics AS (
+                SELECT
+                    a.*,
+                    a.volume_1h / GREATEST(a.volume_baseline, 1) AS volume_acceleration,
+                    a.traders_1h / GREATEST(a.traders_baseline, 1) AS trader_growth,
+                    COALESCE(ts.price_change_1h, 0) AS price_change_1h,
+                    ts.liquidity_usd
+                FROM activity a
+                JOIN token_stats_mv ts ON ts.chain_id = $1 AND ts.token_address = a.token_address
+                WHERE a.volume_1h > 0
+                AND ts.liquidity_usd >= $2
+            ),
+            components AS (
+                SELECT
+                    m.*,
+                    LEAST(1, LN(1 + m.volume_acceleration) / LN(11::numeric)) AS volume_component,
+                    LEAST(1, LN(1 + m.trader_growth) / LN(11::numeric)) AS trader_component,
+                    LEAST(1, GREATEST(m.price_change_1h, 0) / 50) AS price_component,
+                    LEAST(1, LOG(GREATEST(m.liquidity_usd, 1) / GREATEST($2, 1)) / 2) AS liquidity_component
+                FROM metrics m
+            )
+            INSERT INTO token_trending (
+                chain_id, token_address, score,
+                volume_1h_usd, volume_baseline_usd, volume_acceleration,
+                traders_1h, traders_baseline, trader_growth,
+                price_change_1h, liquidity_usd,
+                volume_component, trader_component, price_component, liquidity_component
+            )
+            SELECT
+                $1,
+                token_address,
+                COALESCE(trim_scale(round(
+                    100 * ($3 * volume_component + $4 * trader_component + $5 * price_component + $6 * liquidity_component)
+                        / NULLIF($3 + $4 + $5 + $6, 0),
+                    4
+                )), 0),
+                trim_scale(round(volume_1h, 8)),
+                trim_scale(round(volume_baseline, 8)),
+                trim_scale(round(volume_acceleration, 4)),
+                traders_1h,
+                trim_scale(round(traders_baseline, 4)),
+                trim_scale(round(trader_growth, 4)),
+                price_change_1h,
+                liquidity_usd,
+                trim_scale(round(volume_component, 4)),
+                trim_scale(round(trader_component, 4)),
+                trim_scale(round(price_component, 4)),
+                trim_scale(round(liquidity_component, 4))
+            FROM components
+            "#,
+        )
+        .bind(chain_id)
+        .bind(Decimal::from(config.min_liquidity_usd))
+        .bind(config.volume_weight)
+        .bind(config.trader_weight)
+        .bind(config.price_weight)
+        .bind(config.liquidity_weight)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
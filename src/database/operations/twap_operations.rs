@@ -0,0 +1,231 @@
+use crate::database::utils::*;
+use crate::types::{PairTwap, TwapWindow};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// TWAP 接口默认返回的窗口
+pub const DEFAULT_TWAP_WINDOWS: [&str; 4] = ["5m", "30m", "1h", "24h"];
+
+/// TWAP 窗口允许的单位和最长时长（7 天），由 `parse_window` 解析
+pub const TWAP_WINDOW_UNITS: &[char] = &['m', 'h', 'd'];
+pub const TWAP_WINDOW_MAX_SECS: i64 = 7 * 86400;
+
+/// 一次链上读取的原始值，数值均为十进制字符串
+#[derive(Debug, Clone)]
+pub struct TwapSample {
+    pub pair_address: String,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub price0_cumulative_last: String,
+    pub price1_cumulative_last: String,
+    pub reserve0: String,
+    pub reserve1: String,
+    /// getReserves 返回的 blockTimestampLast（uint32）
+    pub block_timestamp_last: i64,
+}
+
+pub struct TwapOperations;
+
+impl TwapOperations {
+    /// 需要采样的交易对：美元 TVL 不低于下限，按 TVL 从高到低
+    pub async fn get_twap_targets(
+        pool: &PgPool,
+        chain_id: i32,
+        min_liquidity_usd: Decimal,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT pair_address
+            FROM pair_reserves
+            WHERE chain_id = $1 AND reserve_usd >= $2
+            ORDER BY reserve_usd DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(chain_id)
+        .bind(min_liquidity_usd)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| safe_get_string(row, "pair_address")).collect())
+    }
+
+    /// 写入一次采样。与 UniswapV2OracleLibrary.currentCumulativePrices 相同，
+    /// 储备量最后更新之后到采样区块的累计部分按当前储备量价格补上，时间差按 uint32 回绕
+    pub async fn insert_sample(pool: &PgPool, chain_id: i32, sample: &TwapSample) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO pair_twap_samples (
+                chain_id, pair_address, block_number, block_timestamp,
+                price0_cumulative, price1_cumulative, reserve0, reserve1
+            )
+            SELECT
+                $1, $2, $3, to_timestamp($4),
+                CASE WHEN v.r0 > 0 AND v.r1 > 0
+                    THEN mod(v.c0 + div(v.r1 * power(2::numeric, 112), v.r0) * v.elapsed, power(2::numeric, 256))
+                    ELSE v.c0 END,
+                CASE WHEN v.r0 > 0 AND v.r1 > 0
+                    THEN mod(v.c1 + div(v.r0 * power(2::numeric, 112), v.r1) * v.elapsed, power(2::numeric, 256))
+                    ELSE v.c1 END,
+                v.r0,
+                v.r1
+            FROM (
+                SELECT $5::NUMERIC AS c0, $6::NUMERIC AS c1, $7::NUMERIC AS r0, $8::NUMERIC AS r1,
+                       mod($4 - $9 + 4294967296, 4294967296) AS elapsed
+            ) v
+            ON CONFLICT (chain_id, pair_address, block_number) DO NOTHING
+            "#,
+        )
+        .bind(chain_id)
+        .bind(&sample.pair_address)
+        .bind(sample.block_number)
+        .bind(sample.block_timestamp)
+        .bind(&sample.price0_cumulative_last)
+        .bind(&sample.price1_cumulative_last)
+        .bind(&sample.reserve0)
+        .bind(&sample.reserve1)
+        .bind(sample.block_timestamp_last)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 删除超过保留时长的采样，返回删除的行数
+    pub async fn prune_samples(pool: &PgPool, chain_id: i32, retention_hours: u32) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM pair_twap_samples WHERE chain_id = $1 AND block_timestamp < NOW() - make_interval(hours => $2)",
+        )
+        .bind(chain_id)
+        .bind(retention_hours as i32)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 各窗口的 TWAP：终点为最新采样，起点为终点前窗口长度处及之前最近的采样，
+    /// 均价 = 累计价格差（按 2^256 回绕）/ 时间差 / 2^112，再按两边精度换算。`windows` 不能为空，交易对不存在时返回 None
+    pub async fn get_pair_twap(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        quote_side: i32,
+        windows: &[(String, i64)],
+    ) -> Result<Option<PairTwap>> {
+        let labels: Vec<String> = windows.iter().map(|(label, _)| label.clone()).collect();
+        let secs: Vec<i64> = windows.iter().map(|(_, secs)| *secs).collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                w.label,
+                CASE WHEN $5 = 0 THEN tp.token0 ELSE tp.token1 END AS quote_token,
+                s.block_number AS start_block,
+                s.block_timestamp AS start_time,
+                l.block_number AS end_block,
+                l.block_timestamp AS end_time,
+                EXTRACT(EPOCH FROM l.block_timestamp - s.block_timestamp)::BIGINT AS seconds,
+                trim_scale(round(
+                    mod(l.price0_cumulative - s.price0_cumulative + power(2::numeric, 256), power(2::numeric, 256))
+                        / EXTRACT(EPOCH FROM l.block_timestamp - s.block_timestamp)::NUMERIC
+                        / power(2::numeric, 112)
+                        * power(10::numeric, COALESCE(tp.token0_decimals, 18) - COALESCE(tp.token1_decimals, 18)),
+                    28
+                )) AS price0,
+                trim_scale(round(
+                    mod(l.price1_cumulative - s.price1_cumulative + power(2::numeric, 256), power(2::numeric, 256))
+                        / EXTRACT(EPOCH FROM l.block_timestamp - s.block_timestamp)::NUMERIC
+                        / power(2::numeric, 112)
+                        * power(10::numeric, COALESCE(tp.token1_decimals, 18) - COALESCE(tp.token0_decimals, 18)),
+                    28
+                )) AS price1,
+                trim_scale(round(
+                    l.reserve1 / NULLIF(l.reserve0, 0)
+                        * power(10::numeric, COALESCE(tp.token0_decimals, 18) - COALESCE(tp.token1_decimals, 18)),
+                    28
+                )) AS spot_price0,
+                trim_scale(round(
+                    l.reserve0 / NULLIF(l.reserve1, 0)
+                        * power(10::numeric, COALESCE(tp.token1_decimals, 18) - COALESCE(tp.token0_decimals, 18)),
+                    28
+                )) AS spot_price1
+            FROM trading_pairs tp
+            CROSS JOIN UNNEST($3::TEXT[], $4::BIGINT[]) WITH ORDINALITY AS w(label, secs, ord)
+            LEFT JOIN LATERAL (
+                SELECT * FROM pair_twap_samples
+                WHERE chain_id = tp.chain_id AND pair_address = tp.address
+                ORDER BY block_timestamp DESC
+                LIMIT 1
+            ) l ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT * FROM pair_twap_samples
+                WHERE chain_id = tp.chain_id AND pair_address = tp.address
+                AND block_timestamp <= l.block_timestamp - make_interval(secs => w.secs)
+                ORDER BY block_timestamp DESC
+                LIMIT 1
+            ) s ON TRUE
+            WHERE tp.chain_id = $1 AND tp.address = $2
+            ORDER BY w.ord
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(&labels)
+        .bind(&secs)
+        .bind(quote_side)
+        .fetch_all(pool)
+        .await?;
+
+        let quote_token = match rows.first() {
+            Some(row) => safe_get_string(row, "quote_token"),
+            None => return Ok(None),
+        };
+
+        let windows = rows
+            .iter()
+            .map(|row| {
+                let price0 = safe_get_optional_decimal(row, "price0");
+                let price1 = safe_get_optional_decimal(row, "price1");
+                let spot0 = safe_get_optional_decimal(row, "spot_price0");
+                let spot1 = safe_get_optional_decimal(row, "spot_price1");
+                // quote_side = 0：token0 为计价代币，基础代币 token1 的价格为 price1
+                let (price, spot_price) = if quote_side == 0 {
+                    (price1, spot1)
+                } else {
+                    (price0, spot0)
+                };
+                let deviation_pct = match (price, spot_price) {
+                    (Some(price), Some(spot)) if !price.is_zero() => {
+                        Some(((spot - price) / price * Decimal::ONE_HUNDRED).round_dp(4))
+                    }
+                    _ => None,
+                };
+
+                TwapWindow {
+                    window: safe_get_string(row, "label"),
+                    seconds: safe_get_optional_i64(row, "seconds"),
+                    start_block: safe_get_optional_i64(row, "start_block"),
+                    end_block: safe_get_optional_i64(row, "end_block"),
+                    start_time: safe_get_optional_datetime(row, "start_time"),
+                    end_time: safe_get_optional_datetime(row, "end_time"),
+                    price0,
+                    price1,
+                    price,
+                    spot_price,
+                    deviation_pct,
+                }
+            })
+            .collect();
+
+        Ok(Some(PairTwap {
+            chain_id,
+            pair_address: pair_address.to_string(),
+            quote_token,
+            windows,
+        }))
+    }
+}
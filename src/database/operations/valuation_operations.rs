@@ -0,0 +1,289 @@
+use crate::database::utils::*;
+use crate::types::TokenUsdPrice;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// 价格图中的一条边：有储备量的交易对（数量已按精度归一化，地址小写）
+#[derive(Debug, Clone)]
+pub struct PairReserveEdge {
+    pub pair_address: String,
+    pub token0: String,
+    pub token1: String,
+    pub reserve0: Decimal,
+    pub reserve1: Decimal,
+}
+
+pub struct ValuationOperations;
+
+impl ValuationOperations {
+    /// 记录 Sync 事件的储备量，只接受比已有记录更新的 (block_number, log_index)。
+    /// 储备量为十进制字符串形式的原始 uint112，可能超出 Decimal 的表示范围，按 NUMERIC 写入
+    pub async fn upsert_pair_reserves(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+        reserve0: &str,
+        reserve1: &str,
+        block_number: i64,
+        log_index: i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO pair_reserves (chain_id, pair_address, reserve0, reserve1, reserve_usd, block_number, log_index)
+            VALUES ($1, $2, $3::NUMERIC, $4::NUMERIC, pair_liquidity_usd($1, $2, $3::NUMERIC, $4::NUMERIC), $5, $6)
+            ON CONFLICT (chain_id, pair_address) DO UPDATE SET
+                reserve0 = EXCLUDED.reserve0,
+                reserve1 = EXCLUDED.reserve1,
+                reserve_usd = EXCLUDED.reserve_usd,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index,
+                updated_at = NOW()
+            WHERE (pair_reserves.block_number, pair_reserves.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#,
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .bind(reserve0)
+        .bind(reserve1)
+        .bind(block_number)
+        .bind(log_index)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 还没有储备量记录的交易对（升级前创建、尚未出现 Sync 事件）
+    pub async fn get_pairs_without_reserves(pool: &PgPool, chain_id: i32) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT tp.address
+            FROM trading_pairs tp
+            WHERE tp.chain_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM pair_reserves pr
+                  WHERE pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+              )
+            "#,
+        )
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| safe_get_string(row, "address")).collect())
+    }
+
+    /// 加载链上所有两侧储备量都大于 0 的交易对
+    pub async fn get_reserve_edges(pool: &PgPool, chain_id: i32) -> Result<Vec<PairReserveEdge>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT tp.address,
+                   lower(tp.token0) AS token0,
+                   lower(tp.token1) AS token1,
+                   trim_scale(round(pr.reserve0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)), 28)) AS reserve0,
+                   trim_scale(round(pr.reserve1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)), 28)) AS reserve1
+            FROM trading_pairs tp
+            JOIN pair_reserves pr ON pr.chain_id = tp.chain_id AND pr.pair_address = tp.address
+            WHERE tp.chain_id = $1 AND pr.reserve0 > 0 AND pr.reserve1 > 0
+            "#,
+        )
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| PairReserveEdge {
+                pair_address: safe_get_string(row, "address"),
+                token0: safe_get_string(row, "token0"),
+                token1: safe_get_string(row, "token1"),
+                reserve0: safe_get_decimal(row, "reserve0"),
+                reserve1: safe_get_decimal(row, "reserve1"),
+            })
+            .collect())
+    }
+
+    /// token_prices 中外部来源的美元价格（地址统一为小写）
+    pub async fn get_price_anchors(pool: &PgPool, chain_id: i32) -> Result<Vec<(String, Decimal)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (lower(token_address)) lower(token_address) AS token_address, price_usd
+            FROM token_prices
+            WHERE chain_id = $1 AND price_usd > 0
+            ORDER BY lower(token_address), timestamp DESC
+            "#,
+        )
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    safe_get_string(row, "token_address"),
+                    safe_get_decimal(row, "price_usd"),
+                )
+            })
+            .collect())
+    }
+
+    /// 用本轮推导结果整体替换链上的代币美元价格
+    pub async fn replace_token_usd_prices(
+        pool: &PgPool,
+        chain_id: i32,
+        prices: &[TokenUsdPrice],
+    ) -> Result<()> {
+        let tokens: Vec<String> = prices.iter().map(|p| p.token_address.clone()).collect();
+        let values: Vec<Decimal> = prices.iter().map(|p| p.price_usd).collect();
+        let sources: Vec<String> = prices.iter().map(|p| p.source.clone()).collect();
+        let via_pairs: Vec<Option<String>> = prices.iter().map(|p| p.via_pair.clone()).collect();
+        let hops: Vec<i32> = prices.iter().map(|p| p.hops).collect();
+        let liquidity: Vec<Option<Decimal>> = prices.iter().map(|p| p.liquidity_usd).collect();
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM token_usd_prices WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO token_usd_prices (chain_id, token_address, price_usd, source, via_pair, hops, liquidity_usd)
+            SELECT $1, t.token_address, t.price_usd, t.source, t.via_pair, t.hops, t.liquidity_usd
+            FROM unnest($2::TEXT[], $3::NUMERIC[], $4::TEXT[], $5::TEXT[], $6::INTEGER[], $7::NUMERIC[])
+                AS t(token_address, price_usd, source, via_pair, hops, liquidity_usd)
+            "#,
+        )
+        .bind(chain_id)
+        .bind(&tokens)
+        .bind(&values)
+        .bind(&sources)
+        .bind(&via_pairs)
+        .bind(&hops)
+        .bind(&liquidity)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// 按最新代币价格刷新链上所有交易对的美元 TVL
+    pub async fn refresh_reserve_usd(pool: &PgPool, chain_id: i32) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE pair_reserves
+            SET reserve_usd = pair_liquidity_usd(chain_id, pair_address, reserve0, reserve1)
+            WHERE chain_id = $1
+            "#,
+        )
+        .bind(chain_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 补算 `since` 之后写入时还没有价格的 swap 美元成交量和美元手续费，补上的部分同步累加到排行榜聚合和成交活跃度聚合
+    pub async fn backfill_swap_volume_usd(
+        pool: &PgPool,
+        chain_id: i32,
+        since: DateTime<Utc>,
+    ) -> Result<u64> {
+        let updated: i64 = sqlx::query_scalar(
+            r#"
+            WITH updated AS (
+                UPDATE swap_events
+                SET volume_usd = swap_volume_usd(chain_id, pair_address, amount0, amount1),
+                    fee_usd = trim_scale(round(swap_volume_usd(chain_id, pair_address, amount0, amount1) * pair_fee_rate(chain_id), 28))
+                WHERE chain_id = $1 AND timestamp >= $2
+                  AND volume_usd IS NULL AND amount0 IS NOT NULL
+                  AND swap_volume_usd(chain_id, pair_address, amount0, amount1) IS NOT NULL
+                RETURNING chain_id, pair_address, sender, timestamp, side, volume_usd, fee_usd
+            ),
+            rolled AS (
+                INSERT INTO wallet_pair_daily_stats (chain_id, pair_address, wallet_address, day, volume_usd, fees_paid_usd)
+                SELECT chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date,
+                       SUM(volume_usd), COALESCE(SUM(fee_usd), 0)
+                FROM updated
+                GROUP BY chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date
+                ON CONFLICT (chain_id, pair_address, wallet_address, day) DO UPDATE SET
+                    volume_usd = wallet_pair_daily_stats.volume_usd + EXCLUDED.volume_usd,
+                    fees_paid_usd = wallet_pair_daily_stats.fees_paid_usd + EXCLUDED.fees_paid_usd,
+                    updated_at = NOW()
+            ),
+            activity AS (
+                INSERT INTO wallet_pair_activity_5m (chain_id, pair_address, wallet_address, bucket, buy_volume_usd, sell_volume_usd)
+                SELECT chain_id, pair_address, sender, candle_bucket('5m', timestamp),
+                       COALESCE(SUM(volume_usd) FILTER (WHERE side = 'buy'), 0),
+                       COALESCE(SUM(volume_usd) FILTER (WHERE side = 'sell'), 0)
+                FROM updated
+                WHERE timestamp >= NOW() - INTERVAL '24 hours' AND side IS NOT NULL
+                GROUP BY chain_id, pair_address, sender, candle_bucket('5m', timestamp)
+                ON CONFLICT (chain_id, pair_address, bucket, wallet_address) DO UPDATE SET
+                    buy_volume_usd = wallet_pair_activity_5m.buy_volume_usd + EXCLUDED.buy_volume_usd,
+                    sell_volume_usd = wallet_pair_activity_5m.sell_volume_usd + EXCLUDED.sell_volume_usd,
+                    updated_at = NOW()
+            )
+            SELECT COUNT(*) FROM updated
+            "#,
+        )
+        .bind(chain_id)
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(updated as u64)
+    }
+
+    /// 补算 `since` 之后写入时还没有价格的 mint / burn 美元价值，补上的部分同步累加到排行榜聚合
+    pub async fn backfill_liquidity_value_usd(
+        pool: &PgPool,
+        chain_id: i32,
+        since: DateTime<Utc>,
+    ) -> Result<u64> {
+        let mut updated = 0;
+
+        for (table, column) in [
+            ("mint_events", "liquidity_added_usd"),
+            ("burn_events", "liquidity_removed_usd"),
+        ] {
+            let count: i64 = sqlx::query_scalar(&format!(
+                r#"
+                WITH updated AS (
+                    UPDATE {table}
+                    SET value_usd = pair_liquidity_usd(chain_id, pair_address, amount0, amount1)
+                    WHERE chain_id = $1 AND timestamp >= $2
+                      AND value_usd IS NULL
+                      AND pair_liquidity_usd(chain_id, pair_address, amount0, amount1) IS NOT NULL
+                    RETURNING chain_id, pair_address, sender, timestamp, value_usd
+                ),
+                rolled AS (
+                    INSERT INTO wallet_pair_daily_stats (chain_id, pair_address, wallet_address, day, {column})
+                    SELECT chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date, SUM(value_usd)
+                    FROM updated
+                    GROUP BY chain_id, pair_address, sender, (timestamp AT TIME ZONE 'UTC')::date
+                    ON CONFLICT (chain_id, pair_address, wallet_address, day) DO UPDATE SET
+                        {column} = wallet_pair_daily_stats.{column} + EXCLUDED.{column},
+                        updated_at = NOW()
+                )
+                SELECT COUNT(*) FROM updated
+                "#
+            ))
+            .bind(chain_id)
+            .bind(since)
+            .fetch_one(pool)
+            .await?;
+
+            updated += count as u64;
+        }
+
+        Ok(updated)
+    }
+}
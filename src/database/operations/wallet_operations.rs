@@ -3,11 +3,89 @@ use crate::types::WalletTransaction;
 use crate::types::*;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
-use rust_decimal::prelude::FromPrimitive;
 use serde_json::{Number as JsonNumber, Value as JsonValue};
 use sqlx::{PgPool, Row};
+use rust_decimal::Decimal;
 use std::collections::HashMap;
+
+/// 钱包归属方式：事件记录交易发起人 (tx.from) 和代币接收方 (to) 两个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletAttribution {
+    /// 按交易发起人归属，经路由 / 聚合器转发的交易仍记在发起的钱包上
+    Origin,
+    /// 按代币接收方归属，适合智能合约钱包或代他人发起的交易；
+    /// 接收方为路由合约时（如换出原生币）不会记到任何钱包
+    Recipient,
+}
+
+impl WalletAttribution {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "origin" => Some(Self::Origin),
+            "recipient" => Some(Self::Recipient),
+            _ => None,
+        }
+    }
+
+    /// swap / burn 表上匹配钱包（$1）的条件
+    fn condition(&self, alias: &str) -> String {
+        match self {
+            Self::Origin => format!("{}.sender = $1", alias),
+            Self::Recipient => format!("{}.to_address = $1", alias),
+        }
+    }
+}
+
+/// 钱包的一笔 swap，按钱包视角拆成卖出 (token_in) 和买入 (token_out) 两侧，数量已按精度归一化，地址小写
+#[derive(Debug, Clone)]
+pub struct WalletSwapLeg {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub log_index: i32,
+    pub timestamp: DateTime<Utc>,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub token_in_name: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub token_out_symbol: Option<String>,
+    pub token_out_name: Option<String>,
+    pub amount_out: Decimal,
+    /// 成交美元价值，代币尚未定价时为 None
+    pub volume_usd: Option<Decimal>,
+    /// 支付给 LP 的手续费（USD）
+    pub fee_usd: Option<Decimal>,
+}
+
+/// 钱包在单个交易对的 mint / burn 汇总及池子当前状态，数量已按精度归一化。
+/// 流动性以 sqrt(amount0 * amount1) 计量，与 V2 LP 份额成正比
+#[derive(Debug, Clone)]
+pub struct WalletLiquidityAggregate {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub token0: String,
+    pub token1: String,
+    pub token0_symbol: Option<String>,
+    pub token1_symbol: Option<String>,
+    pub first_deposit_at: DateTime<Utc>,
+    pub deposited0: Decimal,
+    pub deposited1: Decimal,
+    pub withdrawn0: Decimal,
+    pub withdrawn1: Decimal,
+    pub liquidity_added: Decimal,
+    pub liquidity_removed: Decimal,
+    /// sqrt(reserve0 * reserve1)，池子没有储备记录时为 None
+    pub pool_liquidity: Option<Decimal>,
+    /// sqrt(reserve1 / reserve0)
+    pub sqrt_price: Option<Decimal>,
+    /// 首次存入以来池子的手续费收入（USD）
+    pub pool_fees_usd: Decimal,
+    pub token0_price_usd: Option<Decimal>,
+    pub token1_price_usd: Option<Decimal>,
+}
+
 pub struct WalletOperations;
 
 impl WalletOperations {
@@ -18,8 +96,9 @@ impl WalletOperations {
         limit: i32,
         offset: i32,
         transaction_type: Option<&str>,
+        attribution: WalletAttribution,
     ) -> Result<Vec<WalletTransaction>, sqlx::Error> {
-        let mut conditions = vec!["(se.sender = $1 OR se.to_address = $1)".to_string()];
+        let mut conditions = vec![attribution.condition("se")];
         let mut param_count = 1;
 
         if let Some(chain_id) = chain_id {
@@ -54,20 +133,32 @@ impl WalletOperations {
         'swap' as transaction_type,
         (se.amount0_in + se.amount0_out)::numeric as amount0,
         (se.amount1_in + se.amount1_out)::numeric as amount1,
-        CASE 
-            WHEN se.amount0_in > 0 AND se.amount1_out > 0 THEN 
-                ((se.amount0_in::numeric / power(10, COALESCE(tp.token0_decimals, 18))) / 
-                 (se.amount1_out::numeric / power(10, COALESCE(tp.token1_decimals, 18))))
-            WHEN se.amount1_in > 0 AND se.amount0_out > 0 THEN 
-                ((se.amount0_out::numeric / power(10, COALESCE(tp.token0_decimals, 18))) / 
-                 (se.amount1_in::numeric / power(10, COALESCE(tp.token1_decimals, 18))))
-            ELSE 0
-        END as price,
+        COALESCE(se.price1, 0) as price,
+        se.volume_usd,
+        se.sender as tx_origin,
+        se.event_sender,
+        se.to_address,
+        kr.address IS NOT NULL as via_router,
+        kr.label as router_label,
+        t.hop_count as trade_hop_count,
+        array_position(t.log_indexes, se.log_index) as trade_hop_index,
+        t.token_in as trade_token_in,
+        t.token_in_symbol as trade_token_in_symbol,
+        t.amount_in as trade_amount_in,
+        t.token_out as trade_token_out,
+        t.token_out_symbol as trade_token_out_symbol,
+        t.amount_out as trade_amount_out,
         se.block_number,
         se.timestamp
     FROM swap_events se
     LEFT JOIN trading_pairs tp 
         ON tp.address = se.pair_address AND tp.chain_id = se.chain_id
+    LEFT JOIN known_routers kr
+        ON kr.chain_id = se.chain_id AND kr.address = se.event_sender
+    LEFT JOIN trades t
+        ON t.chain_id = se.chain_id
+        AND t.transaction_hash = se.transaction_hash
+        AND se.log_index = ANY(t.log_indexes)
     WHERE {}
     ORDER BY se.timestamp DESC
     LIMIT ${} OFFSET ${}
@@ -95,12 +186,6 @@ impl WalletOperations {
             let token0_decimals = safe_get_optional_i32(&row, "token0_decimals").unwrap_or(18);
             let token1_decimals = safe_get_optional_i32(&row, "token1_decimals").unwrap_or(18);
 
-            let raw_price = safe_get_decimal(&row, "price");
-            let decimals_adjustment =
-                Decimal::from_f64(10f64.powi((token1_decimals - token0_decimals) as i32))
-                    .unwrap_or(Decimal::ONE);
-            let adjusted_price = raw_price * decimals_adjustment;
-
             transactions.push(WalletTransaction {
                 id: safe_get_uuid(&row, "id"),
                 chain_id: safe_get_i32(&row, "chain_id"),
@@ -114,8 +199,24 @@ impl WalletOperations {
                 amount1: safe_get_decimal(&row, "amount1"),
                 token0_decimals: Some(token0_decimals),
                 token1_decimals: Some(token1_decimals),
-                price: Some(adjusted_price),
-                value_usd: None,
+                // price1 写入时已按精度归一化
+                price: Some(safe_get_decimal(&row, "price")),
+                value_usd: safe_get_optional_decimal(&row, "volume_usd"),
+                tx_origin: safe_get_string(&row, "tx_origin"),
+                event_sender: safe_get_optional_string(&row, "event_sender"),
+                to_address: safe_get_optional_string(&row, "to_address"),
+                via_router: safe_get_bool(&row, "via_router"),
+                router_label: safe_get_optional_string(&row, "router_label"),
+                trade: safe_get_optional_i32(&row, "trade_hop_count").map(|hop_count| TradeSummary {
+                    hop_count,
+                    hop_index: safe_get_optional_i32(&row, "trade_hop_index").unwrap_or(1),
+                    token_in: safe_get_string(&row, "trade_token_in"),
+                    token_in_symbol: safe_get_optional_string(&row, "trade_token_in_symbol"),
+                    amount_in: safe_get_decimal(&row, "trade_amount_in"),
+                    token_out: safe_get_string(&row, "trade_token_out"),
+                    token_out_symbol: safe_get_optional_string(&row, "trade_token_out_symbol"),
+                    amount_out: safe_get_decimal(&row, "trade_amount_out"),
+                }),
                 block_number: safe_get_i64(&row, "block_number"),
                 timestamp: safe_get_datetime(&row, "timestamp"),
             });
@@ -129,6 +230,7 @@ impl WalletOperations {
         wallet_address: &str,
         chain_id: Option<i32>,
         days: i32,
+        attribution: WalletAttribution,
     ) -> Result<Option<WalletStats>> {
         let chain_filter = if let Some(chain_id) = chain_id {
             format!("AND se.chain_id = {}", chain_id)
@@ -142,16 +244,13 @@ impl WalletOperations {
             SELECT 
                 COUNT(*) as total_transactions,
                 SUM(
-                    CASE 
-                        WHEN se.amount0_in > 0 THEN se.amount0_in
-                        WHEN se.amount1_in > 0 THEN se.amount1_in  
-                        ELSE 0
-                    END
+                    COALESCE(se.volume_usd, 0)
                 ) as total_volume_usd,
+                SUM(COALESCE(se.fee_usd, 0)) as total_fees_paid,
                 MIN(se.timestamp) as first_transaction,
                 MAX(se.timestamp) as last_transaction
             FROM swap_events se
-            WHERE (se.sender = $1 OR se.to_address = $1)
+            WHERE {}
             AND se.timestamp >= NOW() - INTERVAL '1 day' * $2
             {}
         )
@@ -160,7 +259,7 @@ impl WalletOperations {
             {} as chain_id,
             COALESCE(total_transactions, 0) as total_transactions,
             COALESCE(total_volume_usd, 0) as total_volume_usd,
-            0 as total_fees_paid,
+            COALESCE(total_fees_paid, 0) as total_fees_paid,
             0 as profit_loss,
             0 as win_rate,
             CASE 
@@ -171,6 +270,7 @@ impl WalletOperations {
             last_transaction
         FROM wallet_activity
         "#,
+            attribution.condition("se"),
             chain_filter,
             chain_id
                 .map(|c| c.to_string())
@@ -202,4 +302,228 @@ impl WalletOperations {
             Ok(None)
         }
     }
+
+    /// 钱包全部历史 swap（按链上顺序），供成本计算使用
+    pub async fn get_wallet_swap_legs(
+        pool: &PgPool,
+        wallet_address: &str,
+        chain_id: Option<i32>,
+        attribution: WalletAttribution,
+    ) -> Result<Vec<WalletSwapLeg>> {
+        let chain_filter = if let Some(chain_id) = chain_id {
+            format!("AND se.chain_id = {}", chain_id)
+        } else {
+            "".to_string()
+        };
+
+        // side: buy = 用 token0 买入 token1，sell = 用 token1 买入 token0
+        let query = format!(
+            r#"
+        SELECT
+            se.chain_id,
+            se.pair_address,
+            se.transaction_hash,
+            se.block_number,
+            se.log_index,
+            se.timestamp,
+            CASE WHEN se.side = 'buy' THEN lower(tp.token0) ELSE lower(tp.token1) END as token_in,
+            CASE WHEN se.side = 'buy' THEN tp.token0_symbol ELSE tp.token1_symbol END as token_in_symbol,
+            CASE WHEN se.side = 'buy' THEN tp.token0_name ELSE tp.token1_name END as token_in_name,
+            CASE WHEN se.side = 'buy' THEN se.amount0 ELSE se.amount1 END as amount_in,
+            CASE WHEN se.side = 'buy' THEN lower(tp.token1) ELSE lower(tp.token0) END as token_out,
+            CASE WHEN se.side = 'buy' THEN tp.token1_symbol ELSE tp.token0_symbol END as token_out_symbol,
+            CASE WHEN se.side = 'buy' THEN tp.token1_name ELSE tp.token0_name END as token_out_name,
+            CASE WHEN se.side = 'buy' THEN se.amount1 ELSE se.amount0 END as amount_out,
+            se.volume_usd,
+            se.fee_usd
+        FROM swap_events se
+        JOIN trading_pairs tp ON tp.chain_id = se.chain_id AND tp.address = se.pair_address
+        WHERE {}
+        AND se.side IS NOT NULL
+        {}
+        ORDER BY se.block_number, se.log_index
+        "#,
+            attribution.condition("se"),
+            chain_filter
+        );
+
+        let rows = sqlx::query(&query).bind(wallet_address).fetch_all(pool).await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WalletSwapLeg {
+                chain_id: safe_get_i32(row, "chain_id"),
+                pair_address: safe_get_string(row, "pair_address"),
+                transaction_hash: safe_get_string(row, "transaction_hash"),
+                block_number: safe_get_i64(row, "block_number"),
+                log_index: safe_get_i32(row, "log_index"),
+                timestamp: safe_get_datetime(row, "timestamp"),
+                token_in: safe_get_string(row, "token_in"),
+                token_in_symbol: safe_get_optional_string(row, "token_in_symbol"),
+                token_in_name: safe_get_optional_string(row, "token_in_name"),
+                amount_in: safe_get_decimal(row, "amount_in"),
+                token_out: safe_get_string(row, "token_out"),
+                token_out_symbol: safe_get_optional_string(row, "token_out_symbol"),
+                token_out_name: safe_get_optional_string(row, "token_out_name"),
+                amount_out: safe_get_decimal(row, "amount_out"),
+                volume_usd: safe_get_optional_decimal(row, "volume_usd"),
+                fee_usd: safe_get_optional_decimal(row, "fee_usd"),
+            })
+            .collect())
+    }
+
+    /// 钱包做过 mint 的交易对，按交易对汇总存入 / 取出。
+    /// Mint 事件没有接收方，存入和取出两侧统一按交易发起人归属，保证净流动性在同一钱包内配对
+    pub async fn get_wallet_liquidity_aggregates(
+        pool: &PgPool,
+        wallet_address: &str,
+        chain_id: Option<i32>,
+    ) -> Result<Vec<WalletLiquidityAggregate>> {
+        let rows = sqlx::query(
+            r#"
+            WITH legs AS (
+                SELECT chain_id, pair_address, amount0, amount1, timestamp, 1 as direction
+                FROM mint_events
+                WHERE sender = $1 AND ($2::INTEGER IS NULL OR chain_id = $2)
+                UNION ALL
+                SELECT chain_id, pair_address, amount0, amount1, timestamp, -1 as direction
+                FROM burn_events
+                WHERE sender = $1 AND ($2::INTEGER IS NULL OR chain_id = $2)
+            ),
+            normalized AS (
+                SELECT
+                    l.chain_id,
+                    l.pair_address,
+                    l.direction,
+                    l.timestamp,
+                    l.amount0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) as amount0,
+                    l.amount1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) as amount1
+                FROM legs l
+                JOIN trading_pairs tp ON tp.chain_id = l.chain_id AND tp.address = l.pair_address
+            ),
+            positions AS (
+                SELECT
+                    chain_id,
+                    pair_address,
+                    MIN(timestamp) FILTER (WHERE direction = 1) as first_deposit_at,
+                    COALESCE(SUM(amount0) FILTER (WHERE direction = 1), 0) as deposited0,
+                    COALESCE(SUM(amount1) FILTER (WHERE direction = 1), 0) as deposited1,
+                    COALESCE(SUM(amount0) FILTER (WHERE direction = -1), 0) as withdrawn0,
+                    COALESCE(SUM(amount1) FILTER (WHERE direction = -1), 0) as withdrawn1,
+                    COALESCE(SUM(sqrt(amount0 * amount1)) FILTER (WHERE direction = 1), 0) as liquidity_added,
+                    COALESCE(SUM(sqrt(amount0 * amount1)) FILTER (WHERE direction = -1), 0) as liquidity_removed
+                FROM normalized
+                GROUP BY chain_id, pair_address
+                HAVING COUNT(*) FILTER (WHERE direction = 1) > 0
+            )
+            SELECT
+                p.chain_id,
+                p.pair_address,
+                lower(tp.token0) as token0,
+                lower(tp.token1) as token1,
+                tp.token0_symbol,
+                tp.token1_symbol,
+                p.first_deposit_at,
+                trim_scale(round(p.deposited0, 18)) as deposited0,
+                trim_scale(round(p.deposited1, 18)) as deposited1,
+                trim_scale(round(p.withdrawn0, 18)) as withdrawn0,
+                trim_scale(round(p.withdrawn1, 18)) as withdrawn1,
+                trim_scale(round(p.liquidity_added, 18)) as liquidity_added,
+                trim_scale(round(p.liquidity_removed, 18)) as liquidity_removed,
+                trim_scale(round(reserves.pool_liquidity, 18)) as pool_liquidity,
+                trim_scale(round(reserves.sqrt_price, 18)) as sqrt_price,
+                COALESCE(fees.fees_usd, 0) as pool_fees_usd,
+                u0.price_usd as token0_price_usd,
+                u1.price_usd as token1_price_usd
+            FROM positions p
+            JOIN trading_pairs tp ON tp.chain_id = p.chain_id AND tp.address = p.pair_address
+            LEFT JOIN LATERAL (
+                SELECT
+                    sqrt(r0 * r1) as pool_liquidity,
+                    sqrt(r1 / r0) as sqrt_price
+                FROM (
+                    SELECT
+                        pr.reserve0 / power(10::numeric, COALESCE(tp.token0_decimals, 18)) as r0,
+                        pr.reserve1 / power(10::numeric, COALESCE(tp.token1_decimals, 18)) as r1
+                    FROM pair_reserves pr
+                    WHERE pr.chain_id = p.chain_id AND pr.pair_address = p.pair_address
+                    AND pr.reserve0 > 0 AND pr.reserve1 > 0
+                ) r
+            ) reserves ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT SUM(se.fee_usd) as fees_usd
+                FROM swap_events se
+                WHERE se.chain_id = p.chain_id AND se.pair_address = p.pair_address
+                AND se.timestamp >= p.first_deposit_at
+            ) fees ON TRUE
+            LEFT JOIN token_usd_prices u0 ON u0.chain_id = p.chain_id AND u0.token_address = lower(tp.token0)
+            LEFT JOIN token_usd_prices u1 ON u1.chain_id = p.chain_id AND u1.token_address = lower(tp.token1)
+            ORDER BY p.first_deposit_at DESC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WalletLiquidityAggregate {
+                chain_id: safe_get_i32(row, "chain_id"),
+                pair_address: safe_get_string(row, "pair_address"),
+                token0: safe_get_string(row, "token0"),
+                token1: safe_get_string(row, "token1"),
+                token0_symbol: safe_get_optional_string(row, "token0_symbol"),
+                token1_symbol: safe_get_optional_string(row, "token1_symbol"),
+                first_deposit_at: safe_get_datetime(row, "first_deposit_at"),
+                deposited0: safe_get_decimal(row, "deposited0"),
+                deposited1: safe_get_decimal(row, "deposited1"),
+                withdrawn0: safe_get_decimal(row, "withdrawn0"),
+                withdrawn1: safe_get_decimal(row, "withdrawn1"),
+                liquidity_added: safe_get_decimal(row, "liquidity_added"),
+                liquidity_removed: safe_get_decimal(row, "liquidity_removed"),
+                pool_liquidity: safe_get_optional_decimal(row, "pool_liquidity"),
+                sqrt_price: safe_get_optional_decimal(row, "sqrt_price"),
+                pool_fees_usd: safe_get_decimal(row, "pool_fees_usd"),
+                token0_price_usd: safe_get_optional_decimal(row, "token0_price_usd"),
+                token1_price_usd: safe_get_optional_decimal(row, "token1_price_usd"),
+            })
+            .collect())
+    }
+
+    /// 代币当前美元价格，key 为 (chain_id, 小写地址)
+    pub async fn get_current_usd_prices(
+        pool: &PgPool,
+        tokens: &[(i32, String)],
+    ) -> Result<HashMap<(i32, String), Decimal>> {
+        if tokens.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let chain_ids: Vec<i32> = tokens.iter().map(|(c, _)| *c).collect();
+        let addresses: Vec<String> = tokens.iter().map(|(_, a)| a.clone()).collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT u.chain_id, u.token_address, u.price_usd
+            FROM token_usd_prices u
+            JOIN UNNEST($1::INTEGER[], $2::TEXT[]) AS t(chain_id, token_address)
+                ON u.chain_id = t.chain_id AND u.token_address = t.token_address
+            "#,
+        )
+        .bind(&chain_ids)
+        .bind(&addresses)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    (safe_get_i32(row, "chain_id"), safe_get_string(row, "token_address")),
+                    safe_get_decimal(row, "price_usd"),
+                )
+            })
+            .collect())
+    }
 }
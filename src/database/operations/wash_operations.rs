@@ -0,0 +1,136 @@
+use crate::database::utils::*;
+use crate::types::PairWashStats;
+use anyhow::Result;
+use sqlx::PgPool;
+
+pub struct WashOperations;
+
+impl WashOperations {
+    /// 重算链上所有交易对近 24 小时的刷量指标，只统计已定价的 swap，钱包为交易发起人。
+    ///
+    /// 评分 = 100 × min(1, 0.7 × max(来回交易占比, 循环流转占比) + 0.3 × 集中度)，
+    /// 集中度 = max(前 5 钱包占比 - 50%, 0) × 2，即前 5 个钱包贡献过半成交量后才开始扣分
+    pub async fn refresh_pair_wash_stats(pool: &PgPool, chain_id: i32) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM pair_wash_stats WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            WITH swaps AS (
+                SELECT pair_address, sender AS wallet, to_address, side, volume_usd
+                FROM swap_events
+                WHERE chain_id = $1
+                AND timestamp >= NOW() - INTERVAL '24 hours'
+                AND side IS NOT NULL
+                AND volume_usd IS NOT NULL
+            ),
+            wallets AS (
+                SELECT
+                    pair_address,
+                    wallet,
+                    COUNT(*) AS trade_count,
+                    SUM(volume_usd) AS volume_usd,
+                    2 * LEAST(
+                        COALESCE(SUM(volume_usd) FILTER (WHERE side = 'buy'), 0),
+                        COALESCE(SUM(volume_usd) FILTER (WHERE side = 'sell'), 0)
+                    ) AS round_trip_usd,
+                    ROW_NUMBER() OVER (PARTITION BY pair_address ORDER BY SUM(volume_usd) DESC, wallet) AS rn
+                FROM swaps
+                GROUP BY pair_address, wallet
+            ),
+            -- 代币转给了在同一交易对做反向交易的另一个钱包
+            circular AS (
+                SELECT s.pair_address, SUM(s.volume_usd) AS circular_usd
+                FROM swaps s
+                WHERE s.to_address <> s.wallet
+                AND EXISTS (
+                    SELECT 1 FROM swaps o
+                    WHERE o.pair_address = s.pair_address
+                    AND o.wallet = s.to_address
+                    AND o.side <> s.side
+                )
+                GROUP BY s.pair_address
+            ),
+            pairs AS (
+                SELECT
+                    w.pair_address,
+                    SUM(w.volume_usd) AS volume_usd,
+                    SUM(w.trade_count) AS trade_count,
+                    COUNT(*) AS trader_count,
+                    SUM(w.round_trip_usd) AS round_trip_usd,
+                    COALESCE(SUM(w.volume_usd) FILTER (WHERE w.rn = 1), 0) AS top1_usd,
+                    COALESCE(SUM(w.volume_usd) FILTER (WHERE w.rn <= 5), 0) AS top5_usd
+                FROM wallets w
+                GROUP BY w.pair_address
+                HAVING SUM(w.volume_usd) > 0
+            ),
+            scored AS (
+                SELECT
+                    p.*,
+                    COALESCE(c.circular_usd, 0) AS circular_usd,
+                    100 * LEAST(1,
+                        0.7 * GREATEST(p.round_trip_usd / p.volume_usd, COALESCE(c.circular_usd, 0) / p.volume_usd)
+                        + 0.3 * GREATEST(p.top5_usd / p.volume_usd - 0.5, 0) * 2
+                    ) AS score
+                FROM pairs p
+                LEFT JOIN circular c ON c.pair_address = p.pair_address
+            )
+            INSERT INTO pair_wash_stats (
+                chain_id, pair_address, volume_usd, organic_volume_usd, trade_count, trader_count,
+                round_trip_volume_usd, circular_volume_usd, top_wallet_share, top5_wallet_share, wash_score
+            )
+            SELECT
+                $1,
+                pair_address,
+                volume_usd,
+                trim_scale(round(volume_usd * (1 - score / 100), 8)),
+                trade_count,
+                trader_count,
+                round_trip_usd,
+                circular_usd,
+                trim_scale(round(top1_usd / volume_usd, 6)),
+                trim_scale(round(top5_usd / volume_usd, 6)),
+                trim_scale(round(score, 4))
+            FROM scored
+            "#,
+        )
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_pair_wash_stats(
+        pool: &PgPool,
+        chain_id: i32,
+        pair_address: &str,
+    ) -> Result<Option<PairWashStats>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT * FROM pair_wash_stats WHERE chain_id = $1 AND pair_address = $2",
+        )
+        .bind(chain_id)
+        .bind(pair_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| PairWashStats {
+            volume_usd: safe_get_decimal(&row, "volume_usd"),
+            organic_volume_usd: safe_get_decimal(&row, "organic_volume_usd"),
+            trade_count: safe_get_i64(&row, "trade_count"),
+            trader_count: safe_get_i64(&row, "trader_count"),
+            round_trip_volume_usd: safe_get_decimal(&row, "round_trip_volume_usd"),
+            circular_volume_usd: safe_get_decimal(&row, "circular_volume_usd"),
+            top_wallet_share: safe_get_decimal(&row, "top_wallet_share"),
+            top5_wallet_share: safe_get_decimal(&row, "top5_wallet_share"),
+            wash_score: safe_get_decimal(&row, "wash_score"),
+            updated_at: safe_get_datetime(&row, "updated_at"),
+        }))
+    }
+}
@@ -0,0 +1,266 @@
+use crate::config::WhaleThresholds;
+use crate::database::utils::*;
+use crate::types::{BurnEvent, MintEvent, SwapEvent, WhaleTrade};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool};
+
+/// 巨鲸交易查询条件
+#[derive(Debug, Clone, Default)]
+pub struct WhaleFilter {
+    pub chain_id: Option<i32>,
+    pub pair_address: Option<String>,
+    pub wallet: Option<String>,
+    pub event_type: Option<String>,
+    pub min_usd: Option<Decimal>,
+}
+
+/// 待检查的事件，reserve_delta 为事件对池子储备量的有符号原始变化
+struct WhaleCandidate<'a> {
+    chain_id: i32,
+    pair_address: &'a str,
+    event_type: &'static str,
+    side: Option<&'a str>,
+    wallet: &'a str,
+    reserve_delta0: Decimal,
+    reserve_delta1: Decimal,
+    /// swap 使用写入时计算的美元成交量，mint / burn 按两侧数量估值
+    value_usd: Option<Decimal>,
+    transaction_hash: &'a str,
+    block_number: i64,
+    log_index: i32,
+    timestamp: DateTime<Utc>,
+}
+
+pub struct WhaleOperations;
+
+impl WhaleOperations {
+    /// 新写入的 swap 是否达到巨鲸阈值，按输入数量占交易前储备的比例计算
+    pub async fn check_swap(
+        pool: &PgPool,
+        swap: &SwapEvent,
+        thresholds: &WhaleThresholds,
+    ) -> Result<Option<WhaleTrade>> {
+        Self::record(
+            pool,
+            &WhaleCandidate {
+                chain_id: swap.chain_id,
+                pair_address: &swap.pair_address,
+                event_type: "swap",
+                side: swap.side.as_deref(),
+                wallet: &swap.sender,
+                reserve_delta0: swap.amount0_in - swap.amount0_out,
+                reserve_delta1: swap.amount1_in - swap.amount1_out,
+                value_usd: swap.volume_usd,
+                transaction_hash: &swap.transaction_hash,
+                block_number: swap.block_number,
+                log_index: swap.log_index,
+                timestamp: swap.timestamp,
+            },
+            thresholds,
+        )
+        .await
+    }
+
+    pub async fn check_mint(
+        pool: &PgPool,
+        mint: &MintEvent,
+        thresholds: &WhaleThresholds,
+    ) -> Result<Option<WhaleTrade>> {
+        Self::record(
+            pool,
+            &WhaleCandidate {
+                chain_id: mint.chain_id,
+                pair_address: &mint.pair_address,
+                event_type: "mint",
+                side: None,
+                wallet: &mint.sender,
+                reserve_delta0: mint.amount0,
+                reserve_delta1: mint.amount1,
+                value_usd: None,
+                transaction_hash: &mint.transaction_hash,
+                block_number: mint.block_number,
+                log_index: mint.log_index,
+                timestamp: mint.timestamp,
+            },
+            thresholds,
+        )
+        .await
+    }
+
+    pub async fn check_burn(
+        pool: &PgPool,
+        burn: &BurnEvent,
+        thresholds: &WhaleThresholds,
+    ) -> Result<Option<WhaleTrade>> {
+        Self::record(
+            pool,
+            &WhaleCandidate {
+                chain_id: burn.chain_id,
+                pair_address: &burn.pair_address,
+                event_type: "burn",
+                side: None,
+                wallet: &burn.sender,
+                reserve_delta0: -burn.amount0,
+                reserve_delta1: -burn.amount1,
+                value_usd: None,
+                transaction_hash: &burn.transaction_hash,
+                block_number: burn.block_number,
+                log_index: burn.log_index,
+                timestamp: burn.timestamp,
+            },
+            thresholds,
+        )
+        .await
+    }
+
+    /// 达到阈值时写入并返回，未达到或已记录时返回 None。
+    /// 监听器按日志顺序处理，事件之前的 Sync 已写入 pair_reserves，事件前储备 = 当前储备 - 储备变化
+    async fn record(
+        pool: &PgPool,
+        candidate: &WhaleCandidate<'_>,
+        thresholds: &WhaleThresholds,
+    ) -> Result<Option<WhaleTrade>> {
+        let row = sqlx::query(
+            r#"
+            WITH pre AS (
+                SELECT pr.reserve0 - $4 AS reserve0, pr.reserve1 - $5 AS reserve1
+                FROM pair_reserves pr
+                WHERE pr.chain_id = $1 AND pr.pair_address = $2
+            ),
+            metrics AS (
+                SELECT
+                    CASE WHEN $3 = 'swap' THEN $6::DECIMAL
+                         ELSE pair_liquidity_usd($1, $2, ABS($4), ABS($5)) END AS value_usd,
+                    trim_scale(round(
+                        GREATEST(ABS($4) / NULLIF(pre.reserve0, 0), ABS($5) / NULLIF(pre.reserve1, 0)) * 100,
+                        4
+                    )) AS reserve_pct
+                FROM (SELECT 1) one
+                LEFT JOIN pre ON TRUE
+            ),
+            flagged AS (
+                SELECT
+                    m.*,
+                    $12 > 0 AND COALESCE(m.value_usd >= $12, FALSE) AS by_usd,
+                    $13 > 0 AND COALESCE(m.reserve_pct >= $13, FALSE) AS by_reserve
+                FROM metrics m
+            )
+            INSERT INTO whale_trades (
+                chain_id, pair_address, event_type, side, wallet, amount0, amount1, value_usd, reserve_pct,
+                trigger, transaction_hash, block_number, log_index, timestamp
+            )
+            SELECT
+                $1, $2, $3, $7, $8,
+                trim_scale(ABS($4) / power(10::numeric, COALESCE(tp.token0_decimals, 18))),
+                trim_scale(ABS($5) / power(10::numeric, COALESCE(tp.token1_decimals, 18))),
+                f.value_usd,
+                f.reserve_pct,
+                CASE WHEN f.by_usd AND f.by_reserve THEN 'both' WHEN f.by_usd THEN 'usd' ELSE 'reserve' END,
+                $9, $10, $11, $14
+            FROM flagged f
+            LEFT JOIN trading_pairs tp ON tp.chain_id = $1 AND tp.address = $2
+            WHERE f.by_usd OR f.by_reserve
+            ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(candidate.chain_id)
+        .bind(candidate.pair_address)
+        .bind(candidate.event_type)
+        .bind(candidate.reserve_delta0)
+        .bind(candidate.reserve_delta1)
+        .bind(candidate.value_usd)
+        .bind(candidate.side)
+        .bind(candidate.wallet)
+        .bind(candidate.transaction_hash)
+        .bind(candidate.block_number)
+        .bind(candidate.log_index)
+        .bind(thresholds.min_usd)
+        .bind(thresholds.reserve_pct)
+        .bind(candidate.timestamp)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::row_to_whale_trade))
+    }
+
+    pub async fn get_whale_trades(
+        pool: &PgPool,
+        filter: &WhaleFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WhaleTrade>> {
+        let mut conditions = vec!["1=1".to_string()];
+        let mut param_count = 0;
+
+        if filter.chain_id.is_some() {
+            param_count += 1;
+            conditions.push(format!("chain_id = ${}", param_count));
+        }
+        if filter.pair_address.is_some() {
+            param_count += 1;
+            conditions.push(format!("pair_address = ${}", param_count));
+        }
+        if filter.wallet.is_some() {
+            param_count += 1;
+            conditions.push(format!("wallet = ${}", param_count));
+        }
+        if filter.event_type.is_some() {
+            param_count += 1;
+            conditions.push(format!("event_type = ${}", param_count));
+        }
+        if filter.min_usd.is_some() {
+            param_count += 1;
+            conditions.push(format!("value_usd >= ${}", param_count));
+        }
+
+        let query = format!(
+            "SELECT * FROM whale_trades WHERE {} ORDER BY timestamp DESC, log_index DESC LIMIT ${} OFFSET ${}",
+            conditions.join(" AND "),
+            param_count + 1,
+            param_count + 2
+        );
+
+        let mut query_builder = sqlx::query(&query);
+        if let Some(chain_id) = filter.chain_id {
+            query_builder = query_builder.bind(chain_id);
+        }
+        if let Some(pair_address) = &filter.pair_address {
+            query_builder = query_builder.bind(pair_address);
+        }
+        if let Some(wallet) = &filter.wallet {
+            query_builder = query_builder.bind(wallet);
+        }
+        if let Some(event_type) = &filter.event_type {
+            query_builder = query_builder.bind(event_type);
+        }
+        if let Some(min_usd) = filter.min_usd {
+            query_builder = query_builder.bind(min_usd);
+        }
+
+        let rows = query_builder.bind(limit).bind(offset).fetch_all(pool).await?;
+        Ok(rows.iter().map(Self::row_to_whale_trade).collect())
+    }
+
+    fn row_to_whale_trade(row: &PgRow) -> WhaleTrade {
+        WhaleTrade {
+            id: safe_get_uuid(row, "id"),
+            chain_id: safe_get_i32(row, "chain_id"),
+            pair_address: safe_get_string(row, "pair_address"),
+            event_type: safe_get_string(row, "event_type"),
+            side: safe_get_optional_string(row, "side"),
+            wallet: safe_get_string(row, "wallet"),
+            amount0: safe_get_decimal(row, "amount0"),
+            amount1: safe_get_decimal(row, "amount1"),
+            value_usd: safe_get_optional_decimal(row, "value_usd"),
+            reserve_pct: safe_get_optional_decimal(row, "reserve_pct"),
+            trigger: safe_get_string(row, "trigger"),
+            transaction_hash: safe_get_string(row, "transaction_hash"),
+            block_number: safe_get_i64(row, "block_number"),
+            log_index: safe_get_i32(row, "log_index"),
+            timestamp: safe_get_datetime(row, "timestamp"),
+        }
+    }
+}
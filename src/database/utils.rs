@@ -48,6 +48,70 @@ pub fn safe_get_optional_i32(row: &PgRow, column: &str) -> Option<i32> {
     row.try_get::<Option<i32>, _>(column).ok().flatten()
 }
 
+pub fn safe_get_optional_i64(row: &PgRow, column: &str) -> Option<i64> {
+    row.try_get::<Option<i64>, _>(column).ok().flatten()
+}
+
 pub fn safe_get_optional_datetime(row: &PgRow, column: &str) -> Option<DateTime<Utc>> {
     row.try_get::<Option<DateTime<Utc>>, _>(column).ok().flatten()
-}
\ No newline at end of file
+}
+
+/// 解析 `<n><单位>` 形式的时间窗口（如 `5m`、`24h`、`7d`），返回秒数。
+/// 单位只接受 `units` 中列出的 `m` / `h` / `d`，超过 `max_secs` 或溢出时返回 None
+pub fn parse_window(window: &str, units: &[char], max_secs: i64) -> Option<i64> {
+    let window = window.trim();
+    let unit = window.chars().last().filter(|unit| units.contains(unit))?;
+    let unit_secs = match unit {
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return None,
+    };
+    let value: i64 = window[..window.len() - unit.len_utf8()].parse().ok().filter(|v| *v > 0)?;
+    value.checked_mul(unit_secs).filter(|secs| *secs <= max_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNITS: &[char] = &['m', 'h', 'd'];
+
+    #[test]
+    fn parse_window_accepts_allowed_units() {
+        assert_eq!(parse_window("5m", UNITS, 7 * 86400), Some(300));
+        assert_eq!(parse_window("24h", UNITS, 7 * 86400), Some(86400));
+        assert_eq!(parse_window(" 7d ", UNITS, 7 * 86400), Some(7 * 86400));
+    }
+
+    #[test]
+    fn parse_window_rejects_units_outside_allow_list() {
+        assert_eq!(parse_window("30m", &['h', 'd'], 365 * 86400), None);
+        assert_eq!(parse_window("30s", UNITS, 365 * 86400), None);
+        assert_eq!(parse_window("30", UNITS, 365 * 86400), None);
+    }
+
+    #[test]
+    fn parse_window_enforces_max_duration() {
+        assert_eq!(parse_window("7d", UNITS, 7 * 86400), Some(7 * 86400));
+        assert_eq!(parse_window("8d", UNITS, 7 * 86400), None);
+    }
+
+    #[test]
+    fn parse_window_rejects_invalid_values() {
+        assert_eq!(parse_window("", UNITS, 86400), None);
+        assert_eq!(parse_window("d", UNITS, 86400), None);
+        assert_eq!(parse_window("0h", UNITS, 86400), None);
+        assert_eq!(parse_window("-1h", UNITS, 86400), None);
+        assert_eq!(parse_window("1.5h", UNITS, 86400), None);
+    }
+
+    #[test]
+    fn parse_window_is_char_safe_and_overflow_safe() {
+        // 多字节字符不能在字节边界上切分
+        assert_eq!(parse_window("7天", UNITS, i64::MAX), None);
+        assert_eq!(parse_window("天", UNITS, i64::MAX), None);
+        assert_eq!(parse_window("7天d", UNITS, i64::MAX), None);
+        assert_eq!(parse_window("9223372036854775807d", UNITS, i64::MAX), None);
+    }
+}
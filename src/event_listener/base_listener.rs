@@ -1,11 +1,13 @@
-use crate::database::operations::EventOperations;
+use crate::database::operations::{partition_month_key, EventOperations, PartitionOperations};
 use crate::database::Database;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use ethers::{
     providers::{Http, Middleware, Provider},
     types::{Address,H256},
 };
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use tokio::sync::broadcast;
 use tokio::time::{sleep, Duration};
 pub struct BaseEventListener {
@@ -18,6 +20,7 @@ pub struct BaseEventListener {
     pub start_block: u64,
     pub block_batch_size: u64,
     pub event_type: String, // 新增：事件类型标识
+    known_partitions: Mutex<HashSet<i32>>, // 已确认存在的事件表月分区
 }
 
 impl BaseEventListener {
@@ -41,6 +44,7 @@ impl BaseEventListener {
             start_block,
             block_batch_size,
             event_type,
+            known_partitions: Mutex::new(HashSet::new()),
         }
     }
 
@@ -106,6 +110,18 @@ impl BaseEventListener {
         Ok(Some((from_block, to_block)))
     }
 
+    /// 确保事件时间所在月份的分区已创建（历史区块回填时可能落在任意月份）
+    pub async fn ensure_event_partition(&self, timestamp: DateTime<Utc>) -> Result<()> {
+        let key = partition_month_key(timestamp);
+        if self.known_partitions.lock().unwrap().contains(&key) {
+            return Ok(());
+        }
+
+        PartitionOperations::ensure_event_partitions(self.database.pool(), timestamp).await?;
+        self.known_partitions.lock().unwrap().insert(key);
+        Ok(())
+    }
+
     pub async fn sleep_poll_interval(&self) {
         sleep(self.poll_interval).await;
     }
@@ -14,8 +14,11 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::database::operations::EVENT_TYPE_FACTORY;
-use crate::database::operations::{EventOperations, TradingOperations};
+use crate::database::operations::{
+    EventOperations, QuoteOperations, RiskOperations, TradingOperations, QUOTE_SIDE_TOKEN0,
+};
 use crate::api::websocket::send_pair_created_event;
+use crate::services::risk_service::inspect_token;
 
 abigen!(
     UniswapV2Factory,
@@ -244,6 +247,13 @@ impl FactoryEventListener {
         };
 
         TradingOperations::insert_trading_pair(self.base.database.pool(), &pair).await?;
+        // 失败时由风险服务补查
+        if let Err(e) = self.score_pair_risk(&pair).await {
+            warn!(
+                "⚠️ 链 {} (工厂): 交易对 {} 风险检查失败: {}",
+                self.base.chain_id, pair.address, e
+            );
+        }
         // WS的消息推送
         send_pair_created_event(&self.base.event_sender, &pair);
 
@@ -264,6 +274,37 @@ impl FactoryEventListener {
         Ok(())
     }
 
+    /// 对非计价方代币做字节码风险检查并写入交易对
+    async fn score_pair_risk(&self, pair: &TradingPair) -> Result<()> {
+        let pool = self.base.database.pool();
+        let quote_side = QuoteOperations::resolve_quote_side(
+            pool,
+            pair.chain_id,
+            &pair.address,
+            QuoteSelection::Auto,
+        )
+        .await?;
+        let token = if quote_side == QUOTE_SIDE_TOKEN0 {
+            &pair.token1
+        } else {
+            &pair.token0
+        };
+
+        let flags = inspect_token(&self.base.provider, token.parse()?).await?;
+        RiskOperations::set_token_risk(pool, pair.chain_id, &pair.address, token, &flags).await?;
+
+        if !flags.is_empty() {
+            info!(
+                "🛡️ 链 {} (工厂): 代币 {} 风险标记: {}",
+                self.base.chain_id,
+                token,
+                flags.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
     async fn get_token_info(
         &self,
         token_address: Address,
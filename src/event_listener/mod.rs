@@ -4,7 +4,7 @@ pub mod swap_listener;
 
 pub use base_listener::BaseEventListener;
 pub use factory_listener::FactoryEventListener;
-pub use swap_listener::SwapEventListener;
+pub use swap_listener::{SwapEventListener, SwapListenerConfig};
 
 use crate::config::ChainConfig;
 use crate::database::Database;
@@ -29,7 +29,12 @@ impl EventListenerManager {
         }
     }
 
-    pub async fn start_chain_listeners(&self, chain_id: u64, config: &ChainConfig) -> Result<()> {
+    pub async fn start_chain_listeners(
+        &self,
+        chain_id: u64,
+        config: &ChainConfig,
+        swap_listener_config: SwapListenerConfig,
+    ) -> Result<()> {
         let provider = Arc::new(Provider::<Http>::try_from(&config.rpc_url)?);
         let factory_address: Address = config.factory_address.parse()?;
 
@@ -60,7 +65,6 @@ impl EventListenerManager {
         let swap_provider = Arc::clone(&provider);
         let swap_database = Arc::clone(&self.database);
         let swap_sender = self.event_sender.clone();
-        let swap_config = config.clone();
 
         let swap_handle = tokio::spawn(async move {
             let mut swap_listener = SwapEventListener::new(
@@ -68,9 +72,7 @@ impl EventListenerManager {
                 swap_database,
                 chain_id,
                 swap_sender,
-                swap_config.poll_interval,
-                swap_config.start_block,
-                swap_config.block_batch_size,
+                swap_listener_config,
             );
 
             if let Err(e) = swap_listener.start_monitoring().await {
@@ -1,4 +1,5 @@
 use super::base_listener::BaseEventListener;
+use crate::config::{AlertConfig, ArbitrageConfig, TaxConfig, WhaleConfig};
 use crate::database::operations::EVENT_TYPE_SWAP;
 use crate::types::*;
 use anyhow::Result;
@@ -11,13 +12,20 @@ use ethers::{
     utils::keccak256,
 };
 use rust_decimal::Decimal;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::api::websocket::{send_liquidity_event, send_swap_event};
-use crate::database::operations::{EventOperations, TradingOperations};
+use crate::api::websocket::{
+    send_alert_event, send_arbitrage_event, send_liquidity_event, send_swap_event, send_whale_event,
+};
+use crate::database::operations::{
+    ActivityOperations, AlertOperations, CandleOperations, EventOperations, LeaderboardOperations,
+    TaxOperations, TradingOperations, ValuationOperations, WhaleOperations,
+};
+use crate::services::ArbitrageService;
 
 abigen!(
     UniswapV2Pair,
@@ -25,11 +33,29 @@ abigen!(
         event Mint(address indexed sender, uint256 amount0, uint256 amount1)
         event Burn(address indexed sender, uint256 amount0, uint256 amount1, address indexed to)
         event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to)
+        event Sync(uint112 reserve0, uint112 reserve1)
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
     ]"#
 );
 
+/// 交换事件监听器的单链配置：轮询参数，以及撤池告警、转账税、套利扫描和巨鲸阈值
+#[derive(Debug, Clone)]
+pub struct SwapListenerConfig {
+    pub poll_interval: u64,
+    pub start_block: u64,
+    pub block_batch_size: u64,
+    pub alerts: AlertConfig,
+    pub tax: TaxConfig,
+    pub arbitrage: ArbitrageConfig,
+    /// 巨鲸阈值按链配置，可按交易对覆盖
+    pub whales: WhaleConfig,
+}
+
 pub struct SwapEventListener {
     base: BaseEventListener,
+    /// 已尝试通过 getReserves 初始化储备量的交易对，避免每轮重复请求
+    reserves_bootstrapped: HashSet<Address>,
+    config: SwapListenerConfig,
 }
 
 impl SwapEventListener {
@@ -38,9 +64,7 @@ impl SwapEventListener {
         database: Arc<crate::database::Database>,
         chain_id: u64,
         event_sender: broadcast::Sender<String>,
-        poll_interval: u64,
-        start_block: u64,
-        block_batch_size: u64,
+        config: SwapListenerConfig,
     ) -> Self {
         Self {
             base: BaseEventListener::new(
@@ -48,11 +72,13 @@ impl SwapEventListener {
                 database,
                 chain_id,
                 event_sender,
-                poll_interval,
-                start_block,
-                block_batch_size,
+                config.poll_interval,
+                config.start_block,
+                config.block_batch_size,
                 EVENT_TYPE_SWAP.to_string(), // 使用交换事件类型
             ),
+            reserves_bootstrapped: HashSet::new(),
+            config,
         }
     }
 
@@ -129,6 +155,13 @@ impl SwapEventListener {
                 return Ok(());
             }
 
+            if let Err(e) = self.bootstrap_pair_reserves(from_block).await {
+                warn!(
+                    "⚠️ 链 {} (交换): 初始化交易对储备量失败: {}",
+                    self.base.chain_id, e
+                );
+            }
+
             info!(
                 "💱 链 {} (交换): 开始查询 {} 个交易对的事件 (区块 {}-{})",
                 self.base.chain_id,
@@ -140,6 +173,7 @@ impl SwapEventListener {
             let mut total_events = 0;
             let mut failed_pairs = 0;
             let mut successful_pairs = 0;
+            let mut touched_pairs = Vec::new();
 
             // Process each pair individually to avoid RPC limitations
             for (index, &pair_address) in pair_addresses.iter().enumerate() {
@@ -151,6 +185,7 @@ impl SwapEventListener {
                         total_events += event_count;
                         successful_pairs += 1;
                         if event_count > 0 {
+                            touched_pairs.push(format!("0x{:x}", pair_address));
                             debug!(
                                 "💱 链 {} (交换): 交易对 0x{:x} 处理了 {} 个事件",
                                 self.base.chain_id, pair_address, event_count
@@ -183,6 +218,88 @@ impl SwapEventListener {
 
             // 更新处理进度
             self.base.update_last_processed_block(to_block).await?;
+
+            if let Err(e) = self.detect_arbitrage(&touched_pairs, to_block).await {
+                warn!(
+                    "⚠️ 链 {} (交换): 套利检测失败: {}",
+                    self.base.chain_id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 本批次储备量更新后，扫描经过有事件的交易对的套利环路并推送新机会
+    async fn detect_arbitrage(&self, touched_pairs: &[String], block_number: u64) -> Result<()> {
+        let opportunities = ArbitrageService::scan(
+            self.base.database.pool(),
+            self.base.chain_id as i32,
+            touched_pairs,
+            block_number as i64,
+            &self.config.arbitrage,
+        )
+        .await?;
+
+        for opportunity in &opportunities {
+            info!(
+                "🔁 链 {} (交换): 发现套利机会 {} 跳, 投入 {} {}, 预期利润 ${}",
+                self.base.chain_id,
+                opportunity.pairs.len(),
+                opportunity.amount_in,
+                opportunity.start_token,
+                opportunity.profit_usd
+            );
+            send_arbitrage_event(&self.base.event_sender, opportunity);
+        }
+
+        Ok(())
+    }
+
+    /// 为还没有储备量记录的交易对读取 `from_block - 1` 时的 getReserves，
+    /// 之后由本批次及以后的 Sync 事件覆盖
+    async fn bootstrap_pair_reserves(&mut self, from_block: u64) -> Result<()> {
+        if from_block == 0 {
+            return Ok(());
+        }
+        let block = from_block - 1;
+
+        let pairs = ValuationOperations::get_pairs_without_reserves(
+            self.base.database.pool(),
+            self.base.chain_id as i32,
+        )
+        .await?;
+
+        for pair in pairs {
+            let pair_address = match pair.parse::<Address>() {
+                Ok(addr) => addr,
+                Err(_) => continue,
+            };
+            if !self.reserves_bootstrapped.insert(pair_address) {
+                continue;
+            }
+
+            let contract = UniswapV2Pair::new(pair_address, Arc::clone(&self.base.provider));
+            match contract.get_reserves().block(block).call().await {
+                Ok((reserve0, reserve1, _)) => {
+                    ValuationOperations::upsert_pair_reserves(
+                        self.base.database.pool(),
+                        self.base.chain_id as i32,
+                        &pair,
+                        &reserve0.to_string(),
+                        &reserve1.to_string(),
+                        block as i64,
+                        i32::MAX,
+                    )
+                    .await?;
+                }
+                Err(e) => {
+                    debug!(
+                        "链 {} (交换): 读取交易对 {} 在区块 {} 的储备量失败: {}",
+                        self.base.chain_id, pair, block, e
+                    );
+                }
+            }
         }
 
         Ok(())
@@ -205,9 +322,26 @@ impl SwapEventListener {
             return Ok(0);
         }
 
+        // 有 swap / mint / burn 的交易，其余交易中的 Sync 为外部调用 sync()
+        let swap_signature = H256::from(keccak256(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+        ));
+        let mint_signature = H256::from(keccak256("Mint(address,uint256,uint256)"));
+        let burn_signature = H256::from(keccak256("Burn(address,uint256,uint256,address)"));
+        let active_txs: HashSet<H256> = logs
+            .iter()
+            .filter(|log| {
+                matches!(log.topics.first(), Some(t) if *t == swap_signature || *t == mint_signature || *t == burn_signature)
+            })
+            .filter_map(|log| log.transaction_hash)
+            .collect();
+
         let mut event_count = 0;
         for log in logs {
-            if let Err(e) = self.handle_pair_event(log).await {
+            let standalone_sync = log
+                .transaction_hash
+                .is_some_and(|tx| !active_txs.contains(&tx));
+            if let Err(e) = self.handle_pair_event(log, standalone_sync).await {
                 warn!(
                     "⚠️ 链 {} (交换): 处理交易对 0x{:x} 事件失败: {}",
                     self.base.chain_id, pair_address, e
@@ -220,7 +354,13 @@ impl SwapEventListener {
         Ok(event_count)
     }
 
-    async fn handle_pair_event(&self, log: Log) -> Result<()> {
+    async fn handle_pair_event(&self, log: Log, standalone_sync: bool) -> Result<()> {
+        // Sync 只更新储备量，不需要区块时间
+        let sync_signature = H256::from(keccak256("Sync(uint112,uint112)"));
+        if log.topics.first() == Some(&sync_signature) {
+            return self.handle_sync_event(log, standalone_sync).await;
+        }
+
         let block_number = log.block_number.unwrap();
         let block_number_hex = format!("0x{:x}", block_number);
         let raw_block: serde_json::Value = self
@@ -239,6 +379,8 @@ impl SwapEventListener {
         let timestamp =
             DateTime::<Utc>::from_timestamp(timestamp_u64 as i64, 0).unwrap_or_else(|| Utc::now());
 
+        self.base.ensure_event_partition(timestamp).await?;
+
         let event_signature = &log.topics[0];
         let swap_signature = H256::from(keccak256(
             "Swap(address,uint256,uint256,uint256,uint256,address)",
@@ -264,6 +406,47 @@ impl SwapEventListener {
         Ok(())
     }
 
+    async fn handle_sync_event(&self, log: Log, standalone: bool) -> Result<()> {
+        let event = SyncFilter::decode_log(&RawLog {
+            topics: log.topics.clone(),
+            data: log.data.0.to_vec(),
+        })?;
+        let pair_address = format!("0x{:x}", log.address);
+        // uint112 可能超出 Decimal 范围，以十进制字符串按 NUMERIC 写入
+        let reserve0 = event.reserve_0.to_string();
+        let reserve1 = event.reserve_1.to_string();
+
+        // 独立 Sync 改变储备量说明代币余额在交易之外发生了变化（rebase / 反射分红 / 直接转入）
+        if standalone
+            && TaxOperations::record_reserve_drift(
+                self.base.database.pool(),
+                self.base.chain_id as i32,
+                &pair_address,
+                &reserve0,
+                &reserve1,
+            )
+            .await?
+        {
+            debug!(
+                "🔁 链 {} (交换): 交易对 {} 储备量在交易之外发生变化",
+                self.base.chain_id, pair_address
+            );
+        }
+
+        ValuationOperations::upsert_pair_reserves(
+            self.base.database.pool(),
+            self.base.chain_id as i32,
+            &pair_address,
+            &reserve0,
+            &reserve1,
+            log.block_number.unwrap().as_u64() as i64,
+            log.log_index.unwrap().as_u32() as i32,
+        )
+        .await?;
+
+        Ok(())
+    }
+
     async fn handle_swap_event(&self, log: Log, timestamp: DateTime<Utc>) -> Result<()> {
         // 获取交易发起人地址（实际用户）
         let tx_origin =
@@ -274,11 +457,12 @@ impl SwapEventListener {
             data: log.data.0.to_vec(),
         }) {
             Ok(event) => {
-                let swap_event = SwapEvent {
+                let mut swap_event = SwapEvent {
                     id: Uuid::new_v4(),
                     chain_id: self.base.chain_id as i32,
                     pair_address: format!("0x{:x}", log.address),
                     sender: tx_origin,
+                    event_sender: Some(format!("0x{:x}", event.sender)),
                     amount0_in: Decimal::from(event.amount_0_in.as_u128()),
                     amount1_in: Decimal::from(event.amount_1_in.as_u128()),
                     amount0_out: Decimal::from(event.amount_0_out.as_u128()),
@@ -288,9 +472,27 @@ impl SwapEventListener {
                     transaction_hash: format!("0x{:x}", log.transaction_hash.unwrap()),
                     log_index: log.log_index.unwrap().as_u32() as i32,
                     timestamp,
+                    amount0: None,
+                    amount1: None,
+                    price0: None,
+                    price1: None,
+                    side: None,
+                    volume_usd: None,
+                    fee_amount: None,
+                    fee_usd: None,
                 };
 
-                EventOperations::insert_swap_event(self.base.database.pool(), &swap_event).await?;
+                if self.store_swap(&mut swap_event).await? {
+                    self.detect_transfer_tax(&swap_event).await;
+                    self.publish_whale(
+                        WhaleOperations::check_swap(
+                            self.base.database.pool(),
+                            &swap_event,
+                            self.config.whales.thresholds(&swap_event.pair_address),
+                        )
+                        .await,
+                    );
+                }
                 // WS的推送
                 send_swap_event(&self.base.event_sender, &swap_event);
 
@@ -309,11 +511,12 @@ impl SwapEventListener {
                 // Try manual parsing as fallback
                 match self.parse_swap_event_manually(&log) {
                     Ok((sender, amount0_in, amount1_in, amount0_out, amount1_out, to)) => {
-                        let swap_event = SwapEvent {
+                        let mut swap_event = SwapEvent {
                             id: Uuid::new_v4(),
                             chain_id: self.base.chain_id as i32,
                             pair_address: format!("0x{:x}", log.address),
                             sender: tx_origin,
+                            event_sender: Some(format!("0x{:x}", sender)),
                             amount0_in: Decimal::from(amount0_in.as_u128()),
                             amount1_in: Decimal::from(amount1_in.as_u128()),
                             amount0_out: Decimal::from(amount0_out.as_u128()),
@@ -323,10 +526,27 @@ impl SwapEventListener {
                             transaction_hash: format!("0x{:x}", log.transaction_hash.unwrap()),
                             log_index: log.log_index.unwrap().as_u32() as i32,
                             timestamp,
+                            amount0: None,
+                            amount1: None,
+                            price0: None,
+                            price1: None,
+                            side: None,
+                            volume_usd: None,
+                            fee_amount: None,
+                            fee_usd: None,
                         };
 
-                        EventOperations::insert_swap_event(self.base.database.pool(), &swap_event)
-                            .await?;
+                        if self.store_swap(&mut swap_event).await? {
+                            self.detect_transfer_tax(&swap_event).await;
+                            self.publish_whale(
+                                WhaleOperations::check_swap(
+                                    self.base.database.pool(),
+                                    &swap_event,
+                                    self.config.whales.thresholds(&swap_event.pair_address),
+                                )
+                                .await,
+                            );
+                        }
                         // WS的推送
                         send_swap_event(&self.base.event_sender, &swap_event);
 
@@ -361,20 +581,31 @@ impl SwapEventListener {
             data: log.data.0.to_vec(),
         })?;
 
-        let mint_event = MintEvent {
+        let mut mint_event = MintEvent {
             id: Uuid::new_v4(),
             chain_id: self.base.chain_id as i32,
             pair_address: format!("0x{:x}", log.address),
             sender: tx_origin,
+            event_sender: Some(format!("0x{:x}", event.sender)),
             amount0: Decimal::from(event.amount_0.as_u128()),
             amount1: Decimal::from(event.amount_1.as_u128()),
             block_number: log.block_number.unwrap().as_u64() as i64,
             transaction_hash: format!("0x{:x}", log.transaction_hash.unwrap()),
             log_index: log.log_index.unwrap().as_u32() as i32,
             timestamp,
+            value_usd: None,
         };
 
-        EventOperations::insert_mint_event(self.base.database.pool(), &mint_event).await?;
+        if self.store_mint(&mut mint_event).await? {
+            self.publish_whale(
+                WhaleOperations::check_mint(
+                    self.base.database.pool(),
+                    &mint_event,
+                    self.config.whales.thresholds(&mint_event.pair_address),
+                )
+                .await,
+            );
+        }
         // WS 推送
         send_liquidity_event(
             &self.base.event_sender,
@@ -405,11 +636,12 @@ impl SwapEventListener {
             data: log.data.0.to_vec(),
         })?;
 
-        let burn_event = BurnEvent {
+        let mut burn_event = BurnEvent {
             id: Uuid::new_v4(),
             chain_id: self.base.chain_id as i32,
             pair_address: format!("0x{:x}", log.address),
             sender: tx_origin,
+            event_sender: Some(format!("0x{:x}", event.sender)),
             amount0: Decimal::from(event.amount_0.as_u128()),
             amount1: Decimal::from(event.amount_1.as_u128()),
             to_address: format!("0x{:x}", event.to),
@@ -417,9 +649,34 @@ impl SwapEventListener {
             transaction_hash: format!("0x{:x}", log.transaction_hash.unwrap()),
             log_index: log.log_index.unwrap().as_u32() as i32,
             timestamp,
+            value_usd: None,
         };
 
-        EventOperations::insert_burn_event(self.base.database.pool(), &burn_event).await?;
+        if self.store_burn(&mut burn_event).await? {
+            self.publish_whale(
+                WhaleOperations::check_burn(
+                    self.base.database.pool(),
+                    &burn_event,
+                    self.config.whales.thresholds(&burn_event.pair_address),
+                )
+                .await,
+            );
+
+            // 告警失败不影响事件处理
+            match AlertOperations::check_large_burn(self.base.database.pool(), &burn_event, &self.config.alerts)
+                .await
+            {
+                Ok(Some(alert)) => {
+                    warn!(
+                        "🚨 链 {} (交换): 交易对 {} 大额撤池 - {}",
+                        self.base.chain_id, alert.pair_address, alert.message
+                    );
+                    send_alert_event(&self.base.event_sender, &alert);
+                }
+                Ok(None) => {}
+                Err(e) => error!("❌ 链 {} (交换): 大额撤池检测失败: {}", self.base.chain_id, e),
+            }
+        }
         // WS 推送
         send_liquidity_event(
             &self.base.event_sender,
@@ -441,6 +698,206 @@ impl SwapEventListener {
         Ok(())
     }
 
+    /// 推送新标记的巨鲸交易，检测失败不影响事件处理
+    /// 在同一事务中写入 swap 及其聚合（K线、钱包日聚合、5 分钟活跃度），任一步失败整体回滚，
+    /// 重试时事件仍视为新记录，聚合不会丢失
+    async fn store_swap(&self, swap_event: &mut SwapEvent) -> Result<bool> {
+        let mut tx = self.base.database.pool().begin().await?;
+        if !EventOperations::insert_swap_event(&mut tx, swap_event).await? {
+            return Ok(false);
+        }
+        CandleOperations::apply_swap(&mut tx, swap_event).await?;
+        LeaderboardOperations::apply_swap(&mut tx, swap_event).await?;
+        ActivityOperations::apply_swap(&mut tx, swap_event).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// 在同一事务中写入 mint 并累加钱包日聚合
+    async fn store_mint(&self, mint_event: &mut MintEvent) -> Result<bool> {
+        let mut tx = self.base.database.pool().begin().await?;
+        if !EventOperations::insert_mint_event(&mut tx, mint_event).await? {
+            return Ok(false);
+        }
+        LeaderboardOperations::apply_mint(&mut tx, mint_event).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// 在同一事务中写入 burn 并累加钱包日聚合
+    async fn store_burn(&self, burn_event: &mut BurnEvent) -> Result<bool> {
+        let mut tx = self.base.database.pool().begin().await?;
+        if !EventOperations::insert_burn_event(&mut tx, burn_event).await? {
+            return Ok(false);
+        }
+        LeaderboardOperations::apply_burn(&mut tx, burn_event).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    fn publish_whale(&self, result: Result<Option<WhaleTrade>>) {
+        match result {
+            Ok(Some(trade)) => {
+                let label = match trade.event_type.as_str() {
+                    "mint" => "添加流动性",
+                    "burn" => "移除流动性",
+                    _ => "交换",
+                };
+                info!(
+                    "🐋 链 {} ({}): 交易对 {} 巨鲸 {} - 钱包 {}, 价值 ${}, 占储备 {}%",
+                    self.base.chain_id,
+                    label,
+                    trade.pair_address,
+                    trade.event_type,
+                    trade.wallet,
+                    trade.value_usd.map_or("-".to_string(), |v| v.to_string()),
+                    trade.reserve_pct.map_or("-".to_string(), |v| v.to_string())
+                );
+                send_whale_event(&self.base.event_sender, &trade);
+            }
+            Ok(None) => {}
+            Err(e) => error!("❌ 链 {} (交换): 巨鲸交易检测失败: {}", self.base.chain_id, e),
+        }
+    }
+
+    /// 检测风险代币的转账税，失败不影响 swap 写入
+    async fn detect_transfer_tax(&self, swap: &SwapEvent) {
+        if let Err(e) = self.try_detect_transfer_tax(swap).await {
+            debug!(
+                "链 {} (交换): 交易 {} 转账税检测失败: {}",
+                self.base.chain_id, swap.transaction_hash, e
+            );
+        }
+    }
+
+    /// 比对 Swap 数量与同一交易中风险代币的 Transfer 日志：
+    /// - 买入（代币从交易对转出）：税率 = 1 - 接收方实际收到 / amountOut
+    /// - 卖出（代币转入交易对）：税率 = 转入方同时转给其他地址的数量 / (该数量 + 交易对收到的数量)
+    ///   只统计上一个 swap / mint / burn 日志之后、本 Swap 之前的 Transfer。
+    ///   同一代币既有转入又有转出说明储备量之外的余额被一并计入，记为一次 rebase 漂移
+    async fn try_detect_transfer_tax(&self, swap: &SwapEvent) -> Result<()> {
+        let pool = self.base.database.pool();
+        let target = match TaxOperations::get_tax_target(pool, swap.chain_id, &swap.pair_address, &self.config.tax)
+            .await?
+        {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+
+        let (amount_in, amount_out) = if target.token_index == 0 {
+            (swap.amount0_in, swap.amount0_out)
+        } else {
+            (swap.amount1_in, swap.amount1_out)
+        };
+        if amount_in > Decimal::ZERO && amount_out > Decimal::ZERO {
+            TaxOperations::record_rebase_event(pool, swap.chain_id, &target.token_address).await?;
+            return Ok(());
+        }
+        if !target.due || (amount_in.is_zero() && amount_out.is_zero()) {
+            return Ok(());
+        }
+
+        let tx_hash: H256 = swap.transaction_hash.parse()?;
+        let receipt = match self.base.provider.get_transaction_receipt(tx_hash).await? {
+            Some(receipt) => receipt,
+            None => return Ok(()),
+        };
+
+        let pair: Address = swap.pair_address.parse()?;
+        let token: Address = target.token_address.parse()?;
+        let recipient: Address = swap.to_address.parse()?;
+        let swap_log_index = swap.log_index as u64;
+
+        let swap_signature = H256::from(keccak256(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+        ));
+        let mint_signature = H256::from(keccak256("Mint(address,uint256,uint256)"));
+        let burn_signature = H256::from(keccak256("Burn(address,uint256,uint256,address)"));
+        let transfer_signature = H256::from(keccak256("Transfer(address,address,uint256)"));
+
+        let log_index = |log: &Log| log.log_index.map(|i| i.as_u64()).unwrap_or(0);
+        let window_start = receipt
+            .logs
+            .iter()
+            .filter(|log| {
+                log.address == pair
+                    && log_index(log) < swap_log_index
+                    && matches!(log.topics.first(), Some(t) if *t == swap_signature || *t == mint_signature || *t == burn_signature)
+            })
+            .map(|log| log_index(log) + 1)
+            .max()
+            .unwrap_or(0);
+
+        // (from, to, value)
+        let transfers: Vec<(Address, Address, U256)> = receipt
+            .logs
+            .iter()
+            .filter(|log| {
+                log.address == token
+                    && log.topics.len() == 3
+                    && log.topics[0] == transfer_signature
+                    && log_index(log) >= window_start
+                    && log_index(log) < swap_log_index
+            })
+            .map(|log| {
+                (
+                    Address::from(log.topics[1]),
+                    Address::from(log.topics[2]),
+                    U256::from_big_endian(&log.data),
+                )
+            })
+            .collect();
+
+        let (direction, taxed, total) = if amount_out > Decimal::ZERO {
+            let sent = U256::from_dec_str(&amount_out.to_string())?;
+            let received = transfers
+                .iter()
+                .filter(|(from, to, _)| *from == pair && *to == recipient)
+                .fold(U256::zero(), |acc, (_, _, value)| acc.saturating_add(*value));
+            if received.is_zero() {
+                return Ok(());
+            }
+            ("buy", sent.saturating_sub(received), sent)
+        } else {
+            let senders: HashSet<Address> = transfers
+                .iter()
+                .filter(|(_, to, _)| *to == pair)
+                .map(|(from, _, _)| *from)
+                .collect();
+            if senders.is_empty() {
+                return Ok(());
+            }
+            let to_pair = transfers
+                .iter()
+                .filter(|(_, to, _)| *to == pair)
+                .fold(U256::zero(), |acc, (_, _, value)| acc.saturating_add(*value));
+            let tax = transfers
+                .iter()
+                .filter(|(from, to, _)| senders.contains(from) && *to != pair && to != from)
+                .fold(U256::zero(), |acc, (_, _, value)| acc.saturating_add(*value));
+            ("sell", tax, tax.saturating_add(to_pair))
+        };
+        if total.is_zero() {
+            return Ok(());
+        }
+
+        // 税率保留 6 位小数
+        let rate_micros = taxed.min(total).saturating_mul(U256::from(1_000_000)) / total;
+        let rate = Decimal::new(rate_micros.as_u64() as i64, 6);
+
+        TaxOperations::record_tax_sample(pool, swap.chain_id, &target.token_address, direction, rate)
+            .await?;
+        if rate > Decimal::ZERO {
+            TaxOperations::mark_swap_tax(pool, swap, rate).await?;
+            debug!(
+                "🧾 链 {} (交换): 代币 {} {} 转账税 {}",
+                self.base.chain_id, target.token_address, direction, rate
+            );
+        }
+
+        Ok(())
+    }
+
     fn parse_swap_event_manually(
         &self,
         log: &Log,
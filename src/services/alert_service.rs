@@ -0,0 +1,81 @@
+use crate::api::websocket::send_alert_event;
+use crate::config::AlertConfig;
+use crate::database::operations::{AlertOperations, NewAlert};
+use crate::database::Database;
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time;
+use tracing::{error, info, warn};
+
+/// 跑路告警服务：定期检查计价代币储备骤降和 LP 集中在首个流动性提供者手中的交易对。
+/// 单笔大额撤池由 swap 监听器在写入 burn 时即时检测
+pub struct AlertService {
+    database: Arc<Database>,
+    config: AlertConfig,
+    chain_ids: Vec<i32>,
+    event_sender: broadcast::Sender<String>,
+}
+
+impl AlertService {
+    pub fn new(
+        database: Arc<Database>,
+        config: AlertConfig,
+        chain_ids: Vec<i32>,
+        event_sender: broadcast::Sender<String>,
+    ) -> Self {
+        Self {
+            database,
+            config,
+            chain_ids,
+            event_sender,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🚨 启动跑路告警服务，间隔: {}s, 储备窗口: {} 分钟",
+            self.config.interval_secs, self.config.reserve_window_mins
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for chain_id in &self.chain_ids {
+                if let Err(e) = self.run_chain(*chain_id).await {
+                    error!("❌ 链 {} 跑路告警检测失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    pub async fn run_chain(&self, chain_id: i32) -> Result<()> {
+        let pool = self.database.pool();
+
+        let mut candidates =
+            AlertOperations::detect_reserve_drops(pool, chain_id, &self.config).await?;
+        candidates.extend(AlertOperations::detect_lp_concentration(pool, chain_id, &self.config).await?);
+
+        for candidate in &candidates {
+            self.raise(candidate).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 写入并推送，重复告警被 dedup_key 去重后不再推送
+    async fn raise(&self, candidate: &NewAlert) -> Result<()> {
+        if let Some(alert) = AlertOperations::insert_alert(self.database.pool(), candidate).await? {
+            warn!(
+                "🚨 链 {} 交易对 {} {}: {}",
+                alert.chain_id, alert.pair_address, alert.alert_type, alert.message
+            );
+            send_alert_event(&self.event_sender, &alert);
+        }
+
+        Ok(())
+    }
+}
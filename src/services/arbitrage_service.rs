@@ -0,0 +1,285 @@
+use super::supply_service::normalize_units;
+use super::swap_quote_service::{get_amount_out, price_impact, Pool};
+use crate::config::ArbitrageConfig;
+use crate::database::operations::{ArbitrageOperations, NewArbitrageOpportunity, RouteOperations};
+use crate::types::{ArbitrageOpportunity, SwapQuoteHop};
+use anyhow::Result;
+use ethers::types::U256;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+
+/// 三分搜索最优投入的迭代上限，区间每轮缩小到 2/3，足以覆盖 uint112 储备量
+const SIZE_SEARCH_ITERATIONS: usize = 256;
+
+/// 环路中的一跳：(交易对下标, 输入代币)
+type Step<'a> = (usize, &'a str);
+
+/// 循环套利检测：在交易对图上寻找经过指定交易对的环路，按已记录的储备量模拟 V2 兑换，
+/// 以三分搜索求使利润最大的投入
+pub struct ArbitrageService;
+
+impl ArbitrageService {
+    /// 扫描经过 `touched_pairs`（小写地址）的环路，写入并返回新记录的套利机会。
+    /// 环路不重复经过同一代币，以环中第一个有美元价格的代币为起点，没有美元价格的环路跳过；
+    /// 利润已扣除各跳手续费和价格冲击，未扣除 gas
+    pub async fn scan(
+        pool: &PgPool,
+        chain_id: i32,
+        touched_pairs: &[String],
+        block_number: i64,
+        config: &ArbitrageConfig,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        if touched_pairs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let route_pools = RouteOperations::get_route_pools(
+            pool,
+            chain_id,
+            Some(Decimal::from(config.min_liquidity_usd)),
+        )
+        .await?;
+
+        let mut prices: HashMap<String, Decimal> = HashMap::new();
+        for p in &route_pools {
+            if let Some(price) = p.token0_price_usd.filter(|price| *price > Decimal::ZERO) {
+                prices.insert(p.token0.clone(), price);
+            }
+            if let Some(price) = p.token1_price_usd.filter(|price| *price > Decimal::ZERO) {
+                prices.insert(p.token1.clone(), price);
+            }
+        }
+        let pools: Vec<Pool> = route_pools.iter().filter_map(Pool::parse).collect();
+
+        let mut stored = Vec::new();
+        for cycle in find_cycles(&pools, touched_pairs, config.max_hops) {
+            let opportunity = match evaluate(chain_id, block_number, &pools, &prices, &cycle) {
+                Some(opportunity) if opportunity.profit_usd >= config.min_profit_usd => opportunity,
+                _ => continue,
+            };
+            if let Some(opportunity) = ArbitrageOperations::insert_opportunity(pool, &opportunity).await? {
+                stored.push(opportunity);
+            }
+        }
+
+        stored.sort_by_key(|o| std::cmp::Reverse(o.profit_usd));
+        Ok(stored)
+    }
+}
+
+/// 枚举经过 touched 交易对、最多 `max_hops` 跳的有向环路。
+/// 同一环路从不同代币出发只保留一次：旋转到下标最小的交易对开头作为去重键，方向不同视为不同环路
+fn find_cycles<'a>(pools: &'a [Pool], touched_pairs: &[String], max_hops: usize) -> Vec<Vec<Step<'a>>> {
+    let mut adjacency: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut by_tokens: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (i, p) in pools.iter().enumerate() {
+        adjacency.entry(p.token0.as_str()).or_default().push(i);
+        adjacency.entry(p.token1.as_str()).or_default().push(i);
+        by_tokens.entry(token_key(&p.token0, &p.token1)).or_default().push(i);
+    }
+
+    let touched: HashSet<&str> = touched_pairs.iter().map(|p| p.as_str()).collect();
+    let mut found = Vec::new();
+    for (i, p) in pools.iter().enumerate() {
+        if !touched.contains(p.address.as_str()) {
+            continue;
+        }
+        for start in [p.token0.as_str(), p.token1.as_str()] {
+            let (next, _, _) = p.sides(start);
+            let mut path = vec![i];
+            let mut tokens = vec![start, next];
+            extend(pools, &adjacency, &by_tokens, max_hops, &mut path, &mut tokens, &mut found);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    found
+        .into_iter()
+        .filter_map(|(path, tokens)| {
+            let mut steps: Vec<Step> = path.into_iter().zip(tokens).collect();
+            let first = steps.iter().enumerate().min_by_key(|(_, (pool, _))| *pool)?.0;
+            steps.rotate_left(first);
+            seen.insert(steps.clone()).then_some(steps)
+        })
+        .collect()
+}
+
+/// 深度优先延伸路径：能直接回到起点时记录环路，仍有余量时再经过一个新代币
+#[allow(clippy::type_complexity)]
+fn extend<'a>(
+    pools: &'a [Pool],
+    adjacency: &HashMap<&'a str, Vec<usize>>,
+    by_tokens: &HashMap<(&'a str, &'a str), Vec<usize>>,
+    max_hops: usize,
+    path: &mut Vec<usize>,
+    tokens: &mut Vec<&'a str>,
+    found: &mut Vec<(Vec<usize>, Vec<&'a str>)>,
+) {
+    let start = tokens[0];
+    let current = tokens[tokens.len() - 1];
+
+    if path.len() < max_hops {
+        for &j in by_tokens.get(&token_key(current, start)).into_iter().flatten() {
+            if !path.contains(&j) {
+                let mut cycle = path.clone();
+                cycle.push(j);
+                found.push((cycle, tokens.clone()));
+            }
+        }
+    }
+
+    if path.len() + 1 < max_hops {
+        for &j in adjacency.get(current).into_iter().flatten() {
+            if path.contains(&j) {
+                continue;
+            }
+            let (next, _, _) = pools[j].sides(current);
+            if tokens.contains(&next) {
+                continue;
+            }
+            path.push(j);
+            tokens.push(next);
+            extend(pools, adjacency, by_tokens, max_hops, path, tokens, found);
+            path.pop();
+            tokens.pop();
+        }
+    }
+}
+
+fn token_key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// 先用扣除手续费后的边际汇率乘积过滤（不大于 1 时任何投入都亏损），
+/// 利润函数 out(x) - x 为凹函数，在 [0, 首跳输入储备量] 上三分搜索最优投入
+fn evaluate(
+    chain_id: i32,
+    block_number: i64,
+    pools: &[Pool],
+    prices: &HashMap<String, Decimal>,
+    cycle: &[Step],
+) -> Option<NewArbitrageOpportunity> {
+    let start = cycle.iter().position(|(_, token)| prices.contains_key(*token))?;
+    let mut steps = cycle.to_vec();
+    steps.rotate_left(start);
+    let start_token = steps[0].1;
+
+    let rate = steps.iter().fold(1.0, |rate, (i, token_in)| {
+        let pool = &pools[*i];
+        let (_, reserve_in, reserve_out) = pool.sides(token_in);
+        rate * u256_to_f64(reserve_out) / u256_to_f64(reserve_in) * (10_000 - pool.fee_bps) as f64 / 10_000.0
+    });
+    if rate <= 1.0 {
+        return None;
+    }
+
+    let gain = |amount_in: U256| {
+        simulate(pools, &steps, amount_in)
+            .and_then(|amounts| amounts.last().copied())
+            .map(|amount_out| amount_out.saturating_sub(amount_in))
+            .unwrap_or_default()
+    };
+
+    let (_, first_reserve_in, _) = pools[steps[0].0].sides(start_token);
+    let (mut lo, mut hi) = (U256::zero(), first_reserve_in);
+    for _ in 0..SIZE_SEARCH_ITERATIONS {
+        if hi - lo <= U256::from(2) {
+            break;
+        }
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - third);
+        if gain(m1) < gain(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    let mut best = lo;
+    let mut x = lo;
+    while x < hi {
+        x += U256::one();
+        if gain(x) > gain(best) {
+            best = x;
+        }
+    }
+
+    let amounts = simulate(pools, &steps, best)?;
+    let amount_out_raw = *amounts.last()?;
+    if amount_out_raw <= best {
+        return None;
+    }
+
+    let decimals = pools[steps[0].0].decimals(start_token);
+    let amount_in = normalize_units(best, decimals)?;
+    let amount_out = normalize_units(amount_out_raw, decimals)?;
+    let profit = normalize_units(amount_out_raw - best, decimals)?;
+    let price_usd = prices[start_token];
+
+    let mut spread = Decimal::ONE;
+    let mut route = Vec::with_capacity(steps.len());
+    for (k, (i, token_in)) in steps.iter().enumerate() {
+        let pool = &pools[*i];
+        let (token_out, reserve_in, reserve_out) = pool.sides(token_in);
+        let decimals_in = pool.decimals(token_in);
+        let decimals_out = pool.decimals(token_out);
+
+        let hop_mid = normalize_units(reserve_out, decimals_out)?
+            .checked_div(normalize_units(reserve_in, decimals_in)?)?;
+        let hop_fee = Decimal::ONE - Decimal::new(pool.fee_bps as i64, 4);
+        let hop_in = normalize_units(amounts[k], decimals_in)?;
+        let hop_out = normalize_units(amounts[k + 1], decimals_out)?;
+
+        spread = spread.checked_mul(hop_mid)?.checked_mul(hop_fee)?;
+        route.push(SwapQuoteHop {
+            pair_address: pool.address.clone(),
+            token_in: token_in.to_string(),
+            token_out: token_out.to_string(),
+            amount_in: hop_in,
+            amount_out: hop_out,
+            fee_bps: pool.fee_bps as i32,
+            price_impact_pct: price_impact(hop_in, hop_out, hop_mid * hop_fee),
+        });
+    }
+
+    Some(NewArbitrageOpportunity {
+        chain_id,
+        block_number,
+        start_token: start_token.to_string(),
+        pairs: route.iter().map(|hop| hop.pair_address.clone()).collect(),
+        amount_in,
+        amount_out,
+        profit,
+        profit_usd: profit.checked_mul(price_usd)?.round_dp(8),
+        amount_in_usd: amount_in.checked_mul(price_usd)?.round_dp(8),
+        spread_pct: ((spread - Decimal::ONE) * Decimal::ONE_HUNDRED).round_dp(4),
+        return_pct: (profit.checked_div(amount_in)? * Decimal::ONE_HUNDRED).round_dp(4),
+        route,
+    })
+}
+
+/// 沿环路依次兑换，返回每跳之前的数量和最终输出
+fn simulate(pools: &[Pool], steps: &[Step], amount_in: U256) -> Option<Vec<U256>> {
+    let mut amounts = Vec::with_capacity(steps.len() + 1);
+    amounts.push(amount_in);
+    let mut amount = amount_in;
+    for (i, token_in) in steps {
+        let pool = &pools[*i];
+        let (_, reserve_in, reserve_out) = pool.sides(token_in);
+        amount = get_amount_out(amount, reserve_in, reserve_out, pool.fee_bps)?;
+        amounts.push(amount);
+    }
+    Some(amounts)
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    value
+        .0
+        .iter()
+        .rev()
+        .fold(0.0, |acc, limb| acc * 18_446_744_073_709_551_616.0 + *limb as f64)
+}
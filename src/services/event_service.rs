@@ -1,9 +1,22 @@
-use crate::{config::Config, database::Database, event_listener::EventListenerManager};
+use crate::{
+    config::Config,
+    database::Database,
+    event_listener::{EventListenerManager, SwapListenerConfig},
+};
+use crate::database::operations::{FeeOperations, QuoteOperations, RouterOperations};
+use super::risk_service::RiskChain;
+use super::supply_service::SupplyChain;
+use super::{
+    AlertService, MaintenanceService, MevService, RiskService, SupplyService, TradeService,
+    TwapService, ValuationService,
+};
 use anyhow::Result;
+use ethers::providers::{Http, Provider};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::broadcast;
-use tracing::info;
+use tracing::{info, warn};
 
 pub struct EventService {
     config: Config,
@@ -29,12 +42,143 @@ impl EventService {
     pub async fn start(&self) -> Result<()> {
         info!("🚀 启动事件监听服务...");
 
-        let listener_manager = EventListenerManager::new(
+        let listener_manager = EventListenerManager::new(Arc::clone(&self.database), self.event_sender.clone());
+
+        let mut handles = Vec::new();
+
+        let maintenance = MaintenanceService::new(
             Arc::clone(&self.database),
-            self.event_sender.clone(),
+            self.config.maintenance.clone(),
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = maintenance.start().await {
+                tracing::error!("Maintenance service error: {}", e);
+            }
+        }));
+
+        let stable_tokens: HashMap<i32, Vec<String>> = self
+            .config
+            .chains
+            .iter()
+            .filter(|(_, c)| c.enabled)
+            .map(|(chain_id, c)| (*chain_id as i32, c.stable_tokens.clone()))
+            .collect();
+        let valuation = ValuationService::new(
+            Arc::clone(&self.database),
+            self.config.valuation.clone(),
+            self.config.trending.clone(),
+            stable_tokens,
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = valuation.start().await {
+                tracing::error!("Valuation service error: {}", e);
+            }
+        }));
+
+        let mut supply_chains: HashMap<i32, SupplyChain> = HashMap::new();
+        let mut risk_chains: HashMap<i32, RiskChain> = HashMap::new();
+        let mut twap_providers: HashMap<i32, Arc<Provider<Http>>> = HashMap::new();
+        for (chain_id, c) in self.config.chains.iter().filter(|(_, c)| c.enabled) {
+            match Provider::<Http>::try_from(c.rpc_url.as_str()) {
+                Ok(provider) => {
+                    let provider = Arc::new(provider);
+                    twap_providers.insert(*chain_id as i32, Arc::clone(&provider));
+                    supply_chains.insert(
+                        *chain_id as i32,
+                        SupplyChain {
+                            provider: Arc::clone(&provider),
+                            excluded_addresses: c.supply_excluded_addresses.clone(),
+                        },
+                    );
+                    risk_chains.insert(
+                        *chain_id as i32,
+                        RiskChain {
+                            provider,
+                            locked_addresses: c
+                                .supply_excluded_addresses
+                                .iter()
+                                .chain(c.lp_locker_addresses.iter())
+                                .cloned()
+                                .collect(),
+                        },
+                    );
+                }
+                Err(e) => warn!("链 {} RPC 地址无效，跳过供应量、风险评分和 TWAP 采样: {}", chain_id, e),
+            }
+        }
+        let supply = SupplyService::new(
+            Arc::clone(&self.database),
+            self.config.supply.clone(),
+            supply_chains,
         );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = supply.start().await {
+                tracing::error!("Supply service error: {}", e);
+            }
+        }));
 
-        let mut handles = Vec::new();
+        let risk = RiskService::new(
+            Arc::clone(&self.database),
+            self.config.risk.clone(),
+            risk_chains,
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = risk.start().await {
+                tracing::error!("Risk service error: {}", e);
+            }
+        }));
+
+        let twap = TwapService::new(
+            Arc::clone(&self.database),
+            self.config.twap.clone(),
+            twap_providers,
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = twap.start().await {
+                tracing::error!("TWAP service error: {}", e);
+            }
+        }));
+
+        let enabled_chains: Vec<i32> = self
+            .config
+            .chains
+            .iter()
+            .filter(|(_, c)| c.enabled)
+            .map(|(chain_id, _)| *chain_id as i32)
+            .collect();
+        let trades = TradeService::new(
+            Arc::clone(&self.database),
+            self.config.trades.clone(),
+            enabled_chains.clone(),
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = trades.start().await {
+                tracing::error!("Trade service error: {}", e);
+            }
+        }));
+
+        let mev = MevService::new(
+            Arc::clone(&self.database),
+            self.config.mev.clone(),
+            enabled_chains.clone(),
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = mev.start().await {
+                tracing::error!("MEV service error: {}", e);
+            }
+        }));
+
+        let alerts = AlertService::new(
+            Arc::clone(&self.database),
+            self.config.alerts.clone(),
+            enabled_chains,
+            self.event_sender.clone(),
+        );
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = alerts.start().await {
+                tracing::error!("Alert service error: {}", e);
+            }
+        }));
 
         for (chain_id, chain_config) in &self.config.chains {
             if !chain_config.enabled {
@@ -42,14 +186,46 @@ impl EventService {
                 continue;
             }
 
+            QuoteOperations::sync_quote_tokens(
+                self.database.pool(),
+                *chain_id as i32,
+                &chain_config.quote_tokens,
+            )
+            .await?;
+            FeeOperations::sync_factory_fee(
+                self.database.pool(),
+                *chain_id as i32,
+                &chain_config.factory_address,
+                chain_config.swap_fee_bps as i32,
+            )
+            .await?;
+            RouterOperations::sync_known_routers(
+                self.database.pool(),
+                *chain_id as i32,
+                &chain_config.router_addresses,
+            )
+            .await?;
+
             info!("Starting monitoring for chain {} ({})", chain_id, chain_config.name);
 
             let manager = listener_manager.clone();
             let chain_id = *chain_id;
             let config = chain_config.clone();
+            let swap_listener_config = SwapListenerConfig {
+                poll_interval: chain_config.poll_interval,
+                start_block: chain_config.start_block,
+                block_batch_size: chain_config.block_batch_size,
+                alerts: self.config.alerts.clone(),
+                tax: self.config.tax.clone(),
+                arbitrage: self.config.arbitrage.clone(),
+                whales: chain_config.whales.clone(),
+            };
 
             let handle = tokio::spawn(async move {
-                if let Err(e) = manager.start_chain_listeners(chain_id, &config).await {
+                if let Err(e) = manager
+                    .start_chain_listeners(chain_id, &config, swap_listener_config)
+                    .await
+                {
                     tracing::error!("Event listener error for chain {}: {}", chain_id, e);
                 }
             });
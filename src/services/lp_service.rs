@@ -0,0 +1,102 @@
+use crate::database::operations::{WalletLiquidityAggregate, WalletOperations};
+use crate::types::WalletLiquidityPosition;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// LP 头寸分析：根据钱包的 mint / burn 历史和池子当前储备，估算头寸现值、
+/// 相对原样持有的无常损失以及按份额分摊的手续费收入
+pub struct LpService;
+
+impl LpService {
+    /// 钱包的 LP 头寸，`include_closed` 为 false 时只返回尚未全部取出的头寸。
+    /// mint / burn 均按交易发起人归属：Mint 事件没有接收方，无法按接收方配对存入和取出
+    pub async fn wallet_positions(
+        pool: &PgPool,
+        wallet_address: &str,
+        chain_id: Option<i32>,
+        include_closed: bool,
+    ) -> Result<Vec<WalletLiquidityPosition>> {
+        let aggregates =
+            WalletOperations::get_wallet_liquidity_aggregates(pool, wallet_address, chain_id).await?;
+
+        let mut positions: Vec<WalletLiquidityPosition> = aggregates
+            .iter()
+            .map(Self::evaluate)
+            .filter(|p| include_closed || p.status == "open")
+            .collect();
+        positions.sort_by(|a, b| {
+            b.position_value_usd
+                .unwrap_or(Decimal::ZERO)
+                .cmp(&a.position_value_usd.unwrap_or(Decimal::ZERO))
+        });
+
+        Ok(positions)
+    }
+
+    /// V2 池子中流动性 L = sqrt(x * y)，价格 P = y / x 时头寸数量为 (L / sqrt(P), L * sqrt(P))。
+    /// 取出时的流动性同样按 sqrt(x * y) 计，手续费累积会让取出量略偏大，剩余头寸因此偏保守
+    fn evaluate(agg: &WalletLiquidityAggregate) -> WalletLiquidityPosition {
+        let net_liquidity = (agg.liquidity_added - agg.liquidity_removed).max(Decimal::ZERO);
+        let remaining = if agg.liquidity_added > Decimal::ZERO {
+            net_liquidity / agg.liquidity_added
+        } else {
+            Decimal::ZERO
+        };
+        let open = remaining > Decimal::new(1, 6);
+        let net_liquidity = if open { net_liquidity } else { Decimal::ZERO };
+
+        let (current0, current1) = match agg.sqrt_price {
+            Some(sqrt_price) if open && sqrt_price > Decimal::ZERO => {
+                (net_liquidity / sqrt_price, net_liquidity * sqrt_price)
+            }
+            _ => (Decimal::ZERO, Decimal::ZERO),
+        };
+        let share = match agg.pool_liquidity {
+            Some(pool_liquidity) if pool_liquidity > Decimal::ZERO => {
+                (net_liquidity / pool_liquidity).min(Decimal::ONE)
+            }
+            _ => Decimal::ZERO,
+        };
+
+        // 对照组：剩余份额对应的存入代币原样持有
+        let hold0 = if open { agg.deposited0 * remaining } else { Decimal::ZERO };
+        let hold1 = if open { agg.deposited1 * remaining } else { Decimal::ZERO };
+
+        let value = |amount0: Decimal, amount1: Decimal| match (agg.token0_price_usd, agg.token1_price_usd) {
+            (Some(p0), Some(p1)) => Some(amount0 * p0 + amount1 * p1),
+            _ => None,
+        };
+        let position_value = value(current0, current1);
+        let hold_value = value(hold0, hold1);
+        let impermanent_loss = position_value.zip(hold_value).map(|(lp, hold)| lp - hold);
+        let impermanent_loss_pct = impermanent_loss.zip(hold_value).and_then(|(il, hold)| {
+            (hold > Decimal::ZERO).then(|| (il * Decimal::ONE_HUNDRED / hold).round_dp(4))
+        });
+        let fees_earned = (agg.pool_fees_usd * share).round_dp(6);
+
+        WalletLiquidityPosition {
+            chain_id: agg.chain_id,
+            pair_address: agg.pair_address.clone(),
+            token0: agg.token0.clone(),
+            token1: agg.token1.clone(),
+            token0_symbol: agg.token0_symbol.clone(),
+            token1_symbol: agg.token1_symbol.clone(),
+            status: if open { "open" } else { "closed" }.to_string(),
+            first_deposit_at: agg.first_deposit_at,
+            deposited0: agg.deposited0,
+            deposited1: agg.deposited1,
+            withdrawn0: agg.withdrawn0,
+            withdrawn1: agg.withdrawn1,
+            pool_share: (share * Decimal::ONE_HUNDRED).round_dp(6),
+            current_amount0: current0.round_dp(18),
+            current_amount1: current1.round_dp(18),
+            position_value_usd: position_value.map(|v| v.round_dp(6)),
+            hold_value_usd: hold_value.map(|v| v.round_dp(6)),
+            impermanent_loss_usd: impermanent_loss.map(|v| v.round_dp(6)),
+            impermanent_loss_pct,
+            fees_earned_usd: fees_earned,
+            net_vs_hold_usd: impermanent_loss.map(|il| (il + fees_earned).round_dp(6)),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::config::MaintenanceConfig;
+use crate::database::operations::{
+    ActivityOperations, ArbitrageOperations, CandleOperations, PartitionOperations,
+    SystemOperations,
+};
+use crate::database::Database;
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{error, info};
+
+/// 每批回填K线的交易对数量
+const CANDLE_BACKFILL_BATCH: i64 = 100;
+
+/// 数据库维护服务：预建分区、回填K线、按保留策略删除过期分区、分区感知的 VACUUM
+pub struct MaintenanceService {
+    database: Arc<Database>,
+    config: MaintenanceConfig,
+}
+
+impl MaintenanceService {
+    pub fn new(database: Arc<Database>, config: MaintenanceConfig) -> Self {
+        Self { database, config }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🧹 启动数据库维护服务，间隔: {}s, 事件保留: {} 天",
+            self.config.interval_secs, self.config.event_retention_days
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.run_once().await {
+                error!("❌ 数据库维护失败: {}", e);
+            }
+        }
+    }
+
+    pub async fn run_once(&self) -> Result<()> {
+        let pool = self.database.pool();
+
+        PartitionOperations::premake_partitions(pool, self.config.partition_premake_months)
+            .await?;
+
+        // 回填必须在删除过期分区之前，保证历史 swap 先汇总进K线
+        let mut backfilled = 0;
+        loop {
+            let batch = CandleOperations::backfill_missing(pool, CANDLE_BACKFILL_BATCH).await?;
+            backfilled += batch;
+            if batch < CANDLE_BACKFILL_BATCH as usize {
+                break;
+            }
+        }
+        if backfilled > 0 {
+            info!("🕯️ 本轮回填 {} 个交易对的K线", backfilled);
+        }
+
+        let dropped =
+            PartitionOperations::drop_expired_partitions(pool, self.config.event_retention_days)
+                .await?;
+        if !dropped.is_empty() {
+            info!("🗑️ 共删除 {} 个过期分区", dropped.len());
+        }
+
+        let pruned = ActivityOperations::prune(pool).await?;
+        if pruned > 0 {
+            info!("🗑️ 删除 {} 行过期的成交活跃度聚合", pruned);
+        }
+
+        let pruned = ArbitrageOperations::prune(pool, self.config.arbitrage_retention_days).await?;
+        if pruned > 0 {
+            info!("🗑️ 删除 {} 条过期的套利机会", pruned);
+        }
+
+        SystemOperations::vacuum_analyze(pool, self.config.vacuum_hot_partitions).await?;
+
+        info!("✅ 数据库维护完成");
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use crate::config::MevConfig;
+use crate::database::operations::{
+    EventOperations, MevOperations, MevSwap, SandwichRow, TradeOperations, EVENT_TYPE_MEV,
+    EVENT_TYPE_SWAP,
+};
+use crate::database::Database;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+/// 夹子攻击检测服务：按 swap 监听进度逐段扫描已写入的区块，在同一区块同一交易对内
+/// 识别抢跑 / 尾随模式，进度记录在 last_processed_blocks 的 `mev` 类型下
+pub struct MevService {
+    database: Arc<Database>,
+    config: MevConfig,
+    chain_ids: Vec<i32>,
+}
+
+impl MevService {
+    pub fn new(database: Arc<Database>, config: MevConfig, chain_ids: Vec<i32>) -> Self {
+        Self {
+            database,
+            config,
+            chain_ids,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🥪 启动夹子攻击检测服务，间隔: {}s, 批次: {} 个区块",
+            self.config.interval_secs, self.config.batch_blocks
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for chain_id in &self.chain_ids {
+                if let Err(e) = self.run_chain(*chain_id).await {
+                    error!("❌ 链 {} 夹子攻击检测失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    /// 处理到 swap 监听已完成的区块为止
+    pub async fn run_chain(&self, chain_id: i32) -> Result<()> {
+        let pool = self.database.pool();
+        let batch_blocks = self.config.batch_blocks.max(1);
+
+        let head = EventOperations::get_last_processed_block(pool, chain_id, EVENT_TYPE_SWAP).await?;
+        let mut cursor =
+            EventOperations::get_last_processed_block(pool, chain_id, EVENT_TYPE_MEV).await?;
+
+        while cursor < head {
+            // 跳过没有 swap 的区块，避免从创世区块逐段扫描
+            let start = match TradeOperations::get_next_swap_block(pool, chain_id, cursor).await? {
+                Some(block) if block <= head => block,
+                _ => {
+                    EventOperations::update_last_processed_block(pool, chain_id, EVENT_TYPE_MEV, head)
+                        .await?;
+                    break;
+                }
+            };
+            let to_block = (start + batch_blocks - 1).min(head);
+
+            let swaps = MevOperations::get_block_swaps(pool, chain_id, cursor, to_block).await?;
+            let sandwiches = detect_sandwiches(&swaps);
+            MevOperations::replace_sandwiches(pool, chain_id, cursor, to_block, &sandwiches).await?;
+
+            if !sandwiches.is_empty() {
+                info!(
+                    "🥪 链 {} 区块 {}..={} 识别到 {} 次夹子攻击",
+                    chain_id,
+                    cursor + 1,
+                    to_block,
+                    sandwiches.len()
+                );
+            } else {
+                debug!("🥪 链 {} 区块 {}..={} 未发现夹子攻击", chain_id, cursor + 1, to_block);
+            }
+
+            cursor = to_block;
+            EventOperations::update_last_processed_block(pool, chain_id, EVENT_TYPE_MEV, cursor)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 按区块和交易对分组后逐组检测
+fn detect_sandwiches(swaps: &[MevSwap]) -> Vec<SandwichRow> {
+    let mut sandwiches = Vec::new();
+    let mut start = 0;
+
+    while start < swaps.len() {
+        let mut end = start + 1;
+        while end < swaps.len()
+            && swaps[end].block_number == swaps[start].block_number
+            && swaps[end].pair_address == swaps[start].pair_address
+        {
+            end += 1;
+        }
+        if end - start >= 3 {
+            sandwiches.extend(detect_in_pool_block(&swaps[start..end]));
+        }
+        start = end;
+    }
+
+    sandwiches
+}
+
+/// 同一区块同一交易对内按日志顺序匹配：攻击者先同向抢跑，中间至少一笔他人同向 swap，
+/// 之后攻击者反向卖出抢跑买到的代币。同一攻击者指交易发起人相同，或尾随交易的调用方就是抢跑的接收合约（MEV 机器人）。
+/// 尾随卖出数量须在抢跑买入数量的 50% ~ 101% 之间，排除普通的先买后卖
+fn detect_in_pool_block(swaps: &[MevSwap]) -> Vec<SandwichRow> {
+    let mut sandwiches = Vec::new();
+    let mut used = vec![false; swaps.len()];
+
+    for i in 0..swaps.len() {
+        if used[i] {
+            continue;
+        }
+        let front = &swaps[i];
+        if front.amount_out <= Decimal::ZERO || front.amount_in <= Decimal::ZERO {
+            continue;
+        }
+
+        let back_index = (i + 2..swaps.len()).find(|&j| {
+            let back = &swaps[j];
+            !used[j]
+                && back.side != front.side
+                && back.transaction_hash != front.transaction_hash
+                && same_attacker(front, back)
+                && back.amount_in >= front.amount_out * Decimal::new(50, 2)
+                && back.amount_in <= front.amount_out * Decimal::new(101, 2)
+        });
+        let j = match back_index {
+            Some(j) => j,
+            None => continue,
+        };
+        let back = &swaps[j];
+
+        let victims: Vec<MevSwap> = swaps[i + 1..j]
+            .iter()
+            .filter(|v| {
+                v.side == front.side
+                    && v.tx_origin != front.tx_origin
+                    && v.tx_origin != back.tx_origin
+                    && v.transaction_hash != front.transaction_hash
+                    && v.transaction_hash != back.transaction_hash
+            })
+            .cloned()
+            .collect();
+        if victims.is_empty() {
+            continue;
+        }
+
+        // 只按卖出部分对应的抢跑成本计算获利
+        let sold_ratio = back.amount_in / front.amount_out;
+        let profit_amount = back.amount_out - front.amount_in * sold_ratio;
+        if profit_amount <= Decimal::ZERO {
+            continue;
+        }
+        // 抢跑成交时投入代币的美元单价
+        let profit_usd = front
+            .volume_usd
+            .map(|volume| (volume / front.amount_in * profit_amount).round_dp(6));
+
+        used[i] = true;
+        used[j] = true;
+        sandwiches.push(SandwichRow {
+            pair_address: front.pair_address.clone(),
+            block_number: front.block_number,
+            timestamp: front.timestamp,
+            attacker: front.tx_origin.clone(),
+            frontrun: front.clone(),
+            backrun: back.clone(),
+            victims,
+            profit_token: front.token_in.clone(),
+            profit_token_symbol: front.token_in_symbol.clone(),
+            profit_amount: profit_amount.round_dp(18),
+            profit_usd,
+        });
+    }
+
+    sandwiches
+}
+
+fn same_attacker(front: &MevSwap, back: &MevSwap) -> bool {
+    if front.tx_origin == back.tx_origin {
+        return true;
+    }
+    !back.via_router && back.event_sender.as_deref() == Some(front.to_address.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    /// buy: 用 weth 买入 tkn；sell: 卖出 tkn 换回 weth
+    fn swap(tx: &str, origin: &str, side: &str, amount_in: i64, amount_out: i64) -> MevSwap {
+        let (token_in, token_out) = if side == "buy" { ("weth", "tkn") } else { ("tkn", "weth") };
+        MevSwap {
+            transaction_hash: tx.to_string(),
+            log_index: 0,
+            block_number: 1,
+            timestamp: Utc::now(),
+            pair_address: "0xpair".to_string(),
+            tx_origin: origin.to_string(),
+            event_sender: Some("0xrouter".to_string()),
+            via_router: true,
+            to_address: origin.to_string(),
+            side: side.to_string(),
+            token_in: token_in.to_string(),
+            token_in_symbol: None,
+            amount_in: Decimal::from(amount_in),
+            token_out: token_out.to_string(),
+            amount_out: Decimal::from(amount_out),
+            volume_usd: Some(Decimal::from(amount_in * 2000)),
+        }
+    }
+
+    fn sandwich(back_in: i64, back_out: i64) -> Vec<MevSwap> {
+        vec![
+            swap("0x1", "0xattacker", "buy", 10, 100),
+            swap("0x2", "0xvictim", "buy", 5, 45),
+            swap("0x3", "0xattacker", "sell", back_in, back_out),
+        ]
+    }
+
+    #[test]
+    fn detects_sandwich_and_prices_profit_in_frontrun_token() {
+        let found = detect_in_pool_block(&sandwich(100, 11));
+
+        assert_eq!(found.len(), 1);
+        let row = &found[0];
+        assert_eq!(row.attacker, "0xattacker");
+        assert_eq!(row.victims.len(), 1);
+        assert_eq!(row.victims[0].tx_origin, "0xvictim");
+        assert_eq!(row.profit_token, "weth");
+        // 卖回 11 weth，抢跑投入 10 weth，按 2000 美元 / weth 计
+        assert_eq!(row.profit_amount, Decimal::ONE);
+        assert_eq!(row.profit_usd, Some(Decimal::from(2000)));
+    }
+
+    #[test]
+    fn partial_backrun_only_counts_sold_share_of_cost() {
+        // 只卖出一半：成本按 5 weth 计
+        let found = detect_in_pool_block(&sandwich(50, 6));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].profit_amount, Decimal::ONE);
+    }
+
+    #[test]
+    fn backrun_must_sell_between_50_and_101_percent_of_frontrun() {
+        assert!(detect_in_pool_block(&sandwich(49, 6)).is_empty());
+        assert_eq!(detect_in_pool_block(&sandwich(101, 11)).len(), 1);
+        assert!(detect_in_pool_block(&sandwich(102, 12)).is_empty());
+    }
+
+    #[test]
+    fn victims_must_trade_in_frontrun_direction() {
+        let swaps = vec![
+            swap("0x1", "0xattacker", "buy", 10, 100),
+            swap("0x2", "0xvictim", "sell", 45, 5),
+            swap("0x3", "0xattacker", "sell", 100, 11),
+        ];
+        assert!(detect_in_pool_block(&swaps).is_empty());
+    }
+
+    #[test]
+    fn unprofitable_round_trip_is_not_a_sandwich() {
+        assert!(detect_in_pool_block(&sandwich(100, 10)).is_empty());
+        assert!(detect_in_pool_block(&sandwich(100, 9)).is_empty());
+    }
+
+    #[test]
+    fn same_attacker_matches_bot_contract_but_not_router() {
+        let mut front = swap("0x1", "0xeoa1", "buy", 10, 100);
+        front.to_address = "0xbot".to_string();
+        let mut back = swap("0x3", "0xeoa2", "sell", 100, 11);
+        back.event_sender = Some("0xbot".to_string());
+        back.via_router = false;
+        assert!(same_attacker(&front, &back));
+
+        let swaps = vec![front.clone(), swap("0x2", "0xvictim", "buy", 5, 45), back.clone()];
+        assert_eq!(detect_in_pool_block(&swaps).len(), 1);
+
+        // 经已知路由调用时，接收方相同不能说明是同一攻击者
+        back.via_router = true;
+        assert!(!same_attacker(&front, &back));
+        back.event_sender = Some("0xother".to_string());
+        back.via_router = false;
+        assert!(!same_attacker(&front, &back));
+    }
+}
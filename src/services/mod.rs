@@ -1,7 +1,31 @@
 pub mod event_service;
 pub mod api_service;
 pub mod price_service;
+pub mod maintenance_service;
+pub mod valuation_service;
+pub mod supply_service;
+pub mod pnl_service;
+pub mod lp_service;
+pub mod trade_service;
+pub mod mev_service;
+pub mod alert_service;
+pub mod risk_service;
+pub mod twap_service;
+pub mod swap_quote_service;
+pub mod arbitrage_service;
 
 pub use event_service::EventService;
 pub use api_service::ApiService;
-pub use price_service::PriceService;
\ No newline at end of file
+pub use price_service::PriceService;
+pub use maintenance_service::MaintenanceService;
+pub use valuation_service::ValuationService;
+pub use supply_service::SupplyService;
+pub use pnl_service::{CostBasisMethod, PnlService};
+pub use lp_service::LpService;
+pub use trade_service::TradeService;
+pub use mev_service::MevService;
+pub use alert_service::AlertService;
+pub use risk_service::RiskService;
+pub use twap_service::TwapService;
+pub use swap_quote_service::SwapQuoteService;
+pub use arbitrage_service::ArbitrageService;
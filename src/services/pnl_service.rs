@@ -0,0 +1,423 @@
+use crate::database::operations::{WalletAttribution, WalletOperations, WalletSwapLeg};
+use crate::types::{WalletPnL, WalletPnLRecord, WalletPortfolioItem, WalletTokenPnL};
+use anyhow::Result;
+use chrono::{DateTime, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 成本计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostBasisMethod {
+    /// 先进先出：卖出时依次消耗最早买入的批次
+    Fifo,
+    /// 移动加权平均：所有买入合并为一个平均成本
+    Average,
+}
+
+impl CostBasisMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "fifo" => Some(Self::Fifo),
+            "average" | "avg" => Some(Self::Average),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::Average => "average",
+        }
+    }
+}
+
+/// 一批买入：数量和单位成本（USD）
+#[derive(Debug, Clone)]
+struct Lot {
+    quantity: Decimal,
+    unit_cost: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct Position {
+    symbol: Option<String>,
+    name: Option<String>,
+    lots: VecDeque<Lot>,
+    bought: Decimal,
+    sold: Decimal,
+    realized: Decimal,
+    /// 最近一次成交的美元单价
+    last_price: Option<Decimal>,
+}
+
+impl Position {
+    fn balance(&self) -> Decimal {
+        self.lots.iter().map(|l| l.quantity).sum()
+    }
+
+    fn cost_basis(&self) -> Decimal {
+        self.lots.iter().map(|l| l.quantity * l.unit_cost).sum()
+    }
+
+    fn unrealized(&self, price: Decimal) -> Decimal {
+        self.balance() * price - self.cost_basis()
+    }
+
+    fn acquire(&mut self, quantity: Decimal, cost_usd: Decimal, method: CostBasisMethod) {
+        if quantity <= Decimal::ZERO {
+            return;
+        }
+        self.bought += quantity;
+
+        match (method, self.lots.front_mut()) {
+            (CostBasisMethod::Average, Some(lot)) => {
+                let total = lot.quantity + quantity;
+                lot.unit_cost = (lot.quantity * lot.unit_cost + cost_usd) / total;
+                lot.quantity = total;
+            }
+            _ => self.lots.push_back(Lot {
+                quantity,
+                unit_cost: cost_usd / quantity,
+            }),
+        }
+    }
+
+    /// 卖出并返回已实现盈亏；持仓不足的部分（代币来自转入，成本未知）按卖出价计成本，不产生盈亏。
+    /// 没有任何持仓可匹配时返回 None
+    fn dispose(&mut self, quantity: Decimal, proceeds_usd: Decimal) -> Option<Decimal> {
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+        self.sold += quantity;
+
+        let unit_proceeds = proceeds_usd / quantity;
+        let mut remaining = quantity;
+        let mut matched = Decimal::ZERO;
+        let mut realized = Decimal::ZERO;
+
+        while remaining > Decimal::ZERO {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+            let take = remaining.min(lot.quantity);
+            realized += take * (unit_proceeds - lot.unit_cost);
+            lot.quantity -= take;
+            remaining -= take;
+            matched += take;
+            if lot.quantity <= Decimal::ZERO {
+                self.lots.pop_front();
+            }
+        }
+
+        self.realized += realized;
+        (matched > Decimal::ZERO).then_some(realized)
+    }
+}
+
+/// 按链上顺序回放钱包 swap 后的账本
+#[derive(Debug, Default)]
+struct Ledger {
+    positions: BTreeMap<(i32, String), Position>,
+    daily: Vec<WalletPnLRecord>,
+    /// 每笔有持仓可匹配的卖出：(时间, 已实现盈亏)
+    disposals: Vec<(DateTime<Utc>, Decimal)>,
+    unpriced_swaps: i64,
+}
+
+impl Ledger {
+    fn replay(legs: &[WalletSwapLeg], method: CostBasisMethod) -> Self {
+        let mut ledger = Self::default();
+        let mut day: Option<DateTime<Utc>> = None;
+        let mut day_realized = Decimal::ZERO;
+        let mut day_volume = Decimal::ZERO;
+        let mut day_fees = Decimal::ZERO;
+        let mut cumulative_realized = Decimal::ZERO;
+
+        for leg in legs {
+            let leg_day = start_of_day(leg.timestamp);
+            if let Some(current) = day.filter(|d| *d != leg_day) {
+                cumulative_realized += day_realized;
+                ledger.close_day(current, day_realized, day_volume, day_fees, cumulative_realized);
+                day_realized = Decimal::ZERO;
+                day_volume = Decimal::ZERO;
+                day_fees = Decimal::ZERO;
+            }
+            day = Some(leg_day);
+            day_fees += leg.fee_usd.unwrap_or(Decimal::ZERO);
+
+            let volume_usd = match leg.volume_usd {
+                Some(v) if leg.amount_in > Decimal::ZERO && leg.amount_out > Decimal::ZERO => v,
+                _ => {
+                    ledger.unpriced_swaps += 1;
+                    continue;
+                }
+            };
+            day_volume += volume_usd;
+
+            let sold = ledger.position(leg.chain_id, &leg.token_in, &leg.token_in_symbol, &leg.token_in_name);
+            sold.last_price = Some(volume_usd / leg.amount_in);
+            if let Some(realized) = sold.dispose(leg.amount_in, volume_usd) {
+                day_realized += realized;
+                ledger.disposals.push((leg.timestamp, realized));
+            }
+
+            let bought = ledger.position(leg.chain_id, &leg.token_out, &leg.token_out_symbol, &leg.token_out_name);
+            bought.last_price = Some(volume_usd / leg.amount_out);
+            bought.acquire(leg.amount_out, volume_usd, method);
+        }
+
+        if let Some(current) = day {
+            cumulative_realized += day_realized;
+            ledger.close_day(current, day_realized, day_volume, day_fees, cumulative_realized);
+        }
+
+        ledger
+    }
+
+    fn position(
+        &mut self,
+        chain_id: i32,
+        token: &str,
+        symbol: &Option<String>,
+        name: &Option<String>,
+    ) -> &mut Position {
+        let position = self.positions.entry((chain_id, token.to_string())).or_default();
+        if position.symbol.is_none() {
+            position.symbol = symbol.clone();
+            position.name = name.clone();
+        }
+        position
+    }
+
+    /// 日末快照：未实现盈亏以各代币最近一次成交价估值
+    fn close_day(
+        &mut self,
+        day: DateTime<Utc>,
+        realized: Decimal,
+        volume: Decimal,
+        fees: Decimal,
+        cumulative_realized: Decimal,
+    ) {
+        let unrealized: Decimal = self
+            .positions
+            .values()
+            .filter_map(|p| p.last_price.map(|price| p.unrealized(price)))
+            .sum();
+
+        self.daily.push(WalletPnLRecord {
+            date: day,
+            realized_pnl: realized,
+            unrealized_pnl: unrealized,
+            total_pnl: cumulative_realized + unrealized,
+            volume,
+            fees_paid: fees,
+        });
+    }
+}
+
+/// 钱包成本与盈亏计算：回放钱包的全部 swap，按 FIFO 或平均成本计算每个代币的已实现 / 未实现盈亏
+pub struct PnlService;
+
+impl PnlService {
+    /// `since` 为 None 时汇总全部历史；否则已实现盈亏、胜率和每日记录只统计 `since` 之后，
+    /// 代币明细始终为全部历史
+    pub async fn wallet_pnl(
+        pool: &PgPool,
+        wallet_address: &str,
+        chain_id: Option<i32>,
+        method: CostBasisMethod,
+        since: Option<DateTime<Utc>>,
+        attribution: WalletAttribution,
+    ) -> Result<WalletPnL> {
+        let legs =
+            WalletOperations::get_wallet_swap_legs(pool, wallet_address, chain_id, attribution).await?;
+        let ledger = Ledger::replay(&legs, method);
+        let prices = Self::current_prices(pool, &ledger).await?;
+
+        let mut tokens: Vec<WalletTokenPnL> = ledger
+            .positions
+            .iter()
+            .map(|((chain_id, token), position)| {
+                let current_price = prices.get(&(*chain_id, token.clone())).copied();
+                let unrealized = current_price
+                    .or(position.last_price)
+                    .map(|price| position.unrealized(price))
+                    .unwrap_or(Decimal::ZERO);
+
+                WalletTokenPnL {
+                    chain_id: *chain_id,
+                    token_address: token.clone(),
+                    token_symbol: position.symbol.clone().unwrap_or_else(|| "UNKNOWN".to_string()),
+                    bought_amount: position.bought,
+                    sold_amount: position.sold,
+                    balance: position.balance(),
+                    cost_basis: position.cost_basis(),
+                    current_price,
+                    realized_pnl: position.realized,
+                    unrealized_pnl: unrealized,
+                    total_pnl: position.realized + unrealized,
+                }
+            })
+            .collect();
+        tokens.sort_by_key(|t| std::cmp::Reverse(t.total_pnl.abs()));
+
+        let in_window = |ts: &DateTime<Utc>| since.is_none_or(|s| *ts >= start_of_day(s));
+        let daily: Vec<WalletPnLRecord> =
+            ledger.daily.into_iter().filter(|r| in_window(&r.date)).collect();
+        let disposals: Vec<Decimal> = ledger
+            .disposals
+            .iter()
+            .filter(|(ts, _)| in_window(ts))
+            .map(|(_, pnl)| *pnl)
+            .collect();
+
+        let realized_pnl: Decimal = match since {
+            Some(_) => daily.iter().map(|r| r.realized_pnl).sum(),
+            None => tokens.iter().map(|t| t.realized_pnl).sum(),
+        };
+        let unrealized_pnl: Decimal = tokens.iter().map(|t| t.unrealized_pnl).sum();
+        let win_rate = if disposals.is_empty() {
+            Decimal::ZERO
+        } else {
+            let wins = disposals.iter().filter(|pnl| **pnl > Decimal::ZERO).count();
+            (Decimal::from(wins * 100) / Decimal::from(disposals.len())).round_dp(2)
+        };
+
+        Ok(WalletPnL {
+            wallet_address: wallet_address.to_string(),
+            chain_id,
+            method: method.as_str().to_string(),
+            realized_pnl,
+            unrealized_pnl,
+            total_pnl: realized_pnl + unrealized_pnl,
+            win_rate,
+            unpriced_swaps: ledger.unpriced_swaps,
+            tokens,
+            daily,
+        })
+    }
+
+    /// 当前持仓（余额大于 0 的代币），按持仓价值降序
+    pub async fn wallet_portfolio(
+        pool: &PgPool,
+        wallet_address: &str,
+        chain_id: Option<i32>,
+        method: CostBasisMethod,
+        attribution: WalletAttribution,
+    ) -> Result<Vec<WalletPortfolioItem>> {
+        let legs =
+            WalletOperations::get_wallet_swap_legs(pool, wallet_address, chain_id, attribution).await?;
+        let ledger = Ledger::replay(&legs, method);
+        let prices = Self::current_prices(pool, &ledger).await?;
+
+        let mut items: Vec<WalletPortfolioItem> = ledger
+            .positions
+            .iter()
+            .filter(|(_, position)| position.balance() > Decimal::ZERO)
+            .map(|((chain_id, token), position)| {
+                let balance = position.balance();
+                let cost_basis = position.cost_basis();
+                let current_price = prices
+                    .get(&(*chain_id, token.clone()))
+                    .copied()
+                    .or(position.last_price)
+                    .unwrap_or(Decimal::ZERO);
+                let unrealized = position.unrealized(current_price);
+
+                WalletPortfolioItem {
+                    chain_id: *chain_id,
+                    token_address: token.clone(),
+                    token_symbol: position.symbol.clone().unwrap_or_else(|| "UNKNOWN".to_string()),
+                    token_name: position.name.clone().unwrap_or_else(|| "Unknown Token".to_string()),
+                    balance,
+                    value_usd: balance * current_price,
+                    avg_buy_price: cost_basis / balance,
+                    current_price,
+                    profit_loss: unrealized,
+                    profit_loss_percentage: if cost_basis > Decimal::ZERO {
+                        (unrealized * Decimal::ONE_HUNDRED / cost_basis).round_dp(2)
+                    } else {
+                        Decimal::ZERO
+                    },
+                }
+            })
+            .collect();
+        items.sort_by_key(|i| std::cmp::Reverse(i.value_usd));
+
+        Ok(items)
+    }
+
+    async fn current_prices(pool: &PgPool, ledger: &Ledger) -> Result<HashMap<(i32, String), Decimal>> {
+        let tokens: Vec<(i32, String)> = ledger.positions.keys().cloned().collect();
+        WalletOperations::get_current_usd_prices(pool, &tokens).await
+    }
+}
+
+fn start_of_day(ts: DateTime<Utc>) -> DateTime<Utc> {
+    ts.date_naive().and_time(NaiveTime::MIN).and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: i64) -> Decimal {
+        Decimal::from(value)
+    }
+
+    #[test]
+    fn fifo_partial_sell_consumes_oldest_lot_first() {
+        let mut position = Position::default();
+        position.acquire(dec(10), dec(10), CostBasisMethod::Fifo);
+        position.acquire(dec(10), dec(30), CostBasisMethod::Fifo);
+
+        // 15 个按 4 美元卖出：前 10 个成本 1，后 5 个成本 3
+        let realized = position.dispose(dec(15), dec(60));
+        assert_eq!(realized, Some(dec(30) + dec(5)));
+        assert_eq!(position.balance(), dec(5));
+        assert_eq!(position.cost_basis(), dec(15));
+        assert_eq!(position.lots.len(), 1);
+    }
+
+    #[test]
+    fn selling_more_than_held_books_excess_at_sale_price() {
+        let mut position = Position::default();
+        position.acquire(dec(10), dec(10), CostBasisMethod::Fifo);
+
+        // 持有 10 个卖出 20 个，超出部分按卖出价计成本，不产生盈亏
+        let realized = position.dispose(dec(20), dec(40));
+        assert_eq!(realized, Some(dec(10)));
+        assert_eq!(position.balance(), Decimal::ZERO);
+        assert_eq!(position.sold, dec(20));
+        assert!(position.lots.is_empty());
+    }
+
+    #[test]
+    fn selling_without_any_position_returns_none() {
+        let mut position = Position::default();
+        assert_eq!(position.dispose(dec(5), dec(10)), None);
+        assert_eq!(position.realized, Decimal::ZERO);
+        assert_eq!(position.dispose(Decimal::ZERO, dec(10)), None);
+    }
+
+    #[test]
+    fn average_rebuy_merges_into_single_lot() {
+        let mut position = Position::default();
+        position.acquire(dec(10), dec(10), CostBasisMethod::Average);
+        position.acquire(dec(10), dec(30), CostBasisMethod::Average);
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!(position.lots[0].unit_cost, dec(2));
+
+        let realized = position.dispose(dec(5), dec(20));
+        assert_eq!(realized, Some(dec(10)));
+
+        // 卖出后再买入，与剩余持仓重新加权
+        position.acquire(dec(15), dec(90), CostBasisMethod::Average);
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!(position.balance(), dec(30));
+        assert_eq!(position.lots[0].unit_cost, dec(4));
+        assert_eq!(position.unrealized(dec(5)), dec(30));
+        assert_eq!(position.bought, dec(35));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::config::RiskConfig;
+use crate::database::operations::{RiskOperations, RiskTarget};
+use crate::database::Database;
+use anyhow::Result;
+use ethers::{
+    contract::abigen,
+    providers::{Http, Middleware, Provider},
+    types::{Address, H256, U256},
+    utils::id,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+abigen!(
+    LpToken,
+    r#"[
+        function totalSupply() external view returns (uint256)
+        function balanceOf(address account) external view returns (uint256)
+    ]"#
+);
+
+/// EIP-1967 实现合约存储槽
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// EIP-1967 beacon 存储槽
+const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// EIP-1167 最小代理的字节码前缀，其后 20 字节为实现合约地址
+const EIP1167_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+
+/// 字节码中出现任一函数选择器即打上对应标记
+const PRIVILEGED_FUNCTIONS: [(&str, &[&str]); 3] = [
+    (
+        "mintable",
+        &["mint(address,uint256)", "mint(uint256)", "mintTo(address,uint256)"],
+    ),
+    (
+        "blacklist",
+        &[
+            "blacklist(address)",
+            "addToBlacklist(address)",
+            "addBlackList(address)",
+            "setBlacklist(address,bool)",
+            "blacklistAddress(address,bool)",
+        ],
+    ),
+    ("pausable", &["pause()", "setPaused(bool)"]),
+];
+
+/// 单条链的风险检查配置
+pub struct RiskChain {
+    pub provider: Arc<Provider<Http>>,
+    /// 持有的 LP 视为已锁定的地址（小写）：销毁地址和 LP 锁仓合约
+    pub locked_addresses: Vec<String>,
+}
+
+/// 新代币风险服务：交易对创建时由工厂监听器做字节码检查，
+/// 之后在 `RISK_WINDOW_HOURS` 内定期补查失败的字节码检查，并重算 LP 锁仓和转账税标记
+pub struct RiskService {
+    database: Arc<Database>,
+    config: RiskConfig,
+    chains: HashMap<i32, RiskChain>,
+}
+
+impl RiskService {
+    pub fn new(database: Arc<Database>, config: RiskConfig, chains: HashMap<i32, RiskChain>) -> Self {
+        Self {
+            database,
+            config,
+            chains,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🛡️ 启动新代币风险服务，间隔: {}s, 窗口: {} 小时, 批次: {}",
+            self.config.interval_secs, self.config.window_hours, self.config.batch_size
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for (chain_id, chain) in &self.chains {
+                if let Err(e) = self.run_chain(*chain_id, chain).await {
+                    error!("❌ 链 {} 风险评分刷新失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    pub async fn run_chain(&self, chain_id: i32, chain: &RiskChain) -> Result<()> {
+        let pool = self.database.pool();
+
+        let targets = RiskOperations::get_risk_targets(
+            pool,
+            chain_id,
+            self.config.window_hours,
+            self.config.batch_size,
+        )
+        .await?;
+        if targets.is_empty() {
+            return Ok(());
+        }
+
+        let mut updated = 0;
+        for target in &targets {
+            match self.check_pair(chain_id, chain, target).await {
+                Ok(()) => updated += 1,
+                Err(e) => debug!(
+                    "链 {} 交易对 {} 风险检查失败: {}",
+                    chain_id, target.pair_address, e
+                ),
+            }
+        }
+
+        debug!(
+            "🛡️ 链 {} 风险评分刷新完成: {}/{} 个交易对",
+            chain_id,
+            updated,
+            targets.len()
+        );
+        Ok(())
+    }
+
+    async fn check_pair(&self, chain_id: i32, chain: &RiskChain, target: &RiskTarget) -> Result<()> {
+        let pool = self.database.pool();
+
+        if target.unchecked {
+            let flags = inspect_token(&chain.provider, target.token_address.parse()?).await?;
+            RiskOperations::set_token_risk(
+                pool,
+                chain_id,
+                &target.pair_address,
+                &target.token_address,
+                &flags,
+            )
+            .await?;
+        }
+
+        let lp_locked_pct = read_lp_locked_pct(chain, target.pair_address.parse()?).await?;
+        RiskOperations::update_dynamic_risk(
+            pool,
+            chain_id,
+            &target.pair_address,
+            lp_locked_pct,
+            self.config.lp_locked_pct,
+        )
+        .await
+    }
+}
+
+/// 检查代币合约字节码，返回风险标记：
+/// - no_code：地址上没有合约代码
+/// - proxy：EIP-1967 可升级代理，实现合约随时可被替换；能解析出实现合约时一并检查其字节码
+/// - mintable / blacklist / pausable：字节码中存在对应特权函数的选择器
+pub async fn inspect_token(provider: &Provider<Http>, token: Address) -> Result<Vec<String>> {
+    let code = provider.get_code(token, None).await?;
+    if code.is_empty() {
+        return Ok(vec!["no_code".to_string()]);
+    }
+
+    let mut flags = Vec::new();
+    let mut implementation = None;
+
+    if code.len() >= 30 && code.starts_with(&EIP1167_PREFIX) {
+        implementation = Some(Address::from_slice(&code[10..30]));
+    } else {
+        let slot = provider
+            .get_storage_at(token, H256::from_str(EIP1967_IMPLEMENTATION_SLOT)?, None)
+            .await?;
+        let beacon = provider
+            .get_storage_at(token, H256::from_str(EIP1967_BEACON_SLOT)?, None)
+            .await?;
+        if !slot.is_zero() || !beacon.is_zero() {
+            flags.push("proxy".to_string());
+        }
+        if !slot.is_zero() {
+            implementation = Some(Address::from(slot));
+        }
+    }
+
+    let mut codes = vec![code];
+    if let Some(implementation) = implementation {
+        codes.push(provider.get_code(implementation, None).await?);
+    }
+
+    for (flag, signatures) in PRIVILEGED_FUNCTIONS {
+        let present = signatures
+            .iter()
+            .any(|signature| codes.iter().any(|code| has_selector(code, id(signature))));
+        if present {
+            flags.push(flag.to_string());
+        }
+    }
+
+    Ok(flags)
+}
+
+/// Solidity 的函数分发以 PUSH4 <selector> 开头
+fn has_selector(code: &[u8], selector: [u8; 4]) -> bool {
+    code.windows(5)
+        .any(|window| window[0] == 0x63 && window[1..] == selector)
+}
+
+/// 销毁地址和锁仓合约持有的 LP 占总量的百分比，池子还没有 LP 时返回 None
+async fn read_lp_locked_pct(chain: &RiskChain, pair: Address) -> Result<Option<Decimal>> {
+    let contract = LpToken::new(pair, Arc::clone(&chain.provider));
+    // 同一区块读取，保证总量与余额一致
+    let block = chain.provider.get_block_number().await?;
+
+    let total = contract.total_supply().block(block).call().await?;
+    if total.is_zero() {
+        return Ok(None);
+    }
+
+    let mut locked = U256::zero();
+    for holder in &chain.locked_addresses {
+        let holder: Address = match holder.parse() {
+            Ok(addr) => addr,
+            Err(_) => continue,
+        };
+        locked = locked.saturating_add(contract.balance_of(holder).block(block).call().await?);
+    }
+
+    let bps = locked.min(total).saturating_mul(U256::from(10_000)) / total;
+    Ok(Some(Decimal::new(bps.as_u64() as i64, 2)))
+}
@@ -0,0 +1,190 @@
+use crate::config::SupplyConfig;
+use crate::database::operations::{SupplyOperations, SupplyTarget};
+use crate::database::Database;
+use anyhow::Result;
+use ethers::{
+    contract::abigen,
+    providers::{Http, Middleware, Provider},
+    types::{Address, U256},
+};
+use rust_decimal::Decimal;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+abigen!(
+    ERC20Supply,
+    r#"[
+        function totalSupply() external view returns (uint256)
+        function balanceOf(address account) external view returns (uint256)
+    ]"#
+);
+
+/// 单条链的供应量读取配置
+pub struct SupplyChain {
+    pub provider: Arc<Provider<Http>>,
+    /// 对该链所有代币生效的排除地址（小写）
+    pub excluded_addresses: Vec<String>,
+}
+
+/// 供应量服务：定期读取交易对中代币的链上 totalSupply，扣除排除地址余额得到流通量，
+/// 供代币列表和详情计算市值 / FDV
+pub struct SupplyService {
+    database: Arc<Database>,
+    config: SupplyConfig,
+    chains: HashMap<i32, SupplyChain>,
+}
+
+impl SupplyService {
+    pub fn new(database: Arc<Database>, config: SupplyConfig, chains: HashMap<i32, SupplyChain>) -> Self {
+        Self {
+            database,
+            config,
+            chains,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🪙 启动代币供应量服务，间隔: {}s, 刷新周期: {}s, 批次: {}",
+            self.config.interval_secs, self.config.refresh_after_secs, self.config.batch_size
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for (chain_id, chain) in &self.chains {
+                if let Err(e) = self.run_chain(*chain_id, chain).await {
+                    error!("❌ 链 {} 供应量刷新失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    pub async fn run_chain(&self, chain_id: i32, chain: &SupplyChain) -> Result<()> {
+        let pool = self.database.pool();
+
+        let targets = SupplyOperations::get_tokens_due(
+            pool,
+            chain_id,
+            self.config.refresh_after_secs,
+            self.config.batch_size,
+        )
+        .await?;
+        if targets.is_empty() {
+            return Ok(());
+        }
+
+        // 同一轮固定在同一区块读取，保证 totalSupply 与余额一致
+        let block = chain.provider.get_block_number().await?;
+
+        let mut updated = 0;
+        for target in &targets {
+            match self.read_supply(chain_id, chain, target, block.as_u64()).await {
+                Ok(Some((total, excluded))) => {
+                    SupplyOperations::upsert_token_supply(
+                        pool,
+                        chain_id,
+                        &target.token_address,
+                        total,
+                        excluded,
+                        block.as_u64() as i64,
+                    )
+                    .await?;
+                    updated += 1;
+                }
+                Ok(None) => {
+                    debug!(
+                        "链 {} 代币 {} 的供应量超出可表示范围，跳过",
+                        chain_id, target.token_address
+                    );
+                    SupplyOperations::record_failure(pool, chain_id, &target.token_address).await?;
+                }
+                Err(e) => {
+                    debug!(
+                        "链 {} 读取代币 {} 供应量失败: {}",
+                        chain_id, target.token_address, e
+                    );
+                    SupplyOperations::record_failure(pool, chain_id, &target.token_address).await?;
+                }
+            }
+        }
+
+        info!(
+            "🪙 链 {} 供应量刷新完成: {}/{} 个代币",
+            chain_id,
+            updated,
+            targets.len()
+        );
+        Ok(())
+    }
+
+    /// 返回 (totalSupply, 排除地址余额合计)，均按精度归一化
+    async fn read_supply(
+        &self,
+        chain_id: i32,
+        chain: &SupplyChain,
+        target: &SupplyTarget,
+        block: u64,
+    ) -> Result<Option<(Decimal, Decimal)>> {
+        let token: Address = target.token_address.parse()?;
+        let contract = ERC20Supply::new(token, Arc::clone(&chain.provider));
+
+        let total_raw = contract.total_supply().block(block).call().await?;
+
+        let mut holders: BTreeSet<String> = chain.excluded_addresses.iter().cloned().collect();
+        for exclusion in
+            SupplyOperations::get_exclusions(self.database.pool(), chain_id, &target.token_address)
+                .await?
+        {
+            holders.insert(exclusion.holder_address);
+        }
+
+        let mut excluded_raw = U256::zero();
+        for holder in holders {
+            let holder: Address = match holder.parse() {
+                Ok(addr) => addr,
+                Err(_) => continue,
+            };
+            let balance = contract.balance_of(holder).block(block).call().await?;
+            excluded_raw = excluded_raw.saturating_add(balance);
+        }
+
+        let total = match normalize_units(total_raw, target.decimals) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let excluded = match normalize_units(excluded_raw.min(total_raw), target.decimals) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        Ok(Some((total, excluded)))
+    }
+}
+
+/// 按精度把原始整数数量转为 Decimal，超出 28 位有效数字的小数部分截断；整数部分超出范围时返回 None
+pub(crate) fn normalize_units(raw: U256, decimals: i32) -> Option<Decimal> {
+    let decimals = decimals.clamp(0, 77) as usize;
+    let digits = format!("{:0>width$}", raw.to_string(), width = decimals + 1);
+    let (int_part, frac_part) = digits.split_at(digits.len() - decimals);
+
+    if int_part.len() > 28 {
+        return None;
+    }
+    let budget = if int_part == "0" { 28 } else { 28 - int_part.len() };
+    let frac_part = &frac_part[..frac_part.len().min(budget)];
+
+    if frac_part.is_empty() {
+        Decimal::from_str(int_part).ok()
+    } else {
+        Decimal::from_str(&format!("{}.{}", int_part, frac_part))
+            .ok()
+            .map(|v| v.normalize())
+    }
+}
@@ -0,0 +1,478 @@
+use super::supply_service::normalize_units;
+use crate::database::operations::{RouteOperations, RoutePool};
+use crate::types::{SwapQuote, SwapQuoteHop};
+use anyhow::Result;
+use ethers::types::U256;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// 未指定 `max_hops` 时的最大跳数
+pub const DEFAULT_QUOTE_MAX_HOPS: usize = 3;
+/// `max_hops` 上限
+pub const MAX_QUOTE_HOPS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteKind {
+    /// 给定输入数量，求最大输出
+    ExactIn,
+    /// 给定输出数量，求最小输入
+    ExactOut,
+}
+
+impl QuoteKind {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value.unwrap_or("exact_in") {
+            "exact_in" => Some(Self::ExactIn),
+            "exact_out" => Some(Self::ExactOut),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExactIn => "exact_in",
+            Self::ExactOut => "exact_out",
+        }
+    }
+}
+
+/// Uniswap V2 `getAmountOut`，手续费按输入数量收取
+pub fn get_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256, fee_bps: u32) -> Option<U256> {
+    if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() || fee_bps >= 10_000 {
+        return None;
+    }
+    let amount_in_with_fee = amount_in.checked_mul(U256::from(10_000 - fee_bps))?;
+    let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+    let denominator = reserve_in
+        .checked_mul(U256::from(10_000))?
+        .checked_add(amount_in_with_fee)?;
+    Some(numerator / denominator)
+}
+
+/// Uniswap V2 `getAmountIn`，输出数量不能达到储备量
+pub fn get_amount_in(amount_out: U256, reserve_in: U256, reserve_out: U256, fee_bps: u32) -> Option<U256> {
+    if amount_out.is_zero() || reserve_in.is_zero() || amount_out >= reserve_out || fee_bps >= 10_000 {
+        return None;
+    }
+    let numerator = reserve_in
+        .checked_mul(amount_out)?
+        .checked_mul(U256::from(10_000))?;
+    let denominator = (reserve_out - amount_out).checked_mul(U256::from(10_000 - fee_bps))?;
+    Some(numerator / denominator + 1)
+}
+
+/// 按精度把归一化数量转为原始整数，超出精度的小数部分截断；
+/// 精度超出 0-77（10^78 超出 U256）时返回 None
+fn to_raw_units(amount: Decimal, decimals: i32) -> Option<U256> {
+    if !(0..=77).contains(&decimals) {
+        return None;
+    }
+    let amount = amount.normalize();
+    if amount.mantissa() <= 0 {
+        return None;
+    }
+    let mantissa = U256::from(amount.mantissa() as u128);
+    let scale = amount.scale() as i32;
+    let raw = if decimals >= scale {
+        mantissa.checked_mul(U256::exp10((decimals - scale) as usize))?
+    } else {
+        mantissa / U256::exp10((scale - decimals) as usize)
+    };
+    (!raw.is_zero()).then_some(raw)
+}
+
+pub(crate) struct Pool {
+    pub(crate) address: String,
+    pub(crate) token0: String,
+    pub(crate) token1: String,
+    decimals0: i32,
+    decimals1: i32,
+    reserve0: U256,
+    reserve1: U256,
+    pub(crate) fee_bps: u32,
+}
+
+impl Pool {
+    pub(crate) fn parse(pool: &RoutePool) -> Option<Self> {
+        Some(Self {
+            address: pool.pair_address.clone(),
+            token0: pool.token0.clone(),
+            token1: pool.token1.clone(),
+            decimals0: pool.token0_decimals,
+            decimals1: pool.token1_decimals,
+            reserve0: U256::from_dec_str(&pool.reserve0).ok()?,
+            reserve1: U256::from_dec_str(&pool.reserve1).ok()?,
+            fee_bps: pool.fee_bps.clamp(0, 9_999) as u32,
+        })
+    }
+
+    /// 以 `token` 为一侧时返回 (另一侧代币, token 储备量, 另一侧储备量)
+    pub(crate) fn sides(&self, token: &str) -> (&str, U256, U256) {
+        if self.token0 == token {
+            (&self.token1, self.reserve0, self.reserve1)
+        } else {
+            (&self.token0, self.reserve1, self.reserve0)
+        }
+    }
+
+    pub(crate) fn decimals(&self, token: &str) -> i32 {
+        if self.token0 == token {
+            self.decimals0
+        } else {
+            self.decimals1
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Leg {
+    pool: usize,
+    token_in: String,
+    token_out: String,
+    amount_in: U256,
+    amount_out: U256,
+}
+
+/// 到达某个代币的最优路径：exact_in 时 amount 为能得到的该代币数量，exact_out 时为需要投入的该代币数量
+#[derive(Clone)]
+struct Candidate {
+    amount: U256,
+    /// 按兑换顺序排列
+    legs: Vec<Leg>,
+}
+
+impl Candidate {
+    fn visits(&self, token: &str) -> bool {
+        self.legs.iter().any(|leg| leg.token_in == token || leg.token_out == token)
+    }
+}
+
+/// 兑换报价：基于已记录的储备量和工厂手续费率模拟 V2 兑换，并在交易对图上搜索最优路径
+pub struct SwapQuoteService;
+
+impl SwapQuoteService {
+    /// `amount` 为归一化数量：exact_in 时是 token_in 数量，exact_out 时是 token_out 数量。
+    /// 代币不在任何有储备量的交易对中或找不到可行路径时返回 None
+    pub async fn quote(
+        pool: &PgPool,
+        chain_id: i32,
+        token_in: &str,
+        token_out: &str,
+        amount: Decimal,
+        kind: QuoteKind,
+        max_hops: usize,
+    ) -> Result<Option<SwapQuote>> {
+        let token_in = token_in.to_lowercase();
+        let token_out = token_out.to_lowercase();
+        if token_in == token_out {
+            return Ok(None);
+        }
+
+        let pools: Vec<Pool> = RouteOperations::get_route_pools(pool, chain_id, None)
+            .await?
+            .iter()
+            .filter_map(Pool::parse)
+            .collect();
+
+        let mut adjacency: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, p) in pools.iter().enumerate() {
+            adjacency.entry(p.token0.as_str()).or_default().push(i);
+            adjacency.entry(p.token1.as_str()).or_default().push(i);
+        }
+
+        let amount_token = match kind {
+            QuoteKind::ExactIn => &token_in,
+            QuoteKind::ExactOut => &token_out,
+        };
+        let decimals = match adjacency.get(amount_token.as_str()).and_then(|ids| ids.first()) {
+            Some(&i) => pools[i].decimals(amount_token),
+            None => return Ok(None),
+        };
+        let raw_amount = match to_raw_units(amount, decimals) {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+
+        let best = match kind {
+            QuoteKind::ExactIn => search(&pools, &adjacency, kind, &token_in, &token_out, raw_amount, max_hops),
+            QuoteKind::ExactOut => search(&pools, &adjacency, kind, &token_out, &token_in, raw_amount, max_hops),
+        };
+
+        Ok(best.and_then(|candidate| build_quote(chain_id, &pools, kind, &token_in, &token_out, &candidate.legs)))
+    }
+}
+
+/// 按跳数逐层松弛：第 n 层只从第 n-1 层更新过的代币出发，每个代币保留最优路径，路径不重复经过同一代币。
+/// exact_in 从 token_in 正向求最大输出，exact_out 从 token_out 反向求最小输入
+fn search(
+    pools: &[Pool],
+    adjacency: &HashMap<&str, Vec<usize>>,
+    kind: QuoteKind,
+    start: &str,
+    target: &str,
+    amount: U256,
+    max_hops: usize,
+) -> Option<Candidate> {
+    let better = |value: U256, existing: Option<&Candidate>| match (kind, existing) {
+        (_, None) => true,
+        (QuoteKind::ExactIn, Some(existing)) => value > existing.amount,
+        (QuoteKind::ExactOut, Some(existing)) => value < existing.amount,
+    };
+
+    let mut best: HashMap<String, Candidate> = HashMap::new();
+    best.insert(start.to_string(), Candidate { amount, legs: Vec::new() });
+    let mut frontier = vec![start.to_string()];
+
+    for _ in 0..max_hops {
+        let mut next: HashMap<String, Candidate> = HashMap::new();
+
+        for token in &frontier {
+            if token == target {
+                continue;
+            }
+            let candidate = &best[token];
+            for &i in adjacency.get(token.as_str()).into_iter().flatten() {
+                let pool = &pools[i];
+                let (other, reserve_token, reserve_other) = pool.sides(token);
+                if other == start || candidate.visits(other) {
+                    continue;
+                }
+
+                let (value, leg) = match kind {
+                    QuoteKind::ExactIn => {
+                        let out = match get_amount_out(candidate.amount, reserve_token, reserve_other, pool.fee_bps) {
+                            Some(out) if !out.is_zero() => out,
+                            _ => continue,
+                        };
+                        (out, Leg {
+                            pool: i,
+                            token_in: token.clone(),
+                            token_out: other.to_string(),
+                            amount_in: candidate.amount,
+                            amount_out: out,
+                        })
+                    }
+                    QuoteKind::ExactOut => {
+                        let need = match get_amount_in(candidate.amount, reserve_other, reserve_token, pool.fee_bps) {
+                            Some(need) => need,
+                            None => continue,
+                        };
+                        (need, Leg {
+                            pool: i,
+                            token_in: other.to_string(),
+                            token_out: token.clone(),
+                            amount_in: need,
+                            amount_out: candidate.amount,
+                        })
+                    }
+                };
+
+                if better(value, best.get(other)) && better(value, next.get(other)) {
+                    let mut legs = candidate.legs.clone();
+                    match kind {
+                        QuoteKind::ExactIn => legs.push(leg),
+                        QuoteKind::ExactOut => legs.insert(0, leg),
+                    }
+                    next.insert(other.to_string(), Candidate { amount: value, legs });
+                }
+            }
+        }
+
+        if next.is_empty() {
+            break;
+        }
+        frontier = next.keys().cloned().collect();
+        best.extend(next);
+    }
+
+    best.remove(target)
+}
+
+/// 中间价 = 各跳储备量比（按精度归一化）的乘积；价格冲击为成交价相对扣除手续费后的中间价的偏离
+fn build_quote(
+    chain_id: i32,
+    pools: &[Pool],
+    kind: QuoteKind,
+    token_in: &str,
+    token_out: &str,
+    legs: &[Leg],
+) -> Option<SwapQuote> {
+    let first = legs.first()?;
+    let last = legs.last()?;
+
+    let mut mid_price = Decimal::ONE;
+    let mut fee_factor = Decimal::ONE;
+    let mut route = Vec::with_capacity(legs.len());
+
+    for leg in legs {
+        let pool = &pools[leg.pool];
+        let (_, reserve_in, reserve_out) = pool.sides(&leg.token_in);
+        let decimals_in = pool.decimals(&leg.token_in);
+        let decimals_out = pool.decimals(&leg.token_out);
+
+        let hop_mid = normalize_units(reserve_out, decimals_out)?
+            .checked_div(normalize_units(reserve_in, decimals_in)?)?;
+        let hop_fee = Decimal::ONE - Decimal::new(pool.fee_bps as i64, 4);
+        let amount_in = normalize_units(leg.amount_in, decimals_in)?;
+        let amount_out = normalize_units(leg.amount_out, decimals_out)?;
+
+        mid_price = mid_price.checked_mul(hop_mid)?;
+        fee_factor *= hop_fee;
+        route.push(SwapQuoteHop {
+            pair_address: pool.address.clone(),
+            token_in: leg.token_in.clone(),
+            token_out: leg.token_out.clone(),
+            amount_in,
+            amount_out,
+            fee_bps: pool.fee_bps as i32,
+            price_impact_pct: price_impact(amount_in, amount_out, hop_mid * hop_fee),
+        });
+    }
+
+    let amount_in = normalize_units(first.amount_in, pools[first.pool].decimals(token_in))?;
+    let amount_out = normalize_units(last.amount_out, pools[last.pool].decimals(token_out))?;
+
+    Some(SwapQuote {
+        chain_id,
+        token_in: token_in.to_string(),
+        token_out: token_out.to_string(),
+        kind: kind.as_str().to_string(),
+        amount_in,
+        amount_out,
+        amount_in_raw: first.amount_in.to_string(),
+        amount_out_raw: last.amount_out.to_string(),
+        execution_price: amount_out.checked_div(amount_in)?.round_dp(18),
+        mid_price: mid_price.round_dp(18),
+        price_impact_pct: price_impact(amount_in, amount_out, mid_price.checked_mul(fee_factor)?),
+        lp_fee_pct: ((Decimal::ONE - fee_factor) * Decimal::ONE_HUNDRED).round_dp(4),
+        route,
+    })
+}
+
+pub(crate) fn price_impact(amount_in: Decimal, amount_out: Decimal, expected_price: Decimal) -> Decimal {
+    amount_in
+        .checked_mul(expected_price)
+        .filter(|expected| !expected.is_zero())
+        .and_then(|expected| amount_out.checked_div(expected))
+        .map(|ratio| ((Decimal::ONE - ratio) * Decimal::ONE_HUNDRED).round_dp(4))
+        .unwrap_or(Decimal::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(address: &str, token0: &str, token1: &str, reserve0: u64, reserve1: u64) -> Pool {
+        Pool {
+            address: address.to_string(),
+            token0: token0.to_string(),
+            token1: token1.to_string(),
+            decimals0: 18,
+            decimals1: 18,
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            fee_bps: 30,
+        }
+    }
+
+    fn adjacency(pools: &[Pool]) -> HashMap<&str, Vec<usize>> {
+        let mut adjacency: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, p) in pools.iter().enumerate() {
+            adjacency.entry(p.token0.as_str()).or_default().push(i);
+            adjacency.entry(p.token1.as_str()).or_default().push(i);
+        }
+        adjacency
+    }
+
+    #[test]
+    fn amount_out_charges_fee_on_input() {
+        let reserve = U256::from(1_000_000u64);
+        assert_eq!(get_amount_out(U256::from(1000), reserve, reserve, 0), Some(U256::from(999)));
+        assert_eq!(get_amount_out(U256::from(1000), reserve, reserve, 30), Some(U256::from(996)));
+        assert_eq!(get_amount_in(U256::from(996), reserve, reserve, 30), Some(U256::from(1000)));
+    }
+
+    #[test]
+    fn amount_in_round_trip_never_undershoots() {
+        let (reserve_in, reserve_out) = (U256::from(5_000_000u64), U256::from(3_000_000u64));
+        for x in [1u64, 7, 1_000, 123_457, 2_999_999] {
+            let x = U256::from(x);
+            // 按 get_amount_in 投入至少能得到目标输出
+            if let Some(need) = get_amount_in(x, reserve_in, reserve_out, 30) {
+                assert!(get_amount_out(need, reserve_in, reserve_out, 30).unwrap() >= x);
+            }
+            // 得到同样输出所需的最小输入不超过原输入
+            if let Some(out) = get_amount_out(x, reserve_in, reserve_out, 30).filter(|out| !out.is_zero()) {
+                assert!(get_amount_in(out, reserve_in, reserve_out, 30).unwrap() <= x);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_or_insufficient_reserves_have_no_quote() {
+        let reserve = U256::from(1_000_000u64);
+        assert_eq!(get_amount_out(U256::zero(), reserve, reserve, 30), None);
+        assert_eq!(get_amount_out(U256::from(1000), U256::zero(), reserve, 30), None);
+        assert_eq!(get_amount_out(U256::from(1000), reserve, U256::zero(), 30), None);
+        assert_eq!(get_amount_out(U256::from(1000), reserve, reserve, 10_000), None);
+        assert_eq!(get_amount_in(U256::from(1000), U256::zero(), reserve, 30), None);
+        assert_eq!(get_amount_in(reserve, reserve, reserve, 30), None);
+        assert_eq!(get_amount_in(reserve + 1, reserve, reserve, 30), None);
+    }
+
+    #[test]
+    fn search_respects_hop_limit() {
+        let pools = vec![
+            pool("0xab", "a", "b", 1_000_000, 1_000_000),
+            pool("0xbc", "b", "c", 1_000_000, 1_000_000),
+            pool("0xcd", "c", "d", 1_000_000, 1_000_000),
+        ];
+        let adjacency = adjacency(&pools);
+        let amount = U256::from(1000);
+
+        assert!(search(&pools, &adjacency, QuoteKind::ExactIn, "a", "d", amount, 2).is_none());
+
+        let best = search(&pools, &adjacency, QuoteKind::ExactIn, "a", "d", amount, 3).unwrap();
+        let route: Vec<usize> = best.legs.iter().map(|leg| leg.pool).collect();
+        assert_eq!(route, vec![0, 1, 2]);
+        assert_eq!(best.legs[0].amount_in, amount);
+        assert_eq!(best.legs[2].amount_out, best.amount);
+
+        // exact_out 反向搜索，第一跳的输入即所需投入
+        assert!(search(&pools, &adjacency, QuoteKind::ExactOut, "d", "a", amount, 2).is_none());
+        let best = search(&pools, &adjacency, QuoteKind::ExactOut, "d", "a", amount, 3).unwrap();
+        assert_eq!(best.legs.first().unwrap().token_in, "a");
+        assert_eq!(best.legs.last().unwrap().amount_out, amount);
+        assert_eq!(best.legs[0].amount_in, best.amount);
+    }
+
+    #[test]
+    fn search_prefers_deeper_multi_hop_route() {
+        let pools = vec![
+            pool("0xac", "a", "c", 10_000, 10_000),
+            pool("0xab", "a", "b", 1_000_000, 1_000_000),
+            pool("0xbc", "b", "c", 1_000_000, 1_000_000),
+        ];
+        let adjacency = adjacency(&pools);
+
+        let direct = search(&pools, &adjacency, QuoteKind::ExactIn, "a", "c", U256::from(1000), 1).unwrap();
+        assert_eq!(direct.legs.len(), 1);
+
+        let best = search(&pools, &adjacency, QuoteKind::ExactIn, "a", "c", U256::from(1000), 2).unwrap();
+        assert_eq!(best.legs.len(), 2);
+        assert!(best.amount > direct.amount);
+    }
+
+    #[test]
+    fn raw_units_reject_decimals_beyond_u256() {
+        assert_eq!(to_raw_units(Decimal::new(15, 1), 18), Some(U256::exp10(17) * 15));
+        // 超出精度的小数部分截断，截断为 0 时返回 None
+        assert_eq!(to_raw_units(Decimal::new(15, 1), 0), Some(U256::one()));
+        assert_eq!(to_raw_units(Decimal::new(1, 1), 0), None);
+        assert_eq!(to_raw_units(Decimal::ONE, 77), Some(U256::exp10(77)));
+        assert_eq!(to_raw_units(Decimal::ONE, 78), None);
+        assert_eq!(to_raw_units(Decimal::ONE, 255), None);
+        assert_eq!(to_raw_units(Decimal::ONE, -1), None);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::config::TradeConfig;
+use crate::database::operations::{
+    EventOperations, TradeHop, TradeOperations, TradeRow, EVENT_TYPE_SWAP, EVENT_TYPE_TRADES,
+};
+use crate::database::Database;
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+/// 多跳成交重建服务：把同一交易内首尾相接的 swap（如 A -> WETH -> B）合并为一笔逻辑成交，
+/// 按 swap 监听进度逐段处理已写入的区块，进度记录在 last_processed_blocks 的 `trades` 类型下
+pub struct TradeService {
+    database: Arc<Database>,
+    config: TradeConfig,
+    chain_ids: Vec<i32>,
+}
+
+impl TradeService {
+    pub fn new(database: Arc<Database>, config: TradeConfig, chain_ids: Vec<i32>) -> Self {
+        Self {
+            database,
+            config,
+            chain_ids,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "🔀 启动多跳成交重建服务，间隔: {}s, 批次: {} 个区块",
+            self.config.interval_secs, self.config.batch_blocks
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for chain_id in &self.chain_ids {
+                if let Err(e) = self.run_chain(*chain_id).await {
+                    error!("❌ 链 {} 多跳成交重建失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    /// 处理到 swap 监听已完成的区块为止
+    pub async fn run_chain(&self, chain_id: i32) -> Result<()> {
+        let pool = self.database.pool();
+        let batch_blocks = self.config.batch_blocks.max(1);
+
+        let head = EventOperations::get_last_processed_block(pool, chain_id, EVENT_TYPE_SWAP).await?;
+        let mut cursor =
+            EventOperations::get_last_processed_block(pool, chain_id, EVENT_TYPE_TRADES).await?;
+
+        while cursor < head {
+            // 跳过没有 swap 的区块，避免从创世区块逐段扫描
+            let start = match TradeOperations::get_next_swap_block(pool, chain_id, cursor).await? {
+                Some(block) if block <= head => block,
+                _ => {
+                    EventOperations::update_last_processed_block(pool, chain_id, EVENT_TYPE_TRADES, head)
+                        .await?;
+                    break;
+                }
+            };
+            let to_block = (start + batch_blocks - 1).min(head);
+
+            let hops = TradeOperations::get_trade_hops(pool, chain_id, cursor, to_block).await?;
+            let trades = build_trades(hops);
+            TradeOperations::replace_trades(pool, chain_id, cursor, to_block, &trades).await?;
+
+            debug!(
+                "🔀 链 {} 区块 {}..={} 重建 {} 笔成交",
+                chain_id,
+                cursor + 1,
+                to_block,
+                trades.len()
+            );
+
+            cursor = to_block;
+            EventOperations::update_last_processed_block(pool, chain_id, EVENT_TYPE_TRADES, cursor)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 按交易分组，组内按日志顺序把相邻的 swap 串成路径：
+/// 上一跳换出的代币就是这一跳换入的代币，且上一跳的接收方是这一跳的交易对（路由直接转入下一个池子）
+/// 或这一跳的调用方（代币先回到路由合约再转入）时视为同一笔成交，否则另起一笔
+fn build_trades(hops: Vec<TradeHop>) -> Vec<TradeRow> {
+    let mut trades: Vec<TradeRow> = Vec::new();
+    let mut current: Option<TradeRow> = None;
+
+    for hop in hops {
+        if let Some(trade) = current.as_mut() {
+            let same_tx = trade.transaction_hash == hop.transaction_hash;
+            let chained = trade.token_out == hop.token_in
+                && (trade.recipient == hop.pair_address
+                    || hop.event_sender.as_deref() == Some(trade.recipient.as_str()));
+
+            if same_tx && chained {
+                trade.log_indexes.push(hop.log_index);
+                trade.recipient = hop.to_address;
+                trade.token_out = hop.token_out.clone();
+                trade.token_out_symbol = hop.token_out_symbol;
+                trade.amount_out = hop.amount_out;
+                trade.path.push(hop.token_out);
+                trade.pairs.push(hop.pair_address);
+                continue;
+            }
+        }
+
+        if let Some(trade) = current.take() {
+            trades.push(trade);
+        }
+        current = Some(TradeRow {
+            transaction_hash: hop.transaction_hash,
+            log_indexes: vec![hop.log_index],
+            block_number: hop.block_number,
+            timestamp: hop.timestamp,
+            tx_origin: hop.tx_origin,
+            event_sender: hop.event_sender,
+            recipient: hop.to_address,
+            path: vec![hop.token_in.clone(), hop.token_out.clone()],
+            token_in: hop.token_in,
+            token_in_symbol: hop.token_in_symbol,
+            amount_in: hop.amount_in,
+            token_out: hop.token_out,
+            token_out_symbol: hop.token_out_symbol,
+            amount_out: hop.amount_out,
+            pairs: vec![hop.pair_address],
+        });
+    }
+
+    if let Some(trade) = current {
+        trades.push(trade);
+    }
+
+    trades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    const ROUTER: &str = "0xrouter";
+
+    fn hop(
+        tx: &str,
+        log_index: i32,
+        pair: &str,
+        event_sender: &str,
+        to: &str,
+        (token_in, token_out): (&str, &str),
+    ) -> TradeHop {
+        TradeHop {
+            transaction_hash: tx.to_string(),
+            log_index,
+            block_number: 1,
+            timestamp: Utc::now(),
+            tx_origin: "0xwallet".to_string(),
+            event_sender: Some(event_sender.to_string()),
+            pair_address: pair.to_string(),
+            to_address: to.to_string(),
+            token_in: token_in.to_string(),
+            token_in_symbol: None,
+            amount_in: Decimal::from(100 + log_index),
+            token_out: token_out.to_string(),
+            token_out_symbol: None,
+            amount_out: Decimal::from(200 + log_index),
+        }
+    }
+
+    #[test]
+    fn chains_hops_routed_directly_into_the_next_pair() {
+        // A -> WETH 换出直接转入 WETH/B 交易对，再换出给钱包
+        let trades = build_trades(vec![
+            hop("0x1", 3, "0xpair_a_weth", ROUTER, "0xpair_weth_b", ("a", "weth")),
+            hop("0x1", 5, "0xpair_weth_b", ROUTER, "0xwallet", ("weth", "b")),
+        ]);
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.log_indexes, vec![3, 5]);
+        assert_eq!(trade.path, vec!["a", "weth", "b"]);
+        assert_eq!(trade.pairs, vec!["0xpair_a_weth", "0xpair_weth_b"]);
+        assert_eq!(trade.token_in, "a");
+        assert_eq!(trade.token_out, "b");
+        assert_eq!(trade.amount_in, Decimal::from(103));
+        assert_eq!(trade.amount_out, Decimal::from(205));
+        assert_eq!(trade.recipient, "0xwallet");
+    }
+
+    #[test]
+    fn keeps_unrelated_swaps_in_one_tx_separate() {
+        // 同一交易内两笔互不衔接的 swap：代币不衔接，接收方也不是下一跳
+        let trades = build_trades(vec![
+            hop("0x1", 1, "0xpair_a_weth", ROUTER, "0xwallet", ("a", "weth")),
+            hop("0x1", 2, "0xpair_c_d", ROUTER, "0xwallet", ("c", "d")),
+        ]);
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].path, vec!["a", "weth"]);
+        assert_eq!(trades[1].path, vec!["c", "d"]);
+
+        // 代币衔接但换出给钱包、下一跳由其他合约调用，也不能合并
+        let trades = build_trades(vec![
+            hop("0x1", 1, "0xpair_a_weth", ROUTER, "0xwallet", ("a", "weth")),
+            hop("0x1", 2, "0xpair_weth_b", "0xother", "0xwallet", ("weth", "b")),
+        ]);
+        assert_eq!(trades.len(), 2);
+
+        // 不同交易即使首尾相接也不合并
+        let trades = build_trades(vec![
+            hop("0x1", 1, "0xpair_a_weth", ROUTER, "0xpair_weth_b", ("a", "weth")),
+            hop("0x2", 1, "0xpair_weth_b", ROUTER, "0xwallet", ("weth", "b")),
+        ]);
+        assert_eq!(trades.len(), 2);
+    }
+
+    #[test]
+    fn chains_hop_returned_to_router() {
+        // 第一跳换出回到路由合约，再由路由调用下一跳
+        let trades = build_trades(vec![
+            hop("0x1", 1, "0xpair_a_weth", ROUTER, ROUTER, ("a", "weth")),
+            hop("0x1", 4, "0xpair_weth_b", ROUTER, "0xwallet", ("weth", "b")),
+        ]);
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].path, vec!["a", "weth", "b"]);
+        assert_eq!(trades[0].log_indexes, vec![1, 4]);
+        assert_eq!(trades[0].recipient, "0xwallet");
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config::TwapConfig;
+use crate::database::operations::{TwapOperations, TwapSample};
+use crate::database::Database;
+use anyhow::{anyhow, Result};
+use ethers::{
+    contract::abigen,
+    providers::{Http, Middleware, Provider},
+    types::{Address, U64},
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+abigen!(
+    OraclePair,
+    r#"[
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+        function price0CumulativeLast() external view returns (uint256)
+        function price1CumulativeLast() external view returns (uint256)
+    ]"#
+);
+
+/// TWAP 采样服务：每轮在同一个最新区块读取 TVL 靠前交易对的累计价格和储备量，
+/// 供 `/api/pairs/:chain_id/:address/twap` 计算抗操纵的时间加权均价
+pub struct TwapService {
+    database: Arc<Database>,
+    config: TwapConfig,
+    providers: HashMap<i32, Arc<Provider<Http>>>,
+}
+
+impl TwapService {
+    pub fn new(
+        database: Arc<Database>,
+        config: TwapConfig,
+        providers: HashMap<i32, Arc<Provider<Http>>>,
+    ) -> Self {
+        Self {
+            database,
+            config,
+            providers,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "⏱️ 启动 TWAP 采样服务，间隔: {}s, 最低流动性: ${}, 每链最多: {} 个交易对, 保留: {} 小时",
+            self.config.interval_secs,
+            self.config.min_liquidity_usd,
+            self.config.max_pairs,
+            self.config.retention_hours
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for (chain_id, provider) in &self.providers {
+                if let Err(e) = self.run_chain(*chain_id, provider).await {
+                    error!("❌ 链 {} TWAP 采样失败: {}", chain_id, e);
+                }
+            }
+        }
+    }
+
+    pub async fn run_chain(&self, chain_id: i32, provider: &Arc<Provider<Http>>) -> Result<()> {
+        let pool = self.database.pool();
+
+        TwapOperations::prune_samples(pool, chain_id, self.config.retention_hours).await?;
+
+        let targets = TwapOperations::get_twap_targets(
+            pool,
+            chain_id,
+            Decimal::from(self.config.min_liquidity_usd),
+            self.config.max_pairs,
+        )
+        .await?;
+        if targets.is_empty() {
+            return Ok(());
+        }
+
+        // 同一区块读取，所有交易对的采样时间一致
+        let block_number = provider.get_block_number().await?;
+        let block = provider
+            .get_block(block_number)
+            .await?
+            .ok_or_else(|| anyhow!("区块 {} 不存在", block_number))?;
+        let block_timestamp = block.timestamp.as_u64() as i64;
+
+        let mut sampled = 0;
+        for pair_address in &targets {
+            match read_sample(provider, pair_address, block_number, block_timestamp).await {
+                Ok(sample) => {
+                    TwapOperations::insert_sample(pool, chain_id, &sample).await?;
+                    sampled += 1;
+                }
+                Err(e) => debug!("链 {} 交易对 {} TWAP 采样失败: {}", chain_id, pair_address, e),
+            }
+        }
+
+        debug!(
+            "⏱️ 链 {} 区块 {} TWAP 采样完成: {}/{} 个交易对",
+            chain_id,
+            block_number,
+            sampled,
+            targets.len()
+        );
+        Ok(())
+    }
+}
+
+async fn read_sample(
+    provider: &Arc<Provider<Http>>,
+    pair_address: &str,
+    block_number: U64,
+    block_timestamp: i64,
+) -> Result<TwapSample> {
+    let address: Address = pair_address.parse()?;
+    let pair = OraclePair::new(address, Arc::clone(provider));
+
+    let (reserve0, reserve1, block_timestamp_last) =
+        pair.get_reserves().block(block_number).call().await?;
+    let price0_cumulative_last = pair.price_0_cumulative_last().block(block_number).call().await?;
+    let price1_cumulative_last = pair.price_1_cumulative_last().block(block_number).call().await?;
+
+    Ok(TwapSample {
+        pair_address: pair_address.to_string(),
+        block_number: block_number.as_u64() as i64,
+        block_timestamp,
+        price0_cumulative_last: price0_cumulative_last.to_string(),
+        price1_cumulative_last: price1_cumulative_last.to_string(),
+        reserve0: reserve0.to_string(),
+        reserve1: reserve1.to_string(),
+        block_timestamp_last: block_timestamp_last as i64,
+    })
+}
@@ -0,0 +1,202 @@
+use crate::config::{TrendingConfig, ValuationConfig};
+use crate::database::operations::{
+    PairReserveEdge, SystemOperations, TrendingOperations, ValuationOperations, WashOperations,
+};
+use crate::database::Database;
+use crate::types::TokenUsdPrice;
+use anyhow::Result;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, info};
+
+/// USD 估值服务：从稳定币和 token_prices 锚点出发，沿交易对储备量推导所有代币的美元价格，
+/// 并据此刷新交易对 TVL、补算 swap 美元成交量和 mint/burn 美元价值、重算交易对刷量评分，
+/// 最后刷新代币统计视图并重算热门代币评分
+pub struct ValuationService {
+    database: Arc<Database>,
+    config: ValuationConfig,
+    trending: TrendingConfig,
+    /// chain_id -> 稳定币地址（小写）
+    stable_tokens: HashMap<i32, Vec<String>>,
+}
+
+impl ValuationService {
+    pub fn new(
+        database: Arc<Database>,
+        config: ValuationConfig,
+        trending: TrendingConfig,
+        stable_tokens: HashMap<i32, Vec<String>>,
+    ) -> Self {
+        Self {
+            database,
+            config,
+            trending,
+            stable_tokens,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!(
+            "💵 启动 USD 估值服务，间隔: {}s, 最低流动性: ${}, 最大跳数: {}",
+            self.config.interval_secs, self.config.min_liquidity_usd, self.config.max_hops
+        );
+
+        let mut interval = time::interval(Duration::from_secs(self.config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            for (chain_id, stables) in &self.stable_tokens {
+                if let Err(e) = self.run_chain(*chain_id, stables).await {
+                    error!("❌ 链 {} USD 估值失败: {}", chain_id, e);
+                }
+            }
+
+            // 代币统计依赖本轮的美元价格和 TVL
+            if let Err(e) = SystemOperations::refresh_materialized_views(self.database.pool()).await {
+                error!("❌ 刷新代币统计视图失败: {}", e);
+                continue;
+            }
+
+            // 热门评分的涨幅和流动性取自刚刷新的代币统计
+            for chain_id in self.stable_tokens.keys() {
+                match TrendingOperations::refresh_token_trending(self.database.pool(), *chain_id, &self.trending).await {
+                    Ok(count) => debug!("🔥 链 {} 热门代币评分完成: {} 个代币", chain_id, count),
+                    Err(e) => error!("❌ 链 {} 热门代币评分失败: {}", chain_id, e),
+                }
+            }
+        }
+    }
+
+    pub async fn run_chain(&self, chain_id: i32, stables: &[String]) -> Result<()> {
+        let pool = self.database.pool();
+
+        let mut anchors: HashMap<String, TokenUsdPrice> = HashMap::new();
+        for token in stables {
+            anchors.insert(token.clone(), anchor_price(chain_id, token, Decimal::ONE, "stable"));
+        }
+        // 外部价格优先于稳定币的 1 USD 假设（可反映脱锚）
+        for (token, price) in ValuationOperations::get_price_anchors(pool, chain_id).await? {
+            anchors.insert(token.clone(), anchor_price(chain_id, &token, price, "anchor"));
+        }
+
+        let edges = ValuationOperations::get_reserve_edges(pool, chain_id).await?;
+        let prices = derive_usd_prices(
+            anchors,
+            &edges,
+            Decimal::from(self.config.min_liquidity_usd),
+            self.config.max_hops as i32,
+        );
+
+        ValuationOperations::replace_token_usd_prices(pool, chain_id, &prices).await?;
+        ValuationOperations::refresh_reserve_usd(pool, chain_id).await?;
+
+        let since = Utc::now() - chrono::Duration::days(self.config.backfill_days as i64);
+        let swaps = ValuationOperations::backfill_swap_volume_usd(pool, chain_id, since).await?;
+        let liquidity =
+            ValuationOperations::backfill_liquidity_value_usd(pool, chain_id, since).await?;
+        // 刷量评分依赖本轮补算的美元成交量
+        let wash_pairs = WashOperations::refresh_pair_wash_stats(pool, chain_id).await?;
+
+        info!(
+            "💵 链 {} USD 估值完成: {} 个代币有价格, 补算 swap {} 条, 流动性事件 {} 条, 刷量评分 {} 个交易对",
+            chain_id,
+            prices.len(),
+            swaps,
+            liquidity,
+            wash_pairs
+        );
+
+        Ok(())
+    }
+}
+
+fn anchor_price(chain_id: i32, token: &str, price_usd: Decimal, source: &str) -> TokenUsdPrice {
+    TokenUsdPrice {
+        chain_id,
+        token_address: token.to_string(),
+        price_usd,
+        source: source.to_string(),
+        via_pair: None,
+        hops: 0,
+        liquidity_usd: None,
+    }
+}
+
+/// 按跳数逐层推导：第 n 层只从第 n-1 层已定价的代币出发，
+/// 同一代币有多条候选路径时取流动性（USD）最大的交易对，流动性低于下限的交易对不参与推导
+fn derive_usd_prices(
+    anchors: HashMap<String, TokenUsdPrice>,
+    edges: &[PairReserveEdge],
+    min_liquidity_usd: Decimal,
+    max_hops: i32,
+) -> Vec<TokenUsdPrice> {
+    let mut priced = anchors;
+
+    for hop in 1..=max_hops {
+        let mut candidates: HashMap<String, TokenUsdPrice> = HashMap::new();
+
+        for edge in edges {
+            let sides = [
+                (&edge.token0, &edge.token1, edge.reserve0, edge.reserve1),
+                (&edge.token1, &edge.token0, edge.reserve1, edge.reserve0),
+            ];
+
+            for (known, unknown, known_reserve, unknown_reserve) in sides {
+                let known_price = match priced.get(known) {
+                    Some(p) if p.hops == hop - 1 => p,
+                    _ => continue,
+                };
+                if priced.contains_key(unknown) {
+                    continue;
+                }
+
+                let liquidity_usd = match known_reserve
+                    .checked_mul(known_price.price_usd)
+                    .and_then(|v| v.checked_mul(Decimal::TWO))
+                {
+                    Some(v) if v >= min_liquidity_usd => v,
+                    _ => continue,
+                };
+                let price_usd = match known_price
+                    .price_usd
+                    .checked_mul(known_reserve)
+                    .and_then(|v| v.checked_div(unknown_reserve))
+                {
+                    Some(v) if v > Decimal::ZERO => v,
+                    _ => continue,
+                };
+
+                let better = match candidates.get(unknown).and_then(|c| c.liquidity_usd) {
+                    Some(current) => liquidity_usd > current,
+                    None => true,
+                };
+                if better {
+                    candidates.insert(
+                        unknown.clone(),
+                        TokenUsdPrice {
+                            chain_id: known_price.chain_id,
+                            token_address: unknown.clone(),
+                            price_usd,
+                            source: "route".to_string(),
+                            via_pair: Some(edge.pair_address.clone()),
+                            hops: hop,
+                            liquidity_usd: Some(liquidity_usd),
+                        },
+                    );
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+        priced.extend(candidates);
+    }
+
+    priced.into_values().collect()
+}
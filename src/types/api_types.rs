@@ -79,6 +79,32 @@ pub struct UpdateTokenMetadata {
     pub max_supply: Option<Decimal>,
 }
 
+/// 链上读取的代币供应量（按精度归一化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenSupply {
+    pub chain_id: i32,
+    pub token_address: String,
+    pub total_supply: Decimal,
+    /// totalSupply 扣除排除地址余额
+    pub circulating_supply: Decimal,
+    pub excluded_supply: Decimal,
+    pub block_number: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 不计入流通量的持有地址
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplyExclusion {
+    pub holder_address: String,
+    /// team / treasury / burn / locked 等说明
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSupplyExclusions {
+    pub exclusions: Vec<SupplyExclusion>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeSeriesData {
     pub timestamp: DateTime<Utc>,
@@ -96,8 +122,14 @@ pub struct TradeRecord {
     pub token0_decimals: Option<i32>,
     pub token1_decimals: Option<i32>,
     pub transaction_hash: String,
+    /// 交易发起人 (tx.from)
     pub sender: String,
+    /// 事件中的 sender，通常为路由合约；历史数据为 None
+    pub event_sender: Option<String>,
     pub to_address: String,
+    /// event_sender 是否为已知路由 / 聚合器合约
+    pub via_router: bool,
+    pub router_label: Option<String>,
     pub amount0_in: Decimal,
     pub amount1_in: Decimal,
     pub amount0_out: Decimal,
@@ -105,6 +137,10 @@ pub struct TradeRecord {
     pub price: Decimal,
     pub trade_type: String,
     pub volume_usd: Option<Decimal>,
+    /// 该 swap 在夹子攻击中的角色：frontrun / victim / backrun，未被识别时为 None
+    pub mev_role: Option<String>,
+    /// 风险代币的转账税率（0-1），实际到账数量 = 成交数量 × (1 - 税率)；未检测到时为 None
+    pub transfer_tax_rate: Option<Decimal>,
     pub block_number: i64,
     pub timestamp: DateTime<Utc>,
 }
@@ -145,56 +181,264 @@ pub struct WalletTransaction {
     pub token1_decimals: Option<i32>,
     pub price: Option<Decimal>,
     pub value_usd: Option<Decimal>,
+    /// 交易发起人 (tx.from)
+    pub tx_origin: String,
+    /// 事件中的 sender，历史数据为 None
+    pub event_sender: Option<String>,
+    /// 代币接收方
+    pub to_address: Option<String>,
+    /// event_sender 是否为已知路由 / 聚合器合约
+    pub via_router: bool,
+    pub router_label: Option<String>,
+    /// 该 swap 所属的逻辑成交（多跳成交中的一跳），尚未重建时为 None
+    pub trade: Option<TradeSummary>,
     pub block_number: i64,
     pub timestamp: DateTime<Utc>,
 }
 
+/// swap 所属逻辑成交的概要，数量已按精度归一化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSummary {
+    pub hop_count: i32,
+    /// 该 swap 在路径中的位置，从 1 开始
+    pub hop_index: i32,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Decimal,
+}
+
+/// 由同一交易内首尾相接的 swap 重建的逻辑成交，如 A -> WETH -> B 记为一笔 A 换 B。
+/// 数量已按精度归一化，地址小写
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub chain_id: i32,
+    pub transaction_hash: String,
+    pub log_indexes: Vec<i32>,
+    /// 交易发起人 (tx.from)
+    pub tx_origin: String,
+    /// 第一跳事件中的 sender
+    pub event_sender: Option<String>,
+    pub via_router: bool,
+    pub router_label: Option<String>,
+    /// 最后一跳的代币接收方
+    pub recipient: String,
+    pub token_in: String,
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Decimal,
+    pub token_out: String,
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Decimal,
+    /// 代币路径，长度为 hop_count + 1
+    pub path: Vec<String>,
+    pub pairs: Vec<String>,
+    pub hop_count: i32,
+    /// 第一跳的美元成交额，未定价时取后续已定价的一跳
+    pub volume_usd: Option<Decimal>,
+    /// 各跳手续费之和（USD）
+    pub fees_usd: Decimal,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 一次夹子攻击：攻击者在同一区块同一交易对内先于受害者同向买入（frontrun），再反向卖出（backrun）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sandwich {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub token0_symbol: Option<String>,
+    pub token1_symbol: Option<String>,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    /// 攻击者（frontrun 的交易发起人）
+    pub attacker: String,
+    pub frontrun_tx: String,
+    pub frontrun_log_index: i32,
+    pub backrun_tx: String,
+    pub backrun_log_index: i32,
+    pub victims: Vec<SandwichVictim>,
+    pub victim_volume_usd: Option<Decimal>,
+    /// 攻击者获利的代币（frontrun 投入的代币），数量已按精度归一化
+    pub profit_token: String,
+    pub profit_token_symbol: Option<String>,
+    pub profit_amount: Decimal,
+    pub profit_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandwichVictim {
+    pub transaction_hash: String,
+    pub log_index: i32,
+    pub wallet: String,
+    pub volume_usd: Option<Decimal>,
+}
+
+/// 撤池 / 跑路告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: Uuid,
+    pub chain_id: i32,
+    pub pair_address: String,
+    /// large_burn / reserve_drop / lp_concentration
+    pub alert_type: String,
+    /// warning / critical
+    pub severity: String,
+    /// 相关钱包（撤池者 / 部署者）
+    pub wallet: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub message: String,
+    /// 触发告警的指标，字段随 alert_type 不同
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 时间窗口内的成交广度，钱包为交易发起人，美元成交量包含估值服务补算的部分
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeActivity {
+    pub unique_traders: i64,
+    pub unique_buyers: i64,
+    pub unique_sellers: i64,
+    pub buy_count: i64,
+    pub sell_count: i64,
+    pub buy_volume_usd: Decimal,
+    pub sell_volume_usd: Decimal,
+}
+
+/// 按 5 分钟桶聚合，窗口包含与之重叠的桶
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeActivityWindows {
+    #[serde(rename = "5m")]
+    pub m5: TradeActivity,
+    #[serde(rename = "1h")]
+    pub h1: TradeActivity,
+    #[serde(rename = "6h")]
+    pub h6: TradeActivity,
+    #[serde(rename = "24h")]
+    pub h24: TradeActivity,
+}
+
+/// 代币维度的列表项，统计覆盖代币参与的所有交易对
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenListItem {
     pub rank: i32,
     pub chain_id: i32,
     pub chain_name: String,
-    pub pair_address: String,
-    pub token0_symbol: String,
-    pub token1_symbol: String,
-    pub token0_name: String,
-    pub token1_name: String,
-    pub token0_logo_url: Option<String>,
-    pub token1_logo_url: Option<String>,
-    pub token0_website_url: Option<String>,
-    pub token1_website_url: Option<String>,
-    pub token0_explorer_url: Option<String>,
-    pub token1_explorer_url: Option<String>,
-    pub token0_description: Option<String>,
-    pub token1_description: Option<String>,
-    pub token0_tags: Option<Vec<String>>,
-    pub token1_tags: Option<Vec<String>>,
+    pub token_address: String,
+    pub symbol: String,
+    pub name: String,
+    pub logo_url: Option<String>,
+    pub website_url: Option<String>,
+    pub description: Option<String>,
+    /// 各池隐含美元价格按池 TVL 加权
     pub price_usd: Decimal,
+    /// 涨跌幅（%），基于流动性最大的池的 1m K线
     pub price_change_1h: Decimal,
     pub price_change_24h: Decimal,
+    pub price_change_7d: Decimal,
+    /// 所有交易对的美元成交量之和
     pub volume_1h: Decimal,
     pub volume_24h: Decimal,
+    /// 各交易对扣除刷量部分后的 24 小时成交量之和
+    pub organic_volume_24h: Decimal,
+    /// 按成交量加权的刷量评分（0-100）
+    pub wash_score: Decimal,
+    /// 首个以该代币为非计价方的交易对的风险分（0-100），尚未评分时为 None
+    pub risk_score: Option<i32>,
+    /// no_code / proxy / mintable / blacklist / pausable / fee_on_transfer / lp_unlocked
+    pub risk_flags: Vec<String>,
+    pub trade_count_24h: i64,
+    /// 买入 = 换入该代币，同一钱包在多个交易对交易只计一次
+    pub activity: TradeActivityWindows,
+    pub pair_count: i32,
     pub fdv: Option<Decimal>,
     pub market_cap: Option<Decimal>,
+    /// 所有交易对的美元 TVL 之和
     pub liquidity: Decimal,
     pub last_updated: DateTime<Utc>,
 }
 
+/// 热门代币评分，各分项归一化到 0-1，score = 100 × 加权和 / 权重和
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingScore {
+    pub score: Decimal,
+    /// 最近 1 小时自然成交量（扣除刷量部分）
+    pub volume_1h_usd: Decimal,
+    /// 此前 24 小时平均每小时自然成交量
+    pub volume_baseline_usd: Decimal,
+    /// volume_1h_usd / volume_baseline_usd
+    pub volume_acceleration: Decimal,
+    pub traders_1h: i64,
+    /// 此前 24 小时平均每小时独立交易者
+    pub traders_baseline: Decimal,
+    /// traders_1h / traders_baseline
+    pub trader_growth: Decimal,
+    pub price_change_1h: Decimal,
+    pub liquidity_usd: Decimal,
+    pub volume_component: Decimal,
+    pub trader_component: Decimal,
+    pub price_component: Decimal,
+    pub liquidity_component: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingToken {
+    /// 展开为代币列表字段，与 `/api/tokens` 的每项结构一致
+    #[serde(flatten)]
+    pub token: TokenListItem,
+    pub trending: TrendingScore,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenDetail {
-    pub metadata: TokenMetadata,
+    pub chain_id: i32,
+    pub token_address: String,
+    pub metadata: Option<TokenMetadata>,
     pub price_info: Option<TokenPriceInfo>,
     pub trading_pairs: Vec<TradingPairInfo>,
+    /// 转账税 / rebase 检测结果，尚未采样时为 None
+    pub transfer_tax: Option<TokenTransferTax>,
+}
+
+/// 代币转账税检测结果，税率为 0-1 的小数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenTransferTax {
+    /// 最近一次从交易对转出（买入）时的税率
+    pub buy_tax_rate: Option<Decimal>,
+    /// 最近一次转入交易对（卖出）时的税率
+    pub sell_tax_rate: Option<Decimal>,
+    pub max_tax_rate: Decimal,
+    pub sampled_swaps: i64,
+    pub taxed_swaps: i64,
+    /// 储备量多次在 swap / mint / burn 之外变化，疑似 rebase 或反射代币
+    pub rebasing: bool,
+    pub rebase_events: i64,
+    pub last_sampled_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPriceInfo {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
     pub current_price: Decimal,
     pub price_change_1h: Decimal,
     pub price_change_24h: Decimal,
     pub price_change_7d: Decimal,
+    pub volume_1h: Decimal,
     pub volume_24h: Decimal,
+    pub trade_count_24h: i64,
+    pub pair_count: i32,
+    pub liquidity: Decimal,
+    /// 涨跌幅所依据的交易对
+    pub main_pair: Option<String>,
+    pub total_supply: Option<Decimal>,
+    pub circulating_supply: Option<Decimal>,
+    /// 美元价格 × 流通量
     pub market_cap: Option<Decimal>,
+    /// 美元价格 × 最大供应量（未配置时取链上 totalSupply）
     pub fdv: Option<Decimal>,
     pub last_updated: DateTime<Utc>,
 }
@@ -202,10 +446,15 @@ pub struct TokenPriceInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingPairInfo {
     pub pair_address: String,
+    pub other_token_address: String,
     pub other_token_symbol: String,
     pub other_token_name: String,
+    /// 以对手代币计的最新成交价
     pub price: Decimal,
+    /// 由储备量和对手代币美元价推算的本池美元价格
+    pub price_usd: Option<Decimal>,
     pub volume_24h: Decimal,
+    pub trade_count_24h: i64,
     pub liquidity: Decimal,
 }
 
@@ -215,11 +464,26 @@ pub struct PairStats {
     pub chain_id: i32,
     pub token0_symbol: String,
     pub token1_symbol: String,
+    /// 计价代币地址，price / volume_24h 均以它计
+    pub quote_token: String,
     pub price: Decimal,
     pub volume_24h: Decimal,
+    /// 美元 TVL
     pub liquidity: Decimal,
+    /// 涨跌幅（%），基于 1m K线，按计价方向计算
+    pub price_change_1h: Decimal,
     pub price_change_24h: Decimal,
+    pub price_change_7d: Decimal,
     pub tx_count_24h: i64,
+    /// 买入 = 用计价代币买入基础代币
+    pub activity: TradeActivityWindows,
+    /// 工厂手续费率（基点）
+    pub fee_bps: i32,
+    /// LP 手续费收入（USD）
+    pub fees_24h: Decimal,
+    pub fees_7d: Decimal,
+    /// 手续费年化收益率（%），按近 7 天手续费年化除以当前 TVL；流动性过低或溢出时为 None
+    pub fee_apr: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -234,15 +498,176 @@ pub struct PairDetail {
     pub token1_name: Option<String>,
     pub token0_decimals: Option<i32>,
     pub token1_decimals: Option<i32>,
+    /// 计价代币地址，价格与成交量均以它计
+    pub quote_token: String,
     pub current_price: Decimal,
     pub volume_24h: Decimal,
     pub volume_7d: Decimal,
+    /// 美元 TVL
     pub liquidity: Decimal,
+    /// 涨跌幅（%），基于 1m K线，按计价方向计算
+    pub price_change_1h: Decimal,
     pub price_change_24h: Decimal,
     pub price_change_7d: Decimal,
     pub tx_count_24h: i64,
     pub tx_count_7d: i64,
     pub created_at: DateTime<Utc>,
+    /// 各时间窗口的 LP 手续费年化收益
+    pub fee_apr: Vec<PairFeeApr>,
+    /// 近 24 小时刷量指标，估值服务尚未计算或窗口内没有已定价成交时为 None
+    pub wash: Option<PairWashStats>,
+}
+
+/// 交易对近 24 小时的刷量指标，只统计已定价的 swap，钱包为交易发起人
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairWashStats {
+    pub volume_usd: Decimal,
+    /// 扣除刷量评分部分后的成交量：volume_usd × (1 - wash_score / 100)
+    pub organic_volume_usd: Decimal,
+    pub trade_count: i64,
+    pub trader_count: i64,
+    /// 同一钱包来回买卖的成交量：每个钱包 2 × min(买入额, 卖出额) 之和
+    pub round_trip_volume_usd: Decimal,
+    /// 接收方为另一个反向交易钱包的成交量（资金在关联钱包间循环）
+    pub circular_volume_usd: Decimal,
+    /// 成交量最大的 1 / 5 个钱包占比（0-1）
+    pub top_wallet_share: Decimal,
+    pub top5_wallet_share: Decimal,
+    /// 0-100，越高越可能是刷量
+    pub wash_score: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 某个时间窗口内的 LP 手续费收入及按当前 TVL 折算的年化收益
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairFeeApr {
+    /// 窗口标识，如 24h / 7d / 30d
+    pub window: String,
+    pub fees_usd: Decimal,
+    pub volume_usd: Decimal,
+    /// 实际统计的天数，交易对上线不足一个窗口时按上线时长计
+    pub days: Decimal,
+    /// 年化收益（%），流动性过低或溢出时为 None
+    pub apr: Option<Decimal>,
+}
+
+/// 由累计价格采样计算的时间加权均价，窗口内采样不足时价格为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapWindow {
+    /// 窗口标识，如 5m / 1h / 24h
+    pub window: String,
+    /// 实际覆盖的秒数，不小于窗口长度（起点取窗口起始时刻及之前最近的采样）
+    pub seconds: Option<i64>,
+    pub start_block: Option<i64>,
+    pub end_block: Option<i64>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// token0 以 token1 计价
+    pub price0: Option<Decimal>,
+    /// token1 以 token0 计价
+    pub price1: Option<Decimal>,
+    /// 基础代币以计价代币计价
+    pub price: Option<Decimal>,
+    /// 结束采样时的储备量价格，同样按计价方向
+    pub spot_price: Option<Decimal>,
+    /// (spot_price - price) / price × 100
+    pub deviation_pct: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairTwap {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub quote_token: String,
+    pub windows: Vec<TwapWindow>,
+}
+
+/// 报价路径中的一跳，数量按代币精度归一化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuoteHop {
+    pub pair_address: String,
+    pub token_in: String,
+    pub token_out: String,
+    pub amount_in: Decimal,
+    pub amount_out: Decimal,
+    pub fee_bps: i32,
+    /// 本跳成交价相对中间价（扣除手续费后）的偏离（%）
+    pub price_impact_pct: Decimal,
+}
+
+/// 基于已记录储备量模拟的兑换报价，不含转账税和链上最新状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuote {
+    pub chain_id: i32,
+    pub token_in: String,
+    pub token_out: String,
+    /// exact_in：给定输入求最大输出；exact_out：给定输出求最小输入
+    pub kind: String,
+    pub amount_in: Decimal,
+    pub amount_out: Decimal,
+    /// 原始整数数量（十进制字符串）
+    pub amount_in_raw: String,
+    pub amount_out_raw: String,
+    /// 每单位 token_in 得到的 token_out
+    pub execution_price: Decimal,
+    /// 沿路径各池储备量比的乘积，不含手续费
+    pub mid_price: Decimal,
+    /// 成交价相对中间价（扣除手续费后）的偏离（%）
+    pub price_impact_pct: Decimal,
+    /// 沿路径累计的 LP 手续费率（%）
+    pub lp_fee_pct: Decimal,
+    pub route: Vec<SwapQuoteHop>,
+}
+
+/// 循环套利机会：从 start_token 出发沿环路兑换回 start_token，按检测时的储备量估算最优投入和利润
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageOpportunity {
+    pub id: Uuid,
+    pub chain_id: i32,
+    /// 检测时已处理到的区块
+    pub block_number: i64,
+    pub start_token: String,
+    /// 按兑换顺序经过的交易对
+    pub pairs: Vec<String>,
+    /// 使利润最大的投入数量（start_token）
+    pub amount_in: Decimal,
+    pub amount_out: Decimal,
+    /// 扣除手续费和价格冲击后的利润（start_token），未扣除 gas
+    pub profit: Decimal,
+    pub profit_usd: Decimal,
+    pub amount_in_usd: Decimal,
+    /// 无限小投入时扣除手续费后的环路收益率（%）
+    pub spread_pct: Decimal,
+    /// 利润 / 投入（%）
+    pub return_pct: Decimal,
+    pub route: Vec<SwapQuoteHop>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// 巨鲸交易：美元价值或占池子储备比例达到阈值的 swap / mint / burn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhaleTrade {
+    pub id: Uuid,
+    pub chain_id: i32,
+    pub pair_address: String,
+    /// swap / mint / burn
+    pub event_type: String,
+    /// swap 的原始方向（buy = token0 换 token1），mint / burn 为 None
+    pub side: Option<String>,
+    /// 交易发起人
+    pub wallet: String,
+    /// 按精度归一化的 token0 / token1 数量（swap 为净流入或流出数量）
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub value_usd: Option<Decimal>,
+    /// 占事件前对应一侧储备量的比例（%），取两侧较大者
+    pub reserve_pct: Option<Decimal>,
+    /// 达到的阈值：usd / reserve / both
+    pub trigger: String,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub log_index: i32,
+    pub timestamp: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -250,7 +675,9 @@ pub struct ChainStats {
     pub chain_id: i32,
     pub chain_name: String,
     pub total_pairs: i64,
+    /// 24 小时美元成交量
     pub total_volume_24h: Decimal,
+    /// 链上所有交易对的美元 TVL
     pub total_liquidity: Decimal,
     pub active_pairs_24h: i64,
 }
@@ -269,29 +696,125 @@ pub struct WalletStats {
     pub last_transaction: DateTime<Utc>,
 }
 
+/// 钱包持仓，余额为 swap 记录推算的净买入数量（不含转账）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletPortfolioItem {
+    pub chain_id: i32,
     pub token_address: String,
     pub token_symbol: String,
     pub token_name: String,
     pub balance: Decimal,
     pub value_usd: Decimal,
+    /// 剩余持仓的平均成本（USD）
     pub avg_buy_price: Decimal,
     pub current_price: Decimal,
+    /// 未实现盈亏
     pub profit_loss: Decimal,
     pub profit_loss_percentage: Decimal,
 }
 
+/// 按 UTC 日汇总的盈亏，未实现盈亏以当日末最近一次成交价估值
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletPnLRecord {
     pub date: DateTime<Utc>,
+    /// 当日卖出实现的盈亏
     pub realized_pnl: Decimal,
     pub unrealized_pnl: Decimal,
+    /// 截至当日末的累计已实现 + 未实现盈亏
     pub total_pnl: Decimal,
     pub volume: Decimal,
     pub fees_paid: Decimal,
 }
 
+/// 单个代币的成本与盈亏
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTokenPnL {
+    pub chain_id: i32,
+    pub token_address: String,
+    pub token_symbol: String,
+    pub bought_amount: Decimal,
+    pub sold_amount: Decimal,
+    pub balance: Decimal,
+    /// 剩余持仓的成本（USD）
+    pub cost_basis: Decimal,
+    pub current_price: Option<Decimal>,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub total_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletPnL {
+    pub wallet_address: String,
+    pub chain_id: Option<i32>,
+    /// fifo | average
+    pub method: String,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub total_pnl: Decimal,
+    /// 盈利的卖出笔数占比（%）
+    pub win_rate: Decimal,
+    /// 缺少美元价格、未计入成本的 swap 数量
+    pub unpriced_swaps: i64,
+    pub tokens: Vec<WalletTokenPnL>,
+    pub daily: Vec<WalletPnLRecord>,
+}
+
+/// 排行榜中的一个钱包，统计范围为所选窗口内的交易对 / 代币 / 链
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub wallet_address: String,
+    /// 有交易或提供过流动性的交易对数量
+    pub pair_count: i64,
+    pub trade_count: i64,
+    pub volume_usd: Decimal,
+    pub fees_paid_usd: Decimal,
+    /// 窗口内代币净流入按当前美元价格估值
+    pub pnl_usd: Decimal,
+    pub mint_count: i64,
+    pub burn_count: i64,
+    pub liquidity_added_usd: Decimal,
+    pub liquidity_removed_usd: Decimal,
+    pub net_liquidity_usd: Decimal,
+    /// 最近活跃日（UTC 零点）
+    pub last_active_day: Option<DateTime<Utc>>,
+}
+
+/// 钱包在某个交易对的 LP 头寸，与“不做市、原样持有存入代币”对比得到无常损失
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletLiquidityPosition {
+    pub chain_id: i32,
+    pub pair_address: String,
+    pub token0: String,
+    pub token1: String,
+    pub token0_symbol: Option<String>,
+    pub token1_symbol: Option<String>,
+    /// open | closed
+    pub status: String,
+    pub first_deposit_at: DateTime<Utc>,
+    /// 累计存入 / 取出数量（已按精度归一化）
+    pub deposited0: Decimal,
+    pub deposited1: Decimal,
+    pub withdrawn0: Decimal,
+    pub withdrawn1: Decimal,
+    /// 占池子流动性的比例（%）
+    pub pool_share: Decimal,
+    /// 按当前储备计算的头寸数量（不含累积手续费）
+    pub current_amount0: Decimal,
+    pub current_amount1: Decimal,
+    /// 以下美元字段在代币缺少价格时为 None
+    pub position_value_usd: Option<Decimal>,
+    pub hold_value_usd: Option<Decimal>,
+    pub impermanent_loss_usd: Option<Decimal>,
+    /// 无常损失占持有价值的比例（%），为负表示亏损
+    pub impermanent_loss_pct: Option<Decimal>,
+    /// 按当前份额估算的首次存入以来手续费收入
+    pub fees_earned_usd: Decimal,
+    /// 手续费收入 + 无常损失，即相对持有的净收益
+    pub net_vs_hold_usd: Option<Decimal>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemHealth {
     pub status: String,
@@ -311,3 +834,24 @@ pub struct EventListenerStatus {
     pub blocks_behind: i64,
     pub last_updated: DateTime<Utc>,
 }
+
+/// 接口的计价方向参数 `?quote=token0|token1|auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteSelection {
+    Token0,
+    Token1,
+    /// 使用交易对记录的计价方（按计价代币优先级确定）
+    Auto,
+}
+
+impl QuoteSelection {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value.unwrap_or("auto") {
+            "token0" => Some(Self::Token0),
+            "token1" => Some(Self::Token1),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+}
@@ -9,7 +9,10 @@ pub struct SwapEvent {
     pub id: Uuid,
     pub chain_id: i32,
     pub pair_address: String,
+    /// 交易发起人 (tx.from)，钱包归属默认以它为准
     pub sender: String,
+    /// 事件中的 sender，通常为路由 / 聚合器合约；历史数据为 None
+    pub event_sender: Option<String>,
     pub amount0_in: Decimal,
     pub amount1_in: Decimal,
     pub amount0_out: Decimal,
@@ -19,6 +22,21 @@ pub struct SwapEvent {
     pub transaction_hash: String,
     pub log_index: i32,
     pub timestamp: DateTime<Utc>,
+    /// 按代币精度归一化后的 token0 成交数量，写入数据库时计算
+    pub amount0: Option<Decimal>,
+    /// 按代币精度归一化后的 token1 成交数量
+    pub amount1: Option<Decimal>,
+    /// 成交价：token0 以 token1 计价
+    pub price0: Option<Decimal>,
+    /// 成交价：token1 以 token0 计价
+    pub price1: Option<Decimal>,
+    /// buy: 用 token0 买入 token1；sell: 用 token1 买入 token0
+    pub side: Option<String>,
+    /// 美元成交量，按写入时的代币美元价格计算
+    pub volume_usd: Option<Decimal>,
+    /// LP 手续费，按工厂费率对输入数量收取，以输入代币计
+    pub fee_amount: Option<Decimal>,
+    pub fee_usd: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -26,13 +44,18 @@ pub struct MintEvent {
     pub id: Uuid,
     pub chain_id: i32,
     pub pair_address: String,
+    /// 交易发起人 (tx.from)，钱包归属默认以它为准
     pub sender: String,
+    /// 事件中的 sender，通常为路由 / 聚合器合约；历史数据为 None
+    pub event_sender: Option<String>,
     pub amount0: Decimal,
     pub amount1: Decimal,
     pub block_number: i64,
     pub transaction_hash: String,
     pub log_index: i32,
     pub timestamp: DateTime<Utc>,
+    /// 两侧数量的美元价值，写入数据库时按代币美元价格计算
+    pub value_usd: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -40,7 +63,10 @@ pub struct BurnEvent {
     pub id: Uuid,
     pub chain_id: i32,
     pub pair_address: String,
+    /// 交易发起人 (tx.from)，钱包归属默认以它为准
     pub sender: String,
+    /// 事件中的 sender，通常为路由 / 聚合器合约；历史数据为 None
+    pub event_sender: Option<String>,
     pub amount0: Decimal,
     pub amount1: Decimal,
     pub to_address: String,
@@ -48,6 +74,8 @@ pub struct BurnEvent {
     pub transaction_hash: String,
     pub log_index: i32,
     pub timestamp: DateTime<Utc>,
+    /// 两侧数量的美元价值，写入数据库时按代币美元价格计算
+    pub value_usd: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
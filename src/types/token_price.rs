@@ -39,4 +39,19 @@ pub struct PricePoint {
     pub timestamp: DateTime<Utc>,
     pub price_usd: Decimal,
     pub source: String,
-}
\ No newline at end of file
+}
+/// 由交易对图推导出的代币美元价格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsdPrice {
+    pub chain_id: i32,
+    pub token_address: String,
+    pub price_usd: Decimal,
+    /// stable = 稳定币, anchor = token_prices 外部价格, route = 经交易对推导
+    pub source: String,
+    /// 推导所经过的交易对，锚点为 None
+    pub via_pair: Option<String>,
+    /// 距离锚点的跳数，锚点为 0
+    pub hops: i32,
+    /// 推导所用交易对的美元流动性
+    pub liquidity_usd: Option<Decimal>,
+}